@@ -0,0 +1,203 @@
+use std::ops::{Add, Mul};
+
+use crate::{
+    canvas::Canvas,
+    debug::false_color,
+    intersections::{hit, prepare_computations, Precomputation},
+    medium::{has_media, march},
+    occlusion::point_rng,
+    ray::Ray,
+    world::{intersect_world, shade_hit, shade_terms, World},
+    Color,
+};
+
+// Extra buffers that can be rendered alongside the beauty image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aov {
+    // Surface color before lighting
+    Albedo,
+    // Raw world-space shading normal, components in [-1, 1]
+    Normal,
+    // Distance to the first hit in every channel, 0 where nothing was hit
+    Depth,
+    // A distinct color per entry in `World::objects`, so it antialiases like a matte
+    ObjectId,
+    // The `light` term of `shade_hit`: ambient, diffuse and specular, plus the
+    // background and light scattered by media. Integrators that don't split
+    // their radiance put all of it here.
+    Direct,
+    // The reflected term, already weighted by reflectivity and Fresnel
+    Reflection,
+    // The refracted term, already weighted by transparency and Fresnel
+    Refraction,
+}
+
+// Every buffer for one camera ray. Samples are filtered like colors, so each
+// buffer gets the same antialiasing as the beauty image, and the lighting
+// buffers add up to it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AovSample {
+    pub albedo: Color,
+    pub normal: Color,
+    pub depth: Color,
+    pub object_id: Color,
+    pub direct: Color,
+    pub reflection: Color,
+    pub refraction: Color,
+}
+
+impl AovSample {
+    pub fn black() -> Self {
+        let black = Color::new(0., 0., 0.);
+        AovSample {
+            albedo: black,
+            normal: black,
+            depth: black,
+            object_id: black,
+            direct: black,
+            reflection: black,
+            refraction: black,
+        }
+    }
+
+    pub fn get(&self, aov: Aov) -> Color {
+        match aov {
+            Aov::Albedo => self.albedo,
+            Aov::Normal => self.normal,
+            Aov::Depth => self.depth,
+            Aov::ObjectId => self.object_id,
+            Aov::Direct => self.direct,
+            Aov::Reflection => self.reflection,
+            Aov::Refraction => self.refraction,
+        }
+    }
+}
+
+impl Add for AovSample {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        AovSample {
+            albedo: self.albedo + rhs.albedo,
+            normal: self.normal + rhs.normal,
+            depth: self.depth + rhs.depth,
+            object_id: self.object_id + rhs.object_id,
+            direct: self.direct + rhs.direct,
+            reflection: self.reflection + rhs.reflection,
+            refraction: self.refraction + rhs.refraction,
+        }
+    }
+}
+
+impl Mul<f32> for AovSample {
+    type Output = Self;
+
+    fn mul(self, rhs: f32) -> Self {
+        AovSample {
+            albedo: self.albedo * rhs,
+            normal: self.normal * rhs,
+            depth: self.depth * rhs,
+            object_id: self.object_id * rhs,
+            direct: self.direct * rhs,
+            reflection: self.reflection * rhs,
+            refraction: self.refraction * rhs,
+        }
+    }
+}
+
+fn features(world: &World, comps: &Precomputation) -> AovSample {
+    let object = &comps.object;
+    let n = comps.normalv;
+    AovSample {
+        albedo: object.material().color_at(object, comps.over_point),
+        normal: Color::new(n.x, n.y, n.z),
+        depth: Color::new(comps.t, comps.t, comps.t),
+        object_id: match world.objects.iter().position(|o| o == object) {
+            Some(index) => false_color(index),
+            None => Color::new(0., 0., 0.),
+        },
+        ..AovSample::black()
+    }
+}
+
+// Only the buffers read straight off the first hit, leaving the lighting
+// buffers black. Much cheaper than `trace_aovs` since nothing is shaded.
+pub fn trace_features(world: &World, ray: Ray) -> AovSample {
+    let intersections = intersect_world(world, ray);
+    let Some(intersection) = hit(intersections.clone()) else {
+        return AovSample::black();
+    };
+    features(
+        world,
+        &prepare_computations(intersection, ray, intersections),
+    )
+}
+
+// Shade one camera ray the way the Whitted tracer does, returning its color
+// from `color_at` together with the terms that make it up
+pub fn trace_aovs(world: &World, ray: Ray, remaining: u16) -> (Color, AovSample) {
+    let intersections = intersect_world(world, ray);
+    let (color, sample, distance) = match hit(intersections.clone()) {
+        Some(intersection) => {
+            let t = intersection.t;
+            let comps = prepare_computations(intersection, ray, intersections);
+            let features = features(world, &comps);
+            let material = comps.object.material();
+            if material.holdout || material.shadow_catcher {
+                let color = shade_hit(world, comps, remaining);
+                (
+                    color,
+                    AovSample {
+                        direct: color,
+                        ..features
+                    },
+                    t,
+                )
+            } else {
+                let terms = shade_terms(world, comps, remaining);
+                let sample = AovSample {
+                    direct: terms.light,
+                    reflection: terms.reflected,
+                    refraction: terms.refracted,
+                    ..features
+                };
+                (terms.light + terms.reflected + terms.refracted, sample, t)
+            }
+        }
+        None => {
+            let color = world.background.color_at(ray.direction);
+            let sample = AovSample {
+                direct: color,
+                ..AovSample::black()
+            };
+            (color, sample, f32::INFINITY)
+        }
+    };
+    if !has_media(world) {
+        return (color, sample);
+    }
+
+    let mut rng = point_rng(ray.origin + ray.direction);
+    let (scattered, transmittance) = march(world, ray, distance, &mut rng);
+    let sample = AovSample {
+        direct: sample.direct * transmittance + scattered,
+        reflection: sample.reflection * transmittance,
+        refraction: sample.refraction * transmittance,
+        ..sample
+    };
+    (color * transmittance + scattered, sample)
+}
+
+pub struct AovImages {
+    pub beauty: Canvas,
+    pub buffers: Vec<(Aov, Canvas)>,
+}
+
+impl AovImages {
+    pub fn get(&self, aov: Aov) -> Option<&Canvas> {
+        self.buffers
+            .iter()
+            .find(|(kind, _)| *kind == aov)
+            .map(|(_, canvas)| canvas)
+    }
+}
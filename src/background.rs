@@ -0,0 +1,120 @@
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+use crate::{
+    canvas::Canvas,
+    normalize,
+    sky::PreethamSky,
+    uv::{cube_uv, face_from_point, uv_sample, CubeFace},
+    Color, Tuple,
+};
+
+// One image per face of the surrounding cube, oriented as in `uv::cube_uv`
+#[derive(Debug, Clone)]
+pub struct CubeMap {
+    pub left: Arc<Canvas>,
+    pub right: Arc<Canvas>,
+    pub front: Arc<Canvas>,
+    pub back: Arc<Canvas>,
+    pub up: Arc<Canvas>,
+    pub down: Arc<Canvas>,
+}
+
+impl PartialEq for CubeMap {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.left, &other.left)
+            && Arc::ptr_eq(&self.right, &other.right)
+            && Arc::ptr_eq(&self.front, &other.front)
+            && Arc::ptr_eq(&self.back, &other.back)
+            && Arc::ptr_eq(&self.up, &other.up)
+            && Arc::ptr_eq(&self.down, &other.down)
+    }
+}
+
+impl CubeMap {
+    pub fn face(&self, face: CubeFace) -> &Canvas {
+        match face {
+            CubeFace::Left => &self.left,
+            CubeFace::Right => &self.right,
+            CubeFace::Front => &self.front,
+            CubeFace::Back => &self.back,
+            CubeFace::Up => &self.up,
+            CubeFace::Down => &self.down,
+        }
+    }
+}
+
+// What a ray sees when it leaves the scene without hitting anything
+#[derive(Debug, Clone)]
+pub enum Background {
+    Color(Color),
+    // Blends from `bottom` straight down to `top` straight up
+    Gradient { bottom: Color, top: Color },
+    // Latitude-longitude image, laid out like the equirectangular camera's output
+    Equirectangular(Arc<Canvas>),
+    CubeMap(CubeMap),
+    // Analytic daylight sky
+    Sky(PreethamSky),
+}
+
+impl PartialEq for Background {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Background::Color(a), Background::Color(b)) => a == b,
+            (
+                Background::Gradient { bottom, top },
+                Background::Gradient {
+                    bottom: other_bottom,
+                    top: other_top,
+                },
+            ) => bottom == other_bottom && top == other_top,
+            (Background::Equirectangular(a), Background::Equirectangular(b)) => Arc::ptr_eq(a, b),
+            (Background::CubeMap(a), Background::CubeMap(b)) => a == b,
+            (Background::Sky(a), Background::Sky(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Default for Background {
+    fn default() -> Self {
+        Background::Color(Color::new(0., 0., 0.))
+    }
+}
+
+// Inverse of the equirectangular camera projection
+pub fn equirectangular_uv(direction: Tuple) -> (f32, f32) {
+    let d = normalize(direction);
+    let longitude = f32::atan2(d.x, -d.z);
+    let latitude = f32::asin(d.y.clamp(-1., 1.));
+    (0.5 - longitude / (2. * PI), 0.5 + latitude / PI)
+}
+
+impl Background {
+    pub fn color_at(&self, direction: Tuple) -> Color {
+        match self {
+            Background::Color(color) => *color,
+            Background::Gradient { bottom, top } => {
+                let t = 0.5 * (normalize(direction).y + 1.);
+                *bottom * (1. - t) + *top * t
+            }
+            Background::Equirectangular(canvas) => {
+                let (u, v) = equirectangular_uv(direction);
+                uv_sample(canvas, u, v)
+            }
+            Background::CubeMap(cube) => {
+                // Push the direction out onto the unit cube
+                let scale = direction
+                    .x
+                    .abs()
+                    .max(direction.y.abs())
+                    .max(direction.z.abs());
+                let p = direction * (1. / scale);
+                let face = face_from_point(p);
+                let (u, v) = cube_uv(face, p);
+                uv_sample(cube.face(face), u, v)
+            }
+            Background::Sky(sky) => sky.radiance(direction),
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use std::sync::Arc;
+
+use crate::{
+    canvas::Canvas,
+    cross, dot,
+    matrix::Matrix,
+    normalize,
+    patterns::{pattern_at_object, Pattern},
+    uv::{uv_sample, UvMapping},
+    vector,
+    world::ShapeEnum,
+    Tuple,
+};
+
+const BUMP_EPSILON: f32 = 1e-3;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BumpMap {
+    pub pattern: Pattern,
+    pub scale: f32,
+}
+
+impl BumpMap {
+    pub fn new(pattern: Pattern, scale: f32) -> Self {
+        Self { pattern, scale }
+    }
+
+    // Treat the pattern luminance as a height field and tilt the normal
+    // against its gradient, estimated with central differences
+    pub fn perturb(&self, object: &ShapeEnum, point: Tuple, normal: Tuple) -> Tuple {
+        let height = |p: Tuple| pattern_at_object(&self.pattern, object, p).luminance();
+        let axes = [
+            vector(BUMP_EPSILON, 0., 0.),
+            vector(0., BUMP_EPSILON, 0.),
+            vector(0., 0., BUMP_EPSILON),
+        ];
+        let [dx, dy, dz] =
+            axes.map(|axis| (height(point + axis) - height(point - axis)) / (2. * BUMP_EPSILON));
+
+        let gradient = vector(dx, dy, dz);
+        let surface_gradient = gradient - normal * dot(gradient, normal);
+        normalize(normal - surface_gradient * self.scale)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NormalMap {
+    pub canvas: Arc<Canvas>,
+    pub mapping: UvMapping,
+    pub strength: f32,
+}
+
+impl NormalMap {
+    pub fn new(canvas: Arc<Canvas>, mapping: UvMapping) -> Self {
+        Self {
+            canvas,
+            mapping,
+            strength: 1.,
+        }
+    }
+
+    // Sample a tangent space normal and rotate it into world space using the
+    // directions in which u and v increase across the surface
+    pub fn perturb(
+        &self,
+        object: &ShapeEnum,
+        point: Tuple,
+        normal: Tuple,
+    ) -> Result<Tuple, &'static str> {
+        let inverse = object.transform().inverse()?;
+        let Some((tangent, bitangent)) = self.tangent_frame(inverse, point, normal) else {
+            return Ok(normal);
+        };
+
+        let (u, v) = self.uv_at(inverse, point);
+        let texel = uv_sample(&self.canvas, u, v);
+        let x = (texel.red * 2. - 1.) * self.strength;
+        let y = (texel.green * 2. - 1.) * self.strength;
+        let z = texel.blue * 2. - 1.;
+
+        Ok(normalize(tangent * x + bitangent * y + normal * z))
+    }
+
+    fn uv_at(&self, inverse: Matrix<f32, 4, 4>, point: Tuple) -> (f32, f32) {
+        self.mapping.map(inverse * point)
+    }
+
+    fn tangent_frame(
+        &self,
+        inverse: Matrix<f32, 4, 4>,
+        point: Tuple,
+        normal: Tuple,
+    ) -> Option<(Tuple, Tuple)> {
+        let helper = if normal.x.abs() > 0.9 {
+            vector(0., 1., 0.)
+        } else {
+            vector(1., 0., 0.)
+        };
+        let e1 = normalize(cross(normal, helper));
+        let e2 = cross(normal, e1);
+
+        // Wrap differences so seams in the mapping don't produce huge derivatives
+        let wrap = |d: f32| d - d.round();
+        let derivative = |axis: Tuple| {
+            let (u0, v0) = self.uv_at(inverse, point - axis * BUMP_EPSILON);
+            let (u1, v1) = self.uv_at(inverse, point + axis * BUMP_EPSILON);
+            (wrap(u1 - u0), wrap(v1 - v0))
+        };
+        let (du1, dv1) = derivative(e1);
+        let (du2, dv2) = derivative(e2);
+
+        let det = du1 * dv2 - du2 * dv1;
+        if det.abs() < 1e-12 {
+            return None;
+        }
+
+        let tangent = e1 * dv2 - e2 * dv1;
+        let bitangent = e2 * du1 - e1 * du2;
+        let tangent = tangent * det.signum();
+        let bitangent = bitangent * det.signum();
+
+        let tangent = normalize(tangent - normal * dot(tangent, normal));
+        let bitangent =
+            bitangent - normal * dot(bitangent, normal) - tangent * dot(bitangent, tangent);
+        Some((tangent, normalize(bitangent)))
+    }
+}
+
+impl PartialEq for NormalMap {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.canvas, &other.canvas)
+            && self.mapping == other.mapping
+            && self.strength == other.strength
+    }
+}
+
+pub fn perturb_normal(
+    object: &ShapeEnum,
+    point: Tuple,
+    normal: Tuple,
+) -> Result<Tuple, &'static str> {
+    let material = object.material();
+    let mut normal = normal;
+    if let Some(normal_map) = &material.normal_map {
+        normal = normal_map.perturb(object, point, normal)?;
+    }
+    if let Some(bump) = &material.bump_map {
+        normal = bump.perturb(object, point, normal);
+    }
+    Ok(normal)
+}
@@ -1,13 +1,45 @@
+use std::{
+    collections::HashMap,
+    f32::consts::PI,
+    ops::{Add, Mul},
+};
+
 use crate::{
-    canvas::{write_pixel, Canvas},
+    aov::{Aov, AovImages, AovSample},
+    canvas::{write_alpha, write_pixel, Canvas},
+    compositing::{has_compositing_objects, shadow_density},
+    debug::{DebugView, RenderMode},
+    filters::Filter,
+    integrators::{Integrator, Whitted},
+    intersections::{hit, prepare_computations},
     matrix::Matrix,
     normalize, point,
     ray::Ray,
-    world::{color_at, World},
+    sampling::{concentric_sample_disk, pixel_samples, sample_polygon, SamplePattern},
+    vector,
+    world::{intersect_world, World},
     Color,
 };
+use rand::{rngs::SmallRng, Rng, SeedableRng};
 use rayon::prelude::*;
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Aperture {
+    Disk,
+    // Polygonal bokeh with the given number of blades
+    Polygon(u32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Projection {
+    Perspective,
+    Orthographic { view_width: f32 },
+    // Equidistant fisheye covering field_of_view across the inscribed image circle
+    Fisheye { field_of_view: f32 },
+    // Full 360x180 degree latitude/longitude panorama
+    Equirectangular,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct Camera {
     pub hsize: usize,
@@ -17,6 +49,11 @@ pub struct Camera {
     pub half_width: f32,
     pub half_height: f32,
     pub transform: Matrix<f32, 4, 4>,
+    pub aperture_radius: f32,
+    pub focal_distance: f32,
+    pub aperture: Aperture,
+    pub lens_samples: usize,
+    pub projection: Projection,
 }
 
 impl Camera {
@@ -47,43 +84,737 @@ impl Camera {
             half_width,
             half_height,
             transform,
+            aperture_radius: 0.,
+            focal_distance: 1.,
+            aperture: Aperture::Disk,
+            lens_samples: 1,
+            projection: Projection::Perspective,
         }
     }
+
+    pub fn orthographic(hsize: usize, vsize: usize, view_width: f32) -> Self {
+        let mut camera = Camera::new(hsize, vsize, PI / 2.);
+        camera.projection = Projection::Orthographic { view_width };
+        camera.pixel_size = view_width / hsize as f32;
+        camera.half_width = view_width / 2.;
+        camera.half_height = camera.pixel_size * vsize as f32 / 2.;
+        camera
+    }
+
+    pub fn fisheye(hsize: usize, vsize: usize, field_of_view: f32) -> Self {
+        let mut camera = Camera::new(hsize, vsize, field_of_view);
+        camera.projection = Projection::Fisheye { field_of_view };
+        camera.pixel_size = field_of_view / hsize.min(vsize) as f32;
+        camera.half_width = camera.pixel_size * hsize as f32 / 2.;
+        camera.half_height = camera.pixel_size * vsize as f32 / 2.;
+        camera
+    }
+
+    pub fn equirectangular(hsize: usize, vsize: usize) -> Self {
+        let mut camera = Camera::new(hsize, vsize, 2. * PI);
+        camera.projection = Projection::Equirectangular;
+        camera.pixel_size = 2. * PI / hsize as f32;
+        camera.half_width = PI;
+        camera.half_height = PI / 2.;
+        camera
+    }
+
+    pub fn lens_point(&self, u: f32, v: f32) -> (f32, f32) {
+        let (x, y) = match self.aperture {
+            Aperture::Disk => concentric_sample_disk(u, v),
+            Aperture::Polygon(blades) => sample_polygon(blades, u, v),
+        };
+        (x * self.aperture_radius, y * self.aperture_radius)
+    }
 }
 
 pub fn ray_for_pixel(camera: Camera, px: usize, py: usize) -> Result<Ray, &'static str> {
-    let xoffset = (px as f32 + 0.5) * camera.pixel_size;
-    let yoffset = (py as f32 + 0.5) * camera.pixel_size;
+    ray_for_sample(camera, px as f32 + 0.5, py as f32 + 0.5, (0.5, 0.5))
+}
 
-    let world_x = camera.half_width - xoffset;
-    let world_y = camera.half_height - yoffset;
+// Only the perspective projection has a lens to defocus through
+fn camera_inverse(camera: Camera) -> Result<Matrix<f32, 4, 4>, &'static str> {
+    if camera.aperture_radius > 0. && camera.projection != Projection::Perspective {
+        return Err("Depth of field needs the perspective projection");
+    }
+    camera.transform.inverse()
+}
 
-    let pixel = camera.transform.inverse()? * point(world_x, world_y, -1.);
-    let origin = camera.transform.inverse()? * point(0., 0., 0.);
-    let direction = normalize(pixel - origin);
+// Cast a ray through canvas coordinates (x, y) from the point (u, v) on the lens.
+// With a zero aperture every lens sample collapses to the pinhole.
+pub fn ray_for_sample(
+    camera: Camera,
+    x: f32,
+    y: f32,
+    lens: (f32, f32),
+) -> Result<Ray, &'static str> {
+    let inverse = camera_inverse(camera)?;
+    let origin = inverse * point(0., 0., 0.);
+
+    match camera.projection {
+        Projection::Perspective => {
+            let world_x = camera.half_width - x * camera.pixel_size;
+            let world_y = camera.half_height - y * camera.pixel_size;
+
+            if camera.aperture_radius <= 0. {
+                let pixel = inverse * point(world_x, world_y, -1.);
+                return Ok(Ray::new(origin, normalize(pixel - origin)));
+            }
+
+            let fd = camera.focal_distance;
+            let (lens_x, lens_y) = camera.lens_point(lens.0, lens.1);
+            let focus = inverse * point(world_x * fd, world_y * fd, -fd);
+            let origin = inverse * point(lens_x, lens_y, 0.);
+            Ok(Ray::new(origin, normalize(focus - origin)))
+        }
+        Projection::Orthographic { view_width } => {
+            let pixel_size = view_width / camera.hsize as f32;
+            let world_x = view_width / 2. - x * pixel_size;
+            let world_y = pixel_size * camera.vsize as f32 / 2. - y * pixel_size;
+            let origin = inverse * point(world_x, world_y, 0.);
+            let direction = normalize(inverse * vector(0., 0., -1.));
+            Ok(Ray::new(origin, direction))
+        }
+        Projection::Fisheye { field_of_view } => {
+            let radius = camera.hsize.min(camera.vsize) as f32 / 2.;
+            let dx = (camera.hsize as f32 / 2. - x) / radius;
+            let dy = (camera.vsize as f32 / 2. - y) / radius;
+            let r = f32::sqrt(dx * dx + dy * dy);
+            if r > 1. {
+                return Err("Pixel lies outside the fisheye image circle");
+            }
 
-    Ok(Ray::new(origin, direction))
+            let theta = r * field_of_view / 2.;
+            let (dx, dy) = if r > 0. { (dx / r, dy / r) } else { (0., 0.) };
+            let local = vector(theta.sin() * dx, theta.sin() * dy, -theta.cos());
+            Ok(Ray::new(origin, normalize(inverse * local)))
+        }
+        Projection::Equirectangular => {
+            let longitude = (0.5 - x / camera.hsize as f32) * 2. * PI;
+            let latitude = (0.5 - y / camera.vsize as f32) * PI;
+            let local = vector(
+                latitude.cos() * longitude.sin(),
+                latitude.sin(),
+                -latitude.cos() * longitude.cos(),
+            );
+            Ok(Ray::new(origin, normalize(inverse * local)))
+        }
+    }
 }
 
-pub fn render(camera: Camera, world: World) -> Result<Canvas, &'static str> {
-    let mut image = Canvas::new(camera.hsize, camera.vsize);
-    let colors: Vec<Vec<Color>> = (0..camera.vsize)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdaptiveSampling {
+    // Largest per-channel difference between corners before a pixel is split
+    pub threshold: f32,
+    pub max_depth: u32,
+}
+
+impl Default for AdaptiveSampling {
+    fn default() -> Self {
+        AdaptiveSampling {
+            threshold: 0.1,
+            max_depth: 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RenderSettings {
+    pub samples_per_pixel: usize,
+    pub sample_pattern: SamplePattern,
+    pub filter: Filter,
+    // Recursion limit for the default Whitted integrator
+    pub max_depth: u16,
+    // Replaces the fixed sample pattern and filter when set
+    pub adaptive: Option<AdaptiveSampling>,
+    pub mode: RenderMode,
+    // Camera rays that miss everything leave the pixel transparent instead of
+    // showing the background. Reflections and refractions still see it.
+    pub transparent_background: bool,
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        RenderSettings {
+            samples_per_pixel: 1,
+            sample_pattern: SamplePattern::Grid,
+            filter: Filter::Box,
+            max_depth: 5,
+            adaptive: None,
+            mode: RenderMode::Shaded,
+            transparent_background: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RenderStats {
+    // Primary rays cast from the camera
+    pub rays: usize,
+}
+
+// Anything the pixel filter can average: plain colors or a full set of AOVs
+trait PixelSample: Copy + Add<Output = Self> + Mul<f32, Output = Self> {
+    fn zero() -> Self;
+}
+
+impl PixelSample for Color {
+    fn zero() -> Self {
+        Color::new(0., 0., 0.)
+    }
+}
+
+// What a camera sample traces: the beauty color alone, or with its AOVs
+trait Radiance: PixelSample + Send + Sync {
+    fn trace(world: &World, integrator: &dyn Integrator, ray: Ray, rng: &mut SmallRng) -> Self;
+    // Light reflected off a shadow catcher, which has no surface of its own
+    fn reflection(self) -> Self;
+    fn color(&self) -> Color;
+}
+
+impl Radiance for Color {
+    fn trace(world: &World, integrator: &dyn Integrator, ray: Ray, rng: &mut SmallRng) -> Self {
+        integrator.li(world, ray, rng)
+    }
+
+    fn reflection(self) -> Self {
+        self
+    }
+
+    fn color(&self) -> Color {
+        *self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct WithAovs {
+    beauty: Color,
+    aovs: AovSample,
+}
+
+impl Add for WithAovs {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        WithAovs {
+            beauty: self.beauty + other.beauty,
+            aovs: self.aovs + other.aovs,
+        }
+    }
+}
+
+impl Mul<f32> for WithAovs {
+    type Output = Self;
+
+    fn mul(self, scale: f32) -> Self {
+        WithAovs {
+            beauty: self.beauty * scale,
+            aovs: self.aovs * scale,
+        }
+    }
+}
+
+impl PixelSample for WithAovs {
+    fn zero() -> Self {
+        WithAovs {
+            beauty: Color::new(0., 0., 0.),
+            aovs: AovSample::black(),
+        }
+    }
+}
+
+impl Radiance for WithAovs {
+    fn trace(world: &World, integrator: &dyn Integrator, ray: Ray, rng: &mut SmallRng) -> Self {
+        let (beauty, aovs) = integrator.li_aovs(world, ray, rng);
+        WithAovs { beauty, aovs }
+    }
+
+    fn reflection(self) -> Self {
+        WithAovs {
+            beauty: self.beauty,
+            aovs: AovSample {
+                reflection: self.beauty,
+                ..AovSample::black()
+            },
+        }
+    }
+
+    fn color(&self) -> Color {
+        self.beauty
+    }
+}
+
+// Radiance together with whether the camera ray hit anything, so filtering
+// gives fractional coverage along silhouettes
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Covered<R = Color> {
+    color: R,
+    alpha: f32,
+}
+
+impl<R: PixelSample> Add for Covered<R> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Covered {
+            color: self.color + other.color,
+            alpha: self.alpha + other.alpha,
+        }
+    }
+}
+
+impl<R: PixelSample> Mul<f32> for Covered<R> {
+    type Output = Self;
+
+    fn mul(self, scale: f32) -> Self {
+        Covered {
+            color: self.color * scale,
+            alpha: self.alpha * scale,
+        }
+    }
+}
+
+impl<R: PixelSample> PixelSample for Covered<R> {
+    fn zero() -> Self {
+        Covered {
+            color: R::zero(),
+            alpha: 0.,
+        }
+    }
+}
+
+// How camera rays are turned into coverage, worked out once per render
+#[derive(Debug, Clone, Copy)]
+struct Coverage {
+    transparent_background: bool,
+    // Whether the world holds any shadow catchers or holdouts
+    compositing: bool,
+}
+
+impl Coverage {
+    fn new(world: &World, transparent_background: bool) -> Self {
+        Coverage {
+            transparent_background,
+            compositing: has_compositing_objects(world),
+        }
+    }
+}
+
+fn trace_covered<R: Radiance>(
+    world: &World,
+    integrator: &dyn Integrator,
+    coverage: Coverage,
+    ray: Ray,
+    rng: &mut SmallRng,
+) -> Covered<R> {
+    let transparent_background = coverage.transparent_background;
+    // With the background in the picture and nothing to cut out, every camera
+    // ray counts as covered
+    if !transparent_background && !coverage.compositing {
+        return Covered {
+            color: R::trace(world, integrator, ray, rng),
+            alpha: 1.,
+        };
+    }
+    let intersections = intersect_world(world, ray);
+    let Some(intersection) = hit(intersections.clone()) else {
+        if transparent_background {
+            return Covered::zero();
+        }
+        return Covered {
+            color: R::trace(world, integrator, ray, rng),
+            alpha: 1.,
+        };
+    };
+    let material = intersection.object.material().clone();
+    if material.holdout {
+        return Covered::zero();
+    }
+    if !material.shadow_catcher {
+        return Covered {
+            color: R::trace(world, integrator, ray, rng),
+            alpha: 1.,
+        };
+    }
+
+    // A shadow catcher lays its shadow over whatever is behind it, like a
+    // sheet of black with the shadow density as opacity, and adds reflections
+    let comps = prepare_computations(intersection, ray, intersections);
+    let density = shadow_density(world, &comps);
+    let behind: Covered<R> = trace_covered(
+        world,
+        integrator,
+        coverage,
+        Ray::new(comps.under_point, ray.direction),
+        rng,
+    );
+    let reflective = material.reflective_at(&comps.object, comps.over_point);
+    let reflection = Ray::new(comps.over_point, comps.reflectv);
+    // A reflected miss would paint the cut-out background back in
+    let reflects_background =
+        transparent_background && hit(intersect_world(world, reflection)).is_none();
+    let reflected = if reflective > 0. && !reflects_background {
+        R::trace(world, integrator, reflection, rng).reflection() * reflective
+    } else {
+        R::zero()
+    };
+    Covered {
+        color: reflected + behind.color * (1. - density),
+        alpha: density + behind.alpha * (1. - density),
+    }
+}
+
+type Trace<'a, S> = dyn Fn(Ray, &mut SmallRng) -> S + Sync + 'a;
+
+// Each image sample averages `lens_samples` rays across the aperture
+fn sample_color<S: PixelSample>(
+    camera: Camera,
+    trace: &Trace<S>,
+    x: f32,
+    y: f32,
+    rng: &mut SmallRng,
+    rays: &mut usize,
+) -> S {
+    let lens_samples = if camera.aperture_radius > 0. {
+        camera.lens_samples.max(1)
+    } else {
+        1
+    };
+
+    let mut total = S::zero();
+    for _ in 0..lens_samples {
+        let lens = if lens_samples == 1 {
+            (0.5, 0.5)
+        } else {
+            (rng.gen::<f32>(), rng.gen::<f32>())
+        };
+        if let Ok(ray) = ray_for_sample(camera, x, y, lens) {
+            *rays += 1;
+            total = total + trace(ray, rng);
+        }
+    }
+    total * (1. / lens_samples as f32)
+}
+
+// Spread the samples over the filter's support around the pixel center and
+// take the weighted average
+fn pixel_color<S: PixelSample>(
+    camera: Camera,
+    settings: &RenderSettings,
+    trace: &Trace<S>,
+    x: usize,
+    y: usize,
+    rays: &mut usize,
+) -> S {
+    let mut rng = SmallRng::seed_from_u64((y * camera.hsize + x) as u64);
+    let samples = pixel_samples(
+        settings.sample_pattern,
+        settings.samples_per_pixel,
+        &mut rng,
+    );
+    let radius = settings.filter.radius();
+
+    let mut weighted = S::zero();
+    let mut unweighted = S::zero();
+    let mut weight_sum = 0.;
+    for (u, v) in samples.iter() {
+        let dx = (u - 0.5) * 2. * radius;
+        let dy = (v - 0.5) * 2. * radius;
+        let weight = settings.filter.weight(dx, dy);
+        let color = sample_color(
+            camera,
+            trace,
+            x as f32 + 0.5 + dx,
+            y as f32 + 0.5 + dy,
+            &mut rng,
+            rays,
+        );
+
+        weighted = weighted + color * weight;
+        unweighted = unweighted + color;
+        weight_sum += weight;
+    }
+
+    // Negative lobes can cancel out entirely with very few samples
+    if weight_sum.abs() < 1e-6 {
+        unweighted * (1. / samples.len() as f32)
+    } else {
+        weighted * (1. / weight_sum)
+    }
+}
+
+// Render every pixel in parallel rows, returning the samples and the ray count
+fn render_pixels<S: PixelSample + Send>(
+    camera: Camera,
+    settings: &RenderSettings,
+    trace: &Trace<S>,
+) -> (Vec<Vec<S>>, usize) {
+    let rows: Vec<(Vec<S>, usize)> = (0..camera.vsize)
         .into_par_iter()
-        .map(|y: usize| -> Vec<Color> {
-            (0..camera.hsize)
-                .into_par_iter()
-                .map(|x: usize| -> Color {
-                    let ray = ray_for_pixel(camera, x, y).unwrap();
-                    color_at(&world, ray, 5)
-                })
-                .collect()
+        .map(|y: usize| -> (Vec<S>, usize) {
+            let mut row_rays = 0;
+            let row = (0..camera.hsize)
+                .map(|x: usize| -> S { pixel_color(camera, settings, trace, x, y, &mut row_rays) })
+                .collect();
+            (row, row_rays)
         })
         .collect();
+    let rays = rows.iter().map(|(_, rays)| rays).sum();
+    (rows.into_iter().map(|(row, _)| row).collect(), rays)
+}
+
+fn contrast<R: Radiance>(samples: [Covered<R>; 4]) -> f32 {
+    let spread = |channel: fn(&Covered<R>) -> f32| {
+        let values = samples.map(|c| channel(&c));
+        let max = values.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+        let min = values.iter().cloned().fold(f32::INFINITY, f32::min);
+        max - min
+    };
+    spread(|c| c.color.color().red)
+        .max(spread(|c| c.color.color().green))
+        .max(spread(|c| c.color.color().blue))
+        .max(spread(|c| c.alpha))
+}
+
+// Deeper splits would put samples closer together than f32 can tell apart
+const MAX_ADAPTIVE_DEPTH: u32 = 16;
+
+struct AdaptiveContext<'a, R> {
+    camera: Camera,
+    world: &'a World,
+    integrator: &'a dyn Integrator,
+    coverage: Coverage,
+    threshold: f32,
+    max_depth: u32,
+    // Pixel being refined and its samples on a grid 2^max_depth cells across,
+    // so edges shared by neighbouring sub-squares are only traced once
+    x: usize,
+    y: usize,
+    samples: HashMap<(u32, u32), Covered<R>>,
+    rng: SmallRng,
+    rays: usize,
+}
+
+impl<R: Radiance> AdaptiveContext<'_, R> {
+    fn sample(&mut self, gx: u32, gy: u32) -> Covered<R> {
+        if let Some(sample) = self.samples.get(&(gx, gy)) {
+            return *sample;
+        }
+        let (world, integrator) = (self.world, self.integrator);
+        let coverage = self.coverage;
+        let cells = (1u32 << self.max_depth) as f32;
+        let sample = sample_color(
+            self.camera,
+            &|ray, rng| trace_covered(world, integrator, coverage, ray, rng),
+            self.x as f32 + gx as f32 / cells,
+            self.y as f32 + gy as f32 / cells,
+            &mut self.rng,
+            &mut self.rays,
+        );
+        self.samples.insert((gx, gy), sample);
+        sample
+    }
 
-    for y in 0..camera.vsize {
-        for x in 0..camera.hsize {
-            write_pixel(&mut image, x, y, colors[y][x]);
+    // Positions and size are in grid cells. Corners are ordered top-left,
+    // top-right, bottom-left, bottom-right
+    fn subdivide(
+        &mut self,
+        x: u32,
+        y: u32,
+        size: u32,
+        corners: [Covered<R>; 4],
+        depth: u32,
+    ) -> Covered<R> {
+        let [tl, tr, bl, br] = corners;
+        if depth >= self.max_depth || contrast(corners) <= self.threshold {
+            return (tl + tr + bl + br) * 0.25;
         }
+
+        let half = size / 2;
+        let top = self.sample(x + half, y);
+        let left = self.sample(x, y + half);
+        let center = self.sample(x + half, y + half);
+        let right = self.sample(x + size, y + half);
+        let bottom = self.sample(x + half, y + size);
+
+        let quadrants = [
+            self.subdivide(x, y, half, [tl, top, left, center], depth + 1),
+            self.subdivide(x + half, y, half, [top, tr, center, right], depth + 1),
+            self.subdivide(x, y + half, half, [left, center, bl, bottom], depth + 1),
+            self.subdivide(
+                x + half,
+                y + half,
+                half,
+                [center, right, bottom, br],
+                depth + 1,
+            ),
+        ];
+        (quadrants[0] + quadrants[1] + quadrants[2] + quadrants[3]) * 0.25
     }
+}
+
+// Shoot one ray per pixel corner, sharing corners between neighbours, then
+// recursively split any pixel whose corners disagree by more than the threshold
+fn render_adaptive<R: Radiance>(
+    camera: Camera,
+    world: &World,
+    integrator: &dyn Integrator,
+    adaptive: AdaptiveSampling,
+    coverage: Coverage,
+) -> (Vec<Vec<Covered<R>>>, usize) {
+    let corner_rows: Vec<(Vec<Covered<R>>, usize)> = (0..=camera.vsize)
+        .into_par_iter()
+        .map(|y| {
+            let mut rng = SmallRng::seed_from_u64(y as u64);
+            let mut rays = 0;
+            let trace =
+                |ray, rng: &mut SmallRng| trace_covered(world, integrator, coverage, ray, rng);
+            let row = (0..=camera.hsize)
+                .map(|x| sample_color(camera, &trace, x as f32, y as f32, &mut rng, &mut rays))
+                .collect();
+            (row, rays)
+        })
+        .collect();
+    let corner_rays: usize = corner_rows.iter().map(|(_, rays)| rays).sum();
+
+    let pixels: Vec<(Vec<Covered<R>>, usize)> = (0..camera.vsize)
+        .into_par_iter()
+        .map(|y| {
+            let mut row_rays = 0;
+            let row = (0..camera.hsize)
+                .map(|x| {
+                    let corners = [
+                        corner_rows[y].0[x],
+                        corner_rows[y].0[x + 1],
+                        corner_rows[y + 1].0[x],
+                        corner_rows[y + 1].0[x + 1],
+                    ];
+                    let max_depth = adaptive.max_depth.min(MAX_ADAPTIVE_DEPTH);
+                    let mut context = AdaptiveContext {
+                        camera,
+                        world,
+                        integrator,
+                        coverage,
+                        threshold: adaptive.threshold,
+                        max_depth,
+                        x,
+                        y,
+                        samples: HashMap::new(),
+                        rng: SmallRng::seed_from_u64((y * camera.hsize + x) as u64),
+                        rays: 0,
+                    };
+                    let cells = 1 << max_depth;
+                    let color = context.subdivide(0, 0, cells, corners, 0);
+                    row_rays += context.rays;
+                    color
+                })
+                .collect();
+            (row, row_rays)
+        })
+        .collect();
+
+    let rays = corner_rays + pixels.iter().map(|(_, rays)| rays).sum::<usize>();
+    (pixels.into_iter().map(|(row, _)| row).collect(), rays)
+}
+
+pub fn render(camera: Camera, world: World) -> Result<Canvas, &'static str> {
+    render_with(camera, &world, &RenderSettings::default())
+}
+
+pub fn render_with(
+    camera: Camera,
+    world: &World,
+    settings: &RenderSettings,
+) -> Result<Canvas, &'static str> {
+    let (image, _) = render_with_stats(camera, world, settings)?;
     Ok(image)
 }
+
+pub fn render_with_stats(
+    camera: Camera,
+    world: &World,
+    settings: &RenderSettings,
+) -> Result<(Canvas, RenderStats), &'static str> {
+    match settings.mode {
+        RenderMode::Shaded => {
+            render_with_integrator(camera, world, settings, &Whitted::new(settings.max_depth))
+        }
+        mode => render_with_integrator(
+            camera,
+            world,
+            settings,
+            &DebugView::new(mode, settings.max_depth),
+        ),
+    }
+}
+
+pub fn render_with_integrator(
+    camera: Camera,
+    world: &World,
+    settings: &RenderSettings,
+    integrator: &dyn Integrator,
+) -> Result<(Canvas, RenderStats), &'static str> {
+    camera_inverse(camera)?;
+
+    let (samples, rays) = render_covered(camera, world, settings, integrator);
+    let mut image = Canvas::new(camera.hsize, camera.vsize);
+    for (y, row) in samples.iter().enumerate() {
+        for (x, sample) in row.iter().enumerate() {
+            write_covered(&mut image, x, y, sample.color, sample.alpha);
+        }
+    }
+    Ok((image, RenderStats { rays }))
+}
+
+// Trace every pixel adaptively or over the fixed sample pattern
+fn render_covered<R: Radiance>(
+    camera: Camera,
+    world: &World,
+    settings: &RenderSettings,
+    integrator: &dyn Integrator,
+) -> (Vec<Vec<Covered<R>>>, usize) {
+    let coverage = Coverage::new(world, settings.transparent_background);
+    match settings.adaptive {
+        Some(adaptive) => render_adaptive(camera, world, integrator, adaptive, coverage),
+        None => render_pixels(camera, settings, &|ray, rng| {
+            trace_covered(world, integrator, coverage, ray, rng)
+        }),
+    }
+}
+
+fn write_covered(image: &mut Canvas, x: usize, y: usize, color: Color, alpha: f32) {
+    write_pixel(image, x, y, color);
+    // Negative filter lobes can push coverage slightly out of range
+    write_alpha(image, x, y, alpha.clamp(0., 1.));
+}
+
+// Render the beauty image with `integrator` exactly as `render_with_integrator`
+// does, together with the requested AOV buffers. Every buffer is filtered from
+// the same samples as the beauty image and shares its alpha. The lighting
+// buffers follow the Whitted split, so other integrators leave everything in
+// `Aov::Direct`.
+pub fn render_with_aovs(
+    camera: Camera,
+    world: &World,
+    settings: &RenderSettings,
+    integrator: &dyn Integrator,
+    aovs: &[Aov],
+) -> Result<AovImages, &'static str> {
+    camera_inverse(camera)?;
+
+    let (samples, _) = render_covered::<WithAovs>(camera, world, settings, integrator);
+    let mut beauty = Canvas::new(camera.hsize, camera.vsize);
+    let mut buffers: Vec<(Aov, Canvas)> = aovs
+        .iter()
+        .map(|aov| (*aov, Canvas::new(camera.hsize, camera.vsize)))
+        .collect();
+    for (y, row) in samples.iter().enumerate() {
+        for (x, sample) in row.iter().enumerate() {
+            write_covered(&mut beauty, x, y, sample.color.beauty, sample.alpha);
+            for (aov, canvas) in buffers.iter_mut() {
+                write_covered(canvas, x, y, sample.color.aovs.get(*aov), sample.alpha);
+            }
+        }
+    }
+    Ok(AovImages { beauty, buffers })
+}
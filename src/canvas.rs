@@ -2,24 +2,28 @@ use crate::Color;
 
 const MAX_PPM_LEN: usize = 70;
 
+// Colors are premultiplied by alpha, so a pixel with coverage 0 is black.
+// A new canvas is fully opaque.
 #[derive(Debug, Clone)]
 pub struct Canvas {
     pub height: usize,
     pub width: usize,
     pub pixels: Vec<Vec<Color>>,
+    pub alpha: Vec<Vec<f32>>,
 }
 
 impl Canvas {
     pub fn new(width: usize, height: usize) -> Self {
         let mut pixels = Vec::new();
         for _ in 0..height {
-            pixels.push(vec![Color::new(0.0, 0.0, 0.0); width as usize]);
+            pixels.push(vec![Color::new(0.0, 0.0, 0.0); width]);
         }
 
         Canvas {
             width,
             height,
             pixels,
+            alpha: vec![vec![1.; width]; height],
         }
     }
 }
@@ -32,22 +36,65 @@ pub fn pixel_at(canvas: &Canvas, x: usize, y: usize) -> Color {
     canvas.pixels[y][x]
 }
 
+pub fn write_alpha(canvas: &mut Canvas, x: usize, y: usize, alpha: f32) {
+    canvas.alpha[y][x] = alpha;
+}
+
+pub fn alpha_at(canvas: &Canvas, x: usize, y: usize) -> f32 {
+    canvas.alpha[y][x]
+}
+
+// How color is stored next to alpha in an output file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AlphaMode {
+    // Color independent of coverage, as PNG expects
+    #[default]
+    Straight,
+    // Color already scaled by coverage, as EXR and compositors expect
+    Premultiplied,
+}
+
+// The color with alpha divided back out. Fully transparent pixels are black.
+pub fn straight_pixel_at(canvas: &Canvas, x: usize, y: usize) -> Color {
+    let alpha = alpha_at(canvas, x, y);
+    if alpha <= 0. {
+        return Color::new(0., 0., 0.);
+    }
+    pixel_at(canvas, x, y) * (1. / alpha)
+}
+
 pub fn canvas_to_ppm(canvas: &Canvas) -> Vec<String> {
+    let rows: Vec<Vec<[u8; 3]>> = canvas
+        .pixels
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|pix| {
+                    [
+                        (pix.red.clamp(0.0, 1.0) * 255.) as u8,
+                        (pix.green.clamp(0.0, 1.0) * 255.) as u8,
+                        (pix.blue.clamp(0.0, 1.0) * 255.) as u8,
+                    ]
+                })
+                .collect()
+        })
+        .collect();
+    ppm_from_bytes(canvas.width, canvas.height, &rows)
+}
+
+// Plain PPM lines for 8-bit pixels that are already encoded for display
+pub fn ppm_from_bytes(width: usize, height: usize, rows: &[Vec<[u8; 3]>]) -> Vec<String> {
     let mut result = Vec::new();
 
     // Define header
     result.push(String::from("P3"));
-    result.push(format!("{} {}", canvas.width, canvas.height));
+    result.push(format!("{} {}", width, height));
     result.push(String::from("255"));
 
-    for row in &canvas.pixels {
+    for row in rows {
         let mut row_txt = String::new();
         for pix in row {
-            let red_u8 = (pix.red.clamp(0.0, 1.0) * 255.) as u8;
-            let green_u8 = (pix.green.clamp(0.0, 1.0) * 255.) as u8;
-            let blue_u8 = (pix.blue.clamp(0.0, 1.0) * 255.) as u8;
-
-            for color in [red_u8, green_u8, blue_u8].iter() {
+            for color in pix.iter() {
                 let color_str = format!("{}", color);
                 if row_txt.len() + color_str.len() + 1 > MAX_PPM_LEN {
                     result.push(row_txt.to_string());
@@ -64,3 +111,294 @@ pub fn canvas_to_ppm(canvas: &Canvas) -> Vec<String> {
     }
     result
 }
+
+pub fn canvas_from_ppm(ppm: &str) -> Result<Canvas, &'static str> {
+    let mut tokens = ppm
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or(""))
+        .flat_map(|line| line.split_whitespace());
+
+    if tokens.next() != Some("P3") {
+        return Err("Only plain (P3) PPM images are supported");
+    }
+
+    let mut next_number = || -> Result<usize, &'static str> {
+        tokens
+            .next()
+            .ok_or("Unexpected end of PPM data")?
+            .parse::<usize>()
+            .map_err(|_| "Invalid number in PPM data")
+    };
+
+    let width = next_number()?;
+    let height = next_number()?;
+    let scale = next_number()?;
+    if scale == 0 {
+        return Err("PPM maximum value must be positive");
+    }
+
+    let mut canvas = Canvas::new(width, height);
+    for y in 0..height {
+        for x in 0..width {
+            let red = next_number()? as f32 / scale as f32;
+            let green = next_number()? as f32 / scale as f32;
+            let blue = next_number()? as f32 / scale as f32;
+            write_pixel(&mut canvas, x, y, Color::new(red, green, blue));
+        }
+    }
+    Ok(canvas)
+}
+
+// Portable float map: a lossless dump of the canvas, rows stored bottom to
+// top as little-endian floats
+pub fn canvas_to_pfm(canvas: &Canvas) -> Vec<u8> {
+    let mut data = format!("PF\n{} {}\n-1.0\n", canvas.width, canvas.height).into_bytes();
+    for row in canvas.pixels.iter().rev() {
+        for pix in row {
+            for value in [pix.red, pix.green, pix.blue] {
+                data.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+    }
+    data
+}
+
+pub fn canvas_from_pfm(data: &[u8]) -> Result<Canvas, &'static str> {
+    // Magic, width, height and scale, then a single whitespace byte before the data
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    while tokens.len() < 4 {
+        while pos < data.len() && data[pos].is_ascii_whitespace() {
+            pos += 1;
+        }
+        let start = pos;
+        while pos < data.len() && !data[pos].is_ascii_whitespace() {
+            pos += 1;
+        }
+        if start == pos {
+            return Err("Unexpected end of PFM header");
+        }
+        let token = std::str::from_utf8(&data[start..pos]).map_err(|_| "Invalid PFM header")?;
+        tokens.push(token);
+    }
+    pos += 1;
+
+    if tokens[0] != "PF" {
+        return Err("Only color (PF) float maps are supported");
+    }
+    let width = tokens[1]
+        .parse::<usize>()
+        .map_err(|_| "Invalid PFM width")?;
+    let height = tokens[2]
+        .parse::<usize>()
+        .map_err(|_| "Invalid PFM height")?;
+    let scale = tokens[3].parse::<f32>().map_err(|_| "Invalid PFM scale")?;
+    let little_endian = scale < 0.;
+
+    if data.len() < pos + width * height * 12 {
+        return Err("Unexpected end of PFM data");
+    }
+    let mut canvas = Canvas::new(width, height);
+    for y in 0..height {
+        for x in 0..width {
+            let mut channels = [0.; 3];
+            for (c, channel) in channels.iter_mut().enumerate() {
+                let offset = pos + ((y * width + x) * 3 + c) * 4;
+                let bytes: [u8; 4] = data[offset..offset + 4].try_into().unwrap();
+                *channel = if little_endian {
+                    f32::from_le_bytes(bytes)
+                } else {
+                    f32::from_be_bytes(bytes)
+                };
+            }
+            let color = Color::new(channels[0], channels[1], channels[2]);
+            write_pixel(&mut canvas, x, height - 1 - y, color);
+        }
+    }
+    Ok(canvas)
+}
+
+pub fn crop(
+    canvas: &Canvas,
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+) -> Result<Canvas, &'static str> {
+    if x + width > canvas.width || y + height > canvas.height {
+        return Err("Crop extends past the canvas");
+    }
+    let mut result = Canvas::new(width, height);
+    for row in 0..height {
+        result.pixels[row] = canvas.pixels[y + row][x..x + width].to_vec();
+        result.alpha[row] = canvas.alpha[y + row][x..x + width].to_vec();
+    }
+    Ok(result)
+}
+
+pub fn flip_horizontal(canvas: &Canvas) -> Canvas {
+    let mut result = canvas.clone();
+    for row in result.pixels.iter_mut() {
+        row.reverse();
+    }
+    for row in result.alpha.iter_mut() {
+        row.reverse();
+    }
+    result
+}
+
+pub fn flip_vertical(canvas: &Canvas) -> Canvas {
+    let mut result = canvas.clone();
+    result.pixels.reverse();
+    result.alpha.reverse();
+    result
+}
+
+// Pixels of `source` placed with its top-left corner at (`x`, `y`) in
+// `target`, as (source x, source y, target x, target y). Anything that falls
+// outside `target` is skipped.
+fn overlap(
+    target: &Canvas,
+    source: &Canvas,
+    x: isize,
+    y: isize,
+) -> impl Iterator<Item = (usize, usize, usize, usize)> {
+    let (tw, th) = (target.width as isize, target.height as isize);
+    let (sw, sh) = (source.width as isize, source.height as isize);
+    let columns = (0.max(-x)..sw.min(tw - x)).map(move |sx| (sx as usize, (sx + x) as usize));
+    (0.max(-y)..sh.min(th - y)).flat_map(move |sy| {
+        columns
+            .clone()
+            .map(move |(sx, tx)| (sx, sy as usize, tx, (sy + y) as usize))
+    })
+}
+
+// Copy `source` over `target`, replacing color and alpha
+pub fn blit(target: &mut Canvas, source: &Canvas, x: isize, y: isize) {
+    for (sx, sy, tx, ty) in overlap(target, source, x, y).collect::<Vec<_>>() {
+        target.pixels[ty][tx] = source.pixels[sy][sx];
+        target.alpha[ty][tx] = source.alpha[sy][sx];
+    }
+}
+
+// Porter-Duff "over": `source` composited on top of `target`
+pub fn alpha_over(target: &mut Canvas, source: &Canvas, x: isize, y: isize) {
+    for (sx, sy, tx, ty) in overlap(target, source, x, y).collect::<Vec<_>>() {
+        let a = source.alpha[sy][sx];
+        target.pixels[ty][tx] = source.pixels[sy][sx] + target.pixels[ty][tx] * (1. - a);
+        target.alpha[ty][tx] = a + target.alpha[ty][tx] * (1. - a);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResizeFilter {
+    Nearest,
+    #[default]
+    Bilinear,
+    // Windowed sinc with three lobes; sharper, but may ring at hard edges
+    Lanczos,
+}
+
+impl ResizeFilter {
+    fn radius(&self) -> f32 {
+        match self {
+            ResizeFilter::Nearest => 0.5,
+            ResizeFilter::Bilinear => 1.,
+            ResizeFilter::Lanczos => 3.,
+        }
+    }
+
+    fn weight(&self, x: f32) -> f32 {
+        let x = x.abs();
+        match self {
+            ResizeFilter::Nearest => 1.,
+            ResizeFilter::Bilinear => (1. - x).max(0.),
+            ResizeFilter::Lanczos => {
+                if x < 1e-6 {
+                    1.
+                } else if x >= 3. {
+                    0.
+                } else {
+                    let px = std::f32::consts::PI * x;
+                    3. * px.sin() * (px / 3.).sin() / (px * px)
+                }
+            }
+        }
+    }
+
+    // Source indices and normalised weights for each of `to` output samples
+    fn taps(&self, from: usize, to: usize) -> Vec<Vec<(usize, f32)>> {
+        let scale = from as f32 / to as f32;
+        (0..to)
+            .map(|i| {
+                let center = (i as f32 + 0.5) * scale;
+                if *self == ResizeFilter::Nearest {
+                    return vec![((center as usize).min(from - 1), 1.)];
+                }
+                // Widen the kernel when shrinking so every source pixel counts
+                let stretch = scale.max(1.);
+                let support = self.radius() * stretch;
+                let first = (center - support).floor() as isize;
+                let last = (center + support).ceil() as isize;
+                let mut taps: Vec<(usize, f32)> = (first..=last)
+                    .map(|j| {
+                        let weight = self.weight((j as f32 + 0.5 - center) / stretch);
+                        (j.clamp(0, from as isize - 1) as usize, weight)
+                    })
+                    .filter(|&(_, weight)| weight != 0.)
+                    .collect();
+                let total: f32 = taps.iter().map(|(_, weight)| weight).sum();
+                for tap in taps.iter_mut() {
+                    tap.1 /= total;
+                }
+                taps
+            })
+            .collect()
+    }
+}
+
+// Resample to a new size, filtering rows and then columns
+pub fn resize(
+    canvas: &Canvas,
+    width: usize,
+    height: usize,
+    filter: ResizeFilter,
+) -> Result<Canvas, &'static str> {
+    if width == 0 || height == 0 {
+        return Err("Canvas size must be positive");
+    }
+    if canvas.width == 0 || canvas.height == 0 {
+        return Err("Cannot resize an empty canvas");
+    }
+    let columns = filter.taps(canvas.width, width);
+    let mut wide = Canvas::new(width, canvas.height);
+    for y in 0..canvas.height {
+        for (x, taps) in columns.iter().enumerate() {
+            let mut color = Color::new(0., 0., 0.);
+            let mut alpha = 0.;
+            for &(sx, weight) in taps {
+                color = color + canvas.pixels[y][sx] * weight;
+                alpha += canvas.alpha[y][sx] * weight;
+            }
+            wide.pixels[y][x] = color;
+            wide.alpha[y][x] = alpha;
+        }
+    }
+
+    let rows = filter.taps(canvas.height, height);
+    let mut result = Canvas::new(width, height);
+    for (y, taps) in rows.iter().enumerate() {
+        for x in 0..width {
+            let mut color = Color::new(0., 0., 0.);
+            let mut alpha = 0.;
+            for &(sy, weight) in taps {
+                color = color + wide.pixels[sy][x] * weight;
+                alpha += wide.alpha[sy][x] * weight;
+            }
+            result.pixels[y][x] = color;
+            // Lanczos lobes can overshoot
+            result.alpha[y][x] = alpha.clamp(0., 1.);
+        }
+    }
+    Ok(result)
+}
@@ -0,0 +1,44 @@
+use crate::{
+    dot,
+    intersections::Precomputation,
+    medium::{light_transmittance, lights_at},
+    ray::Ray,
+    world::{color_at, reflected_color, World},
+    Color,
+};
+
+pub fn has_compositing_objects(world: &World) -> bool {
+    world.objects.iter().any(|object| {
+        let material = object.material();
+        material.shadow_catcher || material.holdout
+    })
+}
+
+// How much of the light reaching a shadow catcher is blocked, from 0 (fully
+// lit) to 1 (fully in shadow). Each light counts by its brightness and angle
+// to the surface.
+pub fn shadow_density(world: &World, comps: &Precomputation) -> f32 {
+    let mut total = 0.;
+    let mut visible = 0.;
+    for (direction, distance, intensity) in lights_at(world, comps.over_point) {
+        let weight = intensity.luminance() * dot(comps.normalv, direction).max(0.);
+        if weight <= 0. {
+            continue;
+        }
+        total += weight;
+        visible +=
+            weight * light_transmittance(world, comps.over_point, direction, distance).luminance();
+    }
+    if total <= 0. {
+        return 0.;
+    }
+    (1. - visible / total).clamp(0., 1.)
+}
+
+// What a reflection or refraction sees at a shadow catcher: whatever is behind
+// it, darkened by the shadow, plus anything it reflects
+pub fn shadow_catcher_color(world: &World, comps: Precomputation, remaining: u16) -> Color {
+    let behind = Ray::new(comps.under_point, comps.eyev * -1.);
+    let density = shadow_density(world, &comps);
+    reflected_color(world, comps, remaining) + color_at(world, behind, remaining) * (1. - density)
+}
@@ -3,7 +3,7 @@ use crate::{
     vector, world::ShapeEnum, Tuple,
 };
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Cone {
     transform: Matrix<f32, 4, 4>,
     pub material: Material,
@@ -32,21 +32,23 @@ impl Default for Cone {
 
 impl Shape for Cone {
     fn local_intersect(&self, ray: Ray) -> Vec<Intersection> {
-        let a = f32::powi(ray.direction.x, 2) - f32::powi(ray.direction.y, 2) + f32::powi(ray.direction.z, 2);
-        let b = 2. * (
-            ray.origin.x * ray.direction.x - ray.origin.y * ray.direction.y + ray.origin.z * ray.direction.z
-        );
-        let c = f32::powi(ray.origin.x, 2) - f32::powi(ray.origin.y, 2) + f32::powi(ray.origin.z, 2);
+        let a = f32::powi(ray.direction.x, 2) - f32::powi(ray.direction.y, 2)
+            + f32::powi(ray.direction.z, 2);
+        let b = 2.
+            * (ray.origin.x * ray.direction.x - ray.origin.y * ray.direction.y
+                + ray.origin.z * ray.direction.z);
+        let c =
+            f32::powi(ray.origin.x, 2) - f32::powi(ray.origin.y, 2) + f32::powi(ray.origin.z, 2);
         let disc = f32::powi(b, 2) - 4. * a * c;
-    
+
         if a == 0. {
             let mut xs = Vec::new();
 
             if b != 0. {
                 let t = -c / (2. * b);
-                xs.push(Intersection::new(t, ShapeEnum::Cone(*self)));
+                xs.push(Intersection::new(t, ShapeEnum::Cone(self.clone())));
             }
-            intersect_caps(*self, ray, &mut xs);
+            intersect_caps(self.clone(), ray, &mut xs);
             return xs;
         }
 
@@ -55,7 +57,7 @@ impl Shape for Cone {
         } else {
             let mut t0 = (-b - f32::sqrt(disc)) / (2. * a);
             let mut t1 = (-b + f32::sqrt(disc)) / (2. * a);
-            
+
             if t0 > t1 {
                 (t0, t1) = (t1, t0);
             }
@@ -63,15 +65,15 @@ impl Shape for Cone {
             let mut xs = Vec::new();
             let y0 = ray.origin.y + t0 * ray.direction.y;
             if self.minimum < y0 && y0 < self.maximum {
-                xs.push(Intersection::new(t0, ShapeEnum::Cone(*self)));
+                xs.push(Intersection::new(t0, ShapeEnum::Cone(self.clone())));
             }
 
             let y1 = ray.origin.y + t1 * ray.direction.y;
             if self.minimum < y1 && y1 < self.maximum {
-                xs.push(Intersection::new(t1, ShapeEnum::Cone(*self)));
+                xs.push(Intersection::new(t1, ShapeEnum::Cone(self.clone())));
             }
 
-            intersect_caps(*self, ray, &mut xs);
+            intersect_caps(self.clone(), ray, &mut xs);
             xs
         }
     }
@@ -90,7 +92,7 @@ impl Shape for Cone {
             y = -y;
         }
         vector(point.x, y, point.z)
-    }   
+    }
 }
 
 fn check_cap(ray: Ray, t: f32, y: f32) -> bool {
@@ -106,11 +108,11 @@ fn intersect_caps(cone: Cone, ray: Ray, xs: &mut Vec<Intersection>) {
 
     let t = (cone.minimum - ray.origin.y) / ray.direction.y;
     if check_cap(ray, t, cone.minimum) {
-        xs.push(Intersection::new(t, ShapeEnum::Cone(cone)));
+        xs.push(Intersection::new(t, ShapeEnum::Cone(cone.clone())));
     }
 
     let t = (cone.maximum - ray.origin.y) / ray.direction.y;
     if check_cap(ray, t, cone.maximum) {
         xs.push(Intersection::new(t, ShapeEnum::Cone(cone)));
     }
-}
\ No newline at end of file
+}
@@ -3,7 +3,7 @@ use crate::{
     vector, world::ShapeEnum, Tuple,
 };
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct Cube {
     pub transform: Matrix<f32, 4, 4>,
     pub material: Material,
@@ -49,8 +49,8 @@ impl Shape for Cube {
         let [ytmin, ytmax] = check_axis(ray.origin.y, ray.direction.y);
         let [ztmin, ztmax] = check_axis(ray.origin.z, ray.direction.z);
 
-        let tmins = vec![xtmin, ytmin, ztmin];
-        let tmaxs = vec![xtmax, ytmax, ztmax];
+        let tmins = [xtmin, ytmin, ztmin];
+        let tmaxs = [xtmax, ytmax, ztmax];
 
         let tmin = tmins.iter().fold(-f32::INFINITY, |a, &b| a.max(b));
         let tmax = tmaxs.iter().fold(f32::INFINITY, |a, &b| a.min(b));
@@ -59,8 +59,8 @@ impl Shape for Cube {
             Vec::new()
         } else {
             vec![
-                Intersection::new(tmin, ShapeEnum::Cube(*self)),
-                Intersection::new(tmax, ShapeEnum::Cube(*self)),
+                Intersection::new(tmin, ShapeEnum::Cube(self.clone())),
+                Intersection::new(tmax, ShapeEnum::Cube(self.clone())),
             ]
         }
     }
@@ -3,7 +3,7 @@ use crate::{
     vector, world::ShapeEnum, Tuple,
 };
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Cylinder {
     transform: Matrix<f32, 4, 4>,
     pub material: Material,
@@ -39,7 +39,7 @@ impl Shape for Cylinder {
 
         if a == 0. {
             let mut xs = Vec::new();
-            intersect_caps(*self, ray, &mut xs);
+            intersect_caps(self.clone(), ray, &mut xs);
             return xs;
         }
 
@@ -48,7 +48,7 @@ impl Shape for Cylinder {
         } else {
             let mut t0 = (-b - f32::sqrt(disc)) / (2. * a);
             let mut t1 = (-b + f32::sqrt(disc)) / (2. * a);
-            
+
             if t0 > t1 {
                 (t0, t1) = (t1, t0);
             }
@@ -56,15 +56,15 @@ impl Shape for Cylinder {
             let mut xs = Vec::new();
             let y0 = ray.origin.y + t0 * ray.direction.y;
             if self.minimum < y0 && y0 < self.maximum {
-                xs.push(Intersection::new(t0, ShapeEnum::Cylinder(*self)));
+                xs.push(Intersection::new(t0, ShapeEnum::Cylinder(self.clone())));
             }
 
             let y1 = ray.origin.y + t1 * ray.direction.y;
             if self.minimum < y1 && y1 < self.maximum {
-                xs.push(Intersection::new(t1, ShapeEnum::Cylinder(*self)));
+                xs.push(Intersection::new(t1, ShapeEnum::Cylinder(self.clone())));
             }
 
-            intersect_caps(*self, ray, &mut xs);
+            intersect_caps(self.clone(), ray, &mut xs);
             xs
         }
     }
@@ -102,11 +102,11 @@ fn intersect_caps(cyl: Cylinder, ray: Ray, xs: &mut Vec<Intersection>) {
 
     let t = (cyl.minimum - ray.origin.y) / ray.direction.y;
     if check_cap(ray, t) {
-        xs.push(Intersection::new(t, ShapeEnum::Cylinder(cyl)));
+        xs.push(Intersection::new(t, ShapeEnum::Cylinder(cyl.clone())));
     }
 
     let t = (cyl.maximum - ray.origin.y) / ray.direction.y;
     if check_cap(ray, t) {
         xs.push(Intersection::new(t, ShapeEnum::Cylinder(cyl)));
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,165 @@
+use rand::rngs::SmallRng;
+
+use crate::{
+    integrators::{refract_direction, Integrator},
+    intersections::{hit, prepare_computations},
+    ray::{position, Ray},
+    uv::UvMapping,
+    world::{color_at, intersect_world, ShapeEnum, World},
+    Color, Tuple,
+};
+
+// What each pixel shows. Everything but `Shaded` is a diagnostic view for
+// finding out why a scene doesn't look the way it should.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum RenderMode {
+    #[default]
+    Shaded,
+    // Shading normal mapped from [-1, 1] to [0, 1] per channel
+    Normals,
+    // White at `near` fading to black at `far`
+    Depth {
+        near: f32,
+        far: f32,
+    },
+    // A distinct color for each entry in `World::objects`
+    ObjectIndex,
+    // u in red, v in green, using the mapping that suits each shape
+    Uv,
+    // Ray-shape tests spent on the pixel's camera, shadow, reflection and
+    // refraction rays, blue for none up to red at `max_tests`. Sampled terms
+    // such as ambient occlusion aren't counted.
+    IntersectionHeatmap {
+        max_tests: usize,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DebugView {
+    pub mode: RenderMode,
+    // Recursion limit for the shaded and heatmap views
+    pub max_depth: u16,
+}
+
+impl DebugView {
+    pub fn new(mode: RenderMode, max_depth: u16) -> Self {
+        DebugView { mode, max_depth }
+    }
+}
+
+fn natural_mapping(object: ShapeEnum) -> UvMapping {
+    match object {
+        ShapeEnum::Sphere(_) => UvMapping::Spherical,
+        ShapeEnum::Plane(_) => UvMapping::Planar,
+        ShapeEnum::Cube(_) => UvMapping::Cubic,
+        ShapeEnum::Cylinder(_) | ShapeEnum::Cone(_) => UvMapping::Cylindrical,
+    }
+}
+
+fn uv_color(object: ShapeEnum, point: Tuple) -> Result<Color, &'static str> {
+    let object_point = object.transform().inverse()? * point;
+    let (u, v) = natural_mapping(object).map(object_point);
+    Ok(Color::new(u, v, 0.))
+}
+
+// Follow the same rays as the Whitted shading in `color_at`, testing each one
+// against every object
+fn intersection_tests(world: &World, ray: Ray, remaining: u16) -> usize {
+    let per_ray = world.objects.len();
+    let intersections = intersect_world(world, ray);
+    let Some(intersection) = hit(intersections.clone()) else {
+        return per_ray;
+    };
+    let comps = prepare_computations(intersection, ray, intersections);
+    let material = comps.object.material();
+    if material.holdout {
+        return per_ray;
+    }
+    let lights = usize::from(world.light.is_some()) + usize::from(world.sun.is_some());
+    let mut tests = per_ray * (1 + lights);
+    if remaining < 1 {
+        return tests;
+    }
+    if material.reflective_at(&comps.object, comps.over_point) > 0. {
+        let reflected = Ray::new(comps.over_point, comps.reflectv);
+        tests += intersection_tests(world, reflected, remaining - 1);
+    }
+    if material.transparency_at(&comps.object, comps.under_point) > 0. {
+        if let Some(direction) = refract_direction(&comps) {
+            let refracted = Ray::new(comps.under_point, direction);
+            tests += intersection_tests(world, refracted, remaining - 1);
+        }
+    }
+    tests
+}
+
+// Golden-ratio hue steps keep neighbouring indices far apart on the color wheel
+pub fn false_color(index: usize) -> Color {
+    let hue = (index as f32 * 0.618_034).fract() * 6.;
+    let x = 1. - (hue % 2. - 1.).abs();
+    match hue as u32 {
+        0 => Color::new(1., x, 0.),
+        1 => Color::new(x, 1., 0.),
+        2 => Color::new(0., 1., x),
+        3 => Color::new(0., x, 1.),
+        4 => Color::new(x, 0., 1.),
+        _ => Color::new(1., 0., x),
+    }
+}
+
+// Blue, green, yellow, red as t goes from 0 to 1
+pub fn heat_color(t: f32) -> Color {
+    let stops = [
+        Color::new(0., 0., 1.),
+        Color::new(0., 1., 0.),
+        Color::new(1., 1., 0.),
+        Color::new(1., 0., 0.),
+    ];
+    let scaled = t.clamp(0., 1.) * (stops.len() - 1) as f32;
+    let i = (scaled.floor() as usize).min(stops.len() - 2);
+    let f = scaled - i as f32;
+    stops[i] * (1. - f) + stops[i + 1] * f
+}
+
+impl Integrator for DebugView {
+    fn li(&self, world: &World, ray: Ray, _: &mut SmallRng) -> Color {
+        let black = Color::new(0., 0., 0.);
+        match self.mode {
+            RenderMode::Shaded => return color_at(world, ray, self.max_depth),
+            RenderMode::IntersectionHeatmap { max_tests } => {
+                let tests = intersection_tests(world, ray, self.max_depth);
+                return heat_color(tests as f32 / max_tests.max(1) as f32);
+            }
+            _ => {}
+        }
+
+        let intersections = intersect_world(world, ray);
+        let Some(intersection) = hit(intersections.clone()) else {
+            return black;
+        };
+        match self.mode {
+            RenderMode::Normals => {
+                let comps = prepare_computations(intersection, ray, intersections);
+                let n = comps.normalv;
+                Color::new(n.x + 1., n.y + 1., n.z + 1.) * 0.5
+            }
+            RenderMode::Depth { near, far } => {
+                let range = (far - near).max(f32::EPSILON);
+                let d = 1. - ((intersection.t - near) / range).clamp(0., 1.);
+                Color::new(d, d, d)
+            }
+            RenderMode::ObjectIndex => {
+                match world.objects.iter().position(|o| *o == intersection.object) {
+                    Some(index) => false_color(index),
+                    None => black,
+                }
+            }
+            RenderMode::Uv => match uv_color(intersection.object, position(ray, intersection.t)) {
+                Ok(color) => color,
+                // A shape that can't be mapped back to object space shows as a miss
+                Err(_) => black,
+            },
+            RenderMode::Shaded | RenderMode::IntersectionHeatmap { .. } => black,
+        }
+    }
+}
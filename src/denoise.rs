@@ -0,0 +1,192 @@
+use rayon::prelude::*;
+
+use crate::{
+    aov::{Aov, AovImages},
+    camera::{render_with_aovs, Camera, RenderSettings},
+    canvas::{pixel_at, Canvas},
+    integrators::Integrator,
+    world::World,
+    Color,
+};
+
+// Joint bilateral filter settings. Each guide term stops the filter where that
+// buffer changes, so noise is smoothed within a surface but not across edges.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DenoiseSettings {
+    // Half-width of the square window, in pixels
+    pub radius: usize,
+    // Spread of the color difference the filter still averages over; 0 turns
+    // the filter off, larger values smooth more
+    pub strength: f32,
+    pub spatial_sigma: f32,
+    pub albedo_sigma: f32,
+    pub normal_sigma: f32,
+    // Relative to the distance of the nearer pixel
+    pub depth_sigma: f32,
+}
+
+impl Default for DenoiseSettings {
+    fn default() -> Self {
+        DenoiseSettings {
+            radius: 4,
+            strength: 0.5,
+            spatial_sigma: 2.,
+            albedo_sigma: 0.1,
+            normal_sigma: 0.2,
+            depth_sigma: 0.05,
+        }
+    }
+}
+
+impl DenoiseSettings {
+    pub fn new(strength: f32) -> Self {
+        DenoiseSettings {
+            strength,
+            ..DenoiseSettings::default()
+        }
+    }
+}
+
+// Noise-free feature buffers from the same camera, as rendered by
+// `render_with_aovs`. Any of them may be left out.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Guides<'a> {
+    pub albedo: Option<&'a Canvas>,
+    pub normal: Option<&'a Canvas>,
+    pub depth: Option<&'a Canvas>,
+}
+
+impl<'a> Guides<'a> {
+    pub fn from_aovs(images: &'a AovImages) -> Self {
+        Guides {
+            albedo: images.get(Aov::Albedo),
+            normal: images.get(Aov::Normal),
+            depth: images.get(Aov::Depth),
+        }
+    }
+}
+
+fn distance_squared(a: Color, b: Color) -> f32 {
+    let d = a - b;
+    d.red * d.red + d.green * d.green + d.blue * d.blue
+}
+
+// Gaussian falloff of a squared difference
+fn falloff(difference_squared: f32, sigma: f32) -> f32 {
+    f32::exp(-difference_squared / (2. * sigma * sigma))
+}
+
+// How much the guides let the pixel at `q` contribute to the one at `p`
+fn guide_weight(
+    guides: &Guides,
+    settings: &DenoiseSettings,
+    p: (usize, usize),
+    q: (usize, usize),
+) -> f32 {
+    let mut weight = 1.;
+    if let Some(albedo) = guides.albedo {
+        let d = distance_squared(pixel_at(albedo, p.0, p.1), pixel_at(albedo, q.0, q.1));
+        weight *= falloff(d, settings.albedo_sigma);
+    }
+    if let Some(normal) = guides.normal {
+        let d = distance_squared(pixel_at(normal, p.0, p.1), pixel_at(normal, q.0, q.1));
+        weight *= falloff(d, settings.normal_sigma);
+    }
+    if let Some(depth) = guides.depth {
+        // Depth is 0 where nothing was hit, which then differs completely
+        // from any surface
+        let (a, b) = (pixel_at(depth, p.0, p.1).red, pixel_at(depth, q.0, q.1).red);
+        let scale = a.min(b).max(1e-3);
+        weight *= falloff(((a - b) / scale).powi(2), settings.depth_sigma);
+    }
+    weight
+}
+
+fn filter_pixel(
+    image: &Canvas,
+    guides: &Guides,
+    settings: &DenoiseSettings,
+    x: usize,
+    y: usize,
+) -> Color {
+    let center = pixel_at(image, x, y);
+    let radius = settings.radius as isize;
+    let mut total = Color::new(0., 0., 0.);
+    let mut weight_sum = 0.;
+    for dy in -radius..=radius {
+        for dx in -radius..=radius {
+            let (sx, sy) = (x as isize + dx, y as isize + dy);
+            if sx < 0 || sy < 0 || sx >= image.width as isize || sy >= image.height as isize {
+                continue;
+            }
+            let (sx, sy) = (sx as usize, sy as usize);
+            let color = pixel_at(image, sx, sy);
+            let weight = falloff((dx * dx + dy * dy) as f32, settings.spatial_sigma)
+                * falloff(distance_squared(center, color), settings.strength)
+                * guide_weight(guides, settings, (x, y), (sx, sy));
+            total = total + color * weight;
+            weight_sum += weight;
+        }
+    }
+    // `denoise` only runs with positive sigmas, so the center pixel has
+    // weight 1 and the sum can't be zero
+    total * (1. / weight_sum)
+}
+
+// Joint bilateral filter of `image`, keeping its alpha
+pub fn denoise(
+    image: &Canvas,
+    guides: Guides,
+    settings: &DenoiseSettings,
+) -> Result<Canvas, &'static str> {
+    for guide in [guides.albedo, guides.normal, guides.depth]
+        .into_iter()
+        .flatten()
+    {
+        if guide.width != image.width || guide.height != image.height {
+            return Err("Guide buffer size differs from the image");
+        }
+    }
+    let sigmas = [
+        settings.spatial_sigma,
+        settings.albedo_sigma,
+        settings.normal_sigma,
+        settings.depth_sigma,
+    ];
+    if !sigmas.iter().all(|sigma| *sigma > 0.) {
+        return Err("Denoise sigmas must be positive");
+    }
+    if settings.strength <= 0. {
+        return Ok(image.clone());
+    }
+
+    let mut result = image.clone();
+    result.pixels = (0..image.height)
+        .into_par_iter()
+        .map(|y| {
+            (0..image.width)
+                .map(|x| filter_pixel(image, &guides, settings, x, y))
+                .collect()
+        })
+        .collect();
+    Ok(result)
+}
+
+// Render with `integrator`, then denoise the result using the albedo, normal
+// and depth buffers rendered alongside it
+pub fn render_denoised(
+    camera: Camera,
+    world: &World,
+    settings: &RenderSettings,
+    integrator: &dyn Integrator,
+    denoise_settings: &DenoiseSettings,
+) -> Result<Canvas, &'static str> {
+    let images = render_with_aovs(
+        camera,
+        world,
+        settings,
+        integrator,
+        &[Aov::Albedo, Aov::Normal, Aov::Depth],
+    )?;
+    denoise(&images.beauty, Guides::from_aovs(&images), denoise_settings)
+}
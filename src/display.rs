@@ -0,0 +1,250 @@
+use std::sync::OnceLock;
+
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+use crate::{
+    canvas::{pixel_at, ppm_from_bytes, Canvas},
+    lut::Look,
+    Color,
+};
+
+// Operators that squeeze scene radiance into the displayable [0, 1] range,
+// applied to each channel
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ToneMap {
+    #[default]
+    Clamp,
+    Reinhard,
+    // Reinhard that reaches white at `white` instead of at infinity
+    ExtendedReinhard {
+        white: f32,
+    },
+    // Narkowicz's fit of the ACES filmic curve
+    Aces,
+}
+
+impl ToneMap {
+    pub fn apply(&self, x: f32) -> f32 {
+        let x = x.max(0.);
+        match *self {
+            ToneMap::Clamp => x.min(1.),
+            ToneMap::Reinhard => x / (1. + x),
+            ToneMap::ExtendedReinhard { white } => {
+                // A white point at or below black makes any light at all white
+                if white.is_nan() || white <= 0. {
+                    return if x > 0. { 1. } else { 0. };
+                }
+                // Dividing twice keeps a tiny white point from underflowing to 0 / 0
+                (x * (1. + x / white / white) / (1. + x)).min(1.)
+            }
+            ToneMap::Aces => {
+                ((x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14)).clamp(0., 1.)
+            }
+        }
+    }
+}
+
+// Noise added before quantising to 8 bits, trading banding for fine grain
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Dither {
+    #[default]
+    None,
+    // 8x8 Bayer matrix
+    Ordered,
+    // Tiled 32x32 void-and-cluster mask
+    BlueNoise,
+}
+
+const BAYER: [[u8; 8]; 8] = [
+    [0, 32, 8, 40, 2, 34, 10, 42],
+    [48, 16, 56, 24, 50, 18, 58, 26],
+    [12, 44, 4, 36, 14, 46, 6, 38],
+    [60, 28, 52, 20, 62, 30, 54, 22],
+    [3, 35, 11, 43, 1, 33, 9, 41],
+    [51, 19, 59, 27, 49, 17, 57, 25],
+    [15, 47, 7, 39, 13, 45, 5, 37],
+    [63, 31, 55, 23, 61, 29, 53, 21],
+];
+
+const BLUE_NOISE_SIZE: usize = 32;
+
+// Ulichney's void-and-cluster method: starting from a few random points,
+// repeatedly fill the emptiest spot under a Gaussian energy filter. The order
+// in which pixels are filled is the threshold rank.
+fn void_and_cluster() -> Vec<usize> {
+    const SIZE: usize = BLUE_NOISE_SIZE;
+    const SIGMA: f32 = 1.5;
+    let count = SIZE * SIZE;
+    let mut weights = vec![0.; count];
+    for dy in 0..SIZE {
+        for dx in 0..SIZE {
+            // Toroidal distances so the mask tiles seamlessly
+            let x = dx.min(SIZE - dx) as f32;
+            let y = dy.min(SIZE - dy) as f32;
+            weights[dy * SIZE + dx] = f32::exp(-(x * x + y * y) / (2. * SIGMA * SIGMA));
+        }
+    }
+    let mut energy = vec![0.; count];
+    let mut filled = vec![false; count];
+    let toggle = |filled: &mut [bool], energy: &mut [f32], index: usize, on: bool| {
+        filled[index] = on;
+        let sign = if on { 1. } else { -1. };
+        let (px, py) = (index % SIZE, index / SIZE);
+        for (i, e) in energy.iter_mut().enumerate() {
+            let dx = (i % SIZE + SIZE - px) % SIZE;
+            let dy = (i / SIZE + SIZE - py) % SIZE;
+            *e += sign * weights[dy * SIZE + dx];
+        }
+    };
+    let extreme = |filled: &[bool], energy: &[f32], state: bool, highest: bool| {
+        let candidates = (0..count).filter(|&i| filled[i] == state);
+        if highest {
+            candidates.max_by(|&a, &b| energy[a].partial_cmp(&energy[b]).unwrap())
+        } else {
+            candidates.min_by(|&a, &b| energy[a].partial_cmp(&energy[b]).unwrap())
+        }
+    };
+
+    // Initial pattern, relaxed by moving the tightest cluster into the largest void
+    let mut rng = SmallRng::seed_from_u64(0);
+    let initial = count / 10;
+    let mut placed = 0;
+    while placed < initial {
+        let index = rng.gen_range(0..count);
+        if !filled[index] {
+            toggle(&mut filled, &mut energy, index, true);
+            placed += 1;
+        }
+    }
+    loop {
+        let cluster = extreme(&filled, &energy, true, true).unwrap();
+        toggle(&mut filled, &mut energy, cluster, false);
+        let void = extreme(&filled, &energy, false, false).unwrap();
+        toggle(&mut filled, &mut energy, void, true);
+        if void == cluster {
+            break;
+        }
+    }
+    let (initial_filled, initial_energy) = (filled.clone(), energy.clone());
+
+    let mut rank = vec![0; count];
+    // Rank the initial points by removing the tightest cluster first
+    for r in (0..initial).rev() {
+        let cluster = extreme(&filled, &energy, true, true).unwrap();
+        toggle(&mut filled, &mut energy, cluster, false);
+        rank[cluster] = r;
+    }
+    // Then fill the largest remaining void until the mask is full
+    let (mut filled, mut energy) = (initial_filled, initial_energy);
+    for r in initial..count {
+        let void = extreme(&filled, &energy, false, false).unwrap();
+        toggle(&mut filled, &mut energy, void, true);
+        rank[void] = r;
+    }
+    rank
+}
+
+fn blue_noise(x: usize, y: usize) -> usize {
+    static MASK: OnceLock<Vec<usize>> = OnceLock::new();
+    let mask = MASK.get_or_init(void_and_cluster);
+    mask[(y % BLUE_NOISE_SIZE) * BLUE_NOISE_SIZE + x % BLUE_NOISE_SIZE]
+}
+
+impl Dither {
+    // Offset in 8-bit steps, in [-0.5, 0.5)
+    pub fn offset(&self, x: usize, y: usize) -> f32 {
+        match self {
+            Dither::None => 0.,
+            Dither::Ordered => (BAYER[y % 8][x % 8] as f32 + 0.5) / 64. - 0.5,
+            Dither::BlueNoise => {
+                let levels = (BLUE_NOISE_SIZE * BLUE_NOISE_SIZE) as f32;
+                (blue_noise(x, y) as f32 + 0.5) / levels - 0.5
+            }
+        }
+    }
+}
+
+// sRGB opto-electronic transfer function, linear light to encoded [0, 1]
+pub fn srgb_oetf(linear: f32) -> f32 {
+    let linear = linear.clamp(0., 1.);
+    if linear <= 0.0031308 {
+        12.92 * linear
+    } else {
+        1.055 * linear.powf(1. / 2.4) - 0.055
+    }
+}
+
+// Everything between a linear `Canvas` and an 8-bit image: exposure, tone
+// mapping, sRGB encoding, an optional LUT grade and quantisation
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DisplayTransform {
+    // In stops: +1 doubles the light
+    pub exposure: f32,
+    pub tone_map: ToneMap,
+    // Graded onto the encoded color, the input a .cube LUT expects
+    pub look: Option<Look>,
+    pub dither: Dither,
+}
+
+impl DisplayTransform {
+    pub fn new(exposure: f32, tone_map: ToneMap) -> Self {
+        DisplayTransform {
+            exposure,
+            tone_map,
+            look: None,
+            dither: Dither::None,
+        }
+    }
+
+    // Display-encoded color with each channel in [0, 1]
+    pub fn encode(&self, color: Color) -> Color {
+        let scale = f32::powf(2., self.exposure);
+        let channel = |c: f32| srgb_oetf(self.tone_map.apply(c * scale));
+        let encoded = Color::new(
+            channel(color.red),
+            channel(color.green),
+            channel(color.blue),
+        );
+        match &self.look {
+            Some(look) => {
+                let graded = look.apply(encoded);
+                Color::new(
+                    graded.red.clamp(0., 1.),
+                    graded.green.clamp(0., 1.),
+                    graded.blue.clamp(0., 1.),
+                )
+            }
+            None => encoded,
+        }
+    }
+
+    pub fn to_bytes(&self, color: Color, x: usize, y: usize) -> [u8; 3] {
+        let encoded = self.encode(color);
+        let offset = self.dither.offset(x, y);
+        let quantize = |c: f32| (c * 255. + offset).round().clamp(0., 255.) as u8;
+        [
+            quantize(encoded.red),
+            quantize(encoded.green),
+            quantize(encoded.blue),
+        ]
+    }
+
+    pub fn canvas_to_bytes(&self, canvas: &Canvas) -> Vec<Vec<[u8; 3]>> {
+        (0..canvas.height)
+            .map(|y| {
+                (0..canvas.width)
+                    .map(|x| self.to_bytes(pixel_at(canvas, x, y), x, y))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+// Like `canvas_to_ppm`, but through a display transform rather than a plain clamp
+pub fn canvas_to_ppm_with(canvas: &Canvas, transform: &DisplayTransform) -> Vec<String> {
+    ppm_from_bytes(
+        canvas.width,
+        canvas.height,
+        &transform.canvas_to_bytes(canvas),
+    )
+}
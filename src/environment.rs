@@ -0,0 +1,225 @@
+use std::f32::consts::PI;
+use std::sync::Arc;
+
+use rand::Rng;
+
+use crate::{
+    background::equirectangular_uv,
+    canvas::Canvas,
+    dot,
+    intersections::{hit, Precomputation},
+    normalize,
+    ray::Ray,
+    reflect,
+    sampling::{cosine_sample_hemisphere, orthonormal_basis, Distribution2D},
+    uv::uv_sample,
+    world::{intersect_world, World},
+    Color, Tuple,
+};
+
+// Lights the scene from an equirectangular environment map, usually an HDR
+// image loaded with `hdr::canvas_from_hdr`
+#[derive(Debug, Clone)]
+pub struct EnvironmentLight {
+    pub map: Arc<Canvas>,
+    pub intensity: f32,
+    // Light samples and BRDF samples taken per shading point
+    pub samples: usize,
+    distribution: Distribution2D,
+}
+
+impl PartialEq for EnvironmentLight {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.map, &other.map)
+            && self.intensity == other.intensity
+            && self.samples == other.samples
+    }
+}
+
+// Inverse of `equirectangular_uv`
+pub fn equirectangular_direction(u: f32, v: f32) -> Tuple {
+    let longitude = (0.5 - u) * 2. * PI;
+    let latitude = (v - 0.5) * PI;
+    Tuple::new(
+        latitude.cos() * longitude.sin(),
+        latitude.sin(),
+        -latitude.cos() * longitude.cos(),
+        0.,
+    )
+}
+
+impl EnvironmentLight {
+    pub fn new(map: Arc<Canvas>) -> Result<Self, &'static str> {
+        if map.width == 0 || map.height == 0 {
+            return Err("Environment maps can't be empty");
+        }
+        // Rows near the poles cover less solid angle, so weight by cos(latitude)
+        let rows = (0..map.height)
+            .map(|y| {
+                let latitude = (0.5 - (y as f32 + 0.5) / map.height as f32) * PI;
+                map.pixels[y]
+                    .iter()
+                    .map(|pixel| pixel.luminance().max(0.) * latitude.cos())
+                    .collect()
+            })
+            .collect();
+        Ok(EnvironmentLight {
+            map,
+            intensity: 1.,
+            samples: 16,
+            distribution: Distribution2D::new(rows),
+        })
+    }
+
+    pub fn radiance(&self, direction: Tuple) -> Color {
+        let (u, v) = equirectangular_uv(direction);
+        uv_sample(&self.map, u, v) * self.intensity
+    }
+
+    // The distribution runs top to bottom like the canvas rows, v runs upwards
+    pub fn sample(&self, u0: f32, u1: f32) -> (Tuple, f32) {
+        let ((u, s), pdf) = self.distribution.sample(u0, u1);
+        let v = 1. - s;
+        let cos_latitude = ((v - 0.5) * PI).cos();
+        if pdf == 0. || cos_latitude <= 0. {
+            return (equirectangular_direction(u, v), 0.);
+        }
+        (
+            equirectangular_direction(u, v),
+            pdf / (2. * PI * PI * cos_latitude),
+        )
+    }
+
+    // Density with respect to solid angle
+    pub fn pdf(&self, direction: Tuple) -> f32 {
+        let (u, v) = equirectangular_uv(direction);
+        let cos_latitude = ((v - 0.5) * PI).cos();
+        if cos_latitude <= 0. {
+            return 0.;
+        }
+        self.distribution.pdf(u, 1. - v) / (2. * PI * PI * cos_latitude)
+    }
+}
+
+// Normalised Lambert plus Phong lobe matching the material's diffuse and
+// specular terms
+struct SurfaceResponse {
+    albedo: Color,
+    diffuse: f32,
+    specular: f32,
+    shininess: f32,
+    normal: Tuple,
+    mirror: Tuple,
+}
+
+impl SurfaceResponse {
+    fn new(comps: &Precomputation) -> Self {
+        let object = &comps.object;
+        let material = object.material();
+        let point = comps.over_point;
+        SurfaceResponse {
+            albedo: material.color_at(object, point),
+            diffuse: material.diffuse_at(object, point),
+            specular: material.specular_at(object, point),
+            shininess: material.shininess_at(object, point),
+            normal: comps.normalv,
+            mirror: normalize(reflect(-comps.eyev, comps.normalv)),
+        }
+    }
+
+    fn diffuse_weight(&self) -> f32 {
+        let diffuse = self.diffuse * self.albedo.luminance();
+        let total = diffuse + self.specular;
+        if total > 0. {
+            diffuse / total
+        } else {
+            1.
+        }
+    }
+
+    // BRDF times the cosine term
+    fn eval(&self, direction: Tuple) -> Color {
+        let cos_theta = dot(self.normal, direction);
+        if cos_theta <= 0. {
+            return Color::new(0., 0., 0.);
+        }
+        let diffuse = self.albedo * (self.diffuse / PI);
+        let cos_alpha = dot(self.mirror, direction).max(0.);
+        let glossy =
+            self.specular * (self.shininess + 2.) / (2. * PI) * cos_alpha.powf(self.shininess);
+        (diffuse + Color::new(glossy, glossy, glossy)) * cos_theta
+    }
+
+    fn pdf(&self, direction: Tuple) -> f32 {
+        let cos_theta = dot(self.normal, direction);
+        if cos_theta <= 0. {
+            return 0.;
+        }
+        let cos_alpha = dot(self.mirror, direction).max(0.);
+        let glossy = (self.shininess + 1.) / (2. * PI) * cos_alpha.powf(self.shininess);
+        let weight = self.diffuse_weight();
+        weight * cos_theta / PI + (1. - weight) * glossy
+    }
+
+    fn sample<R: Rng>(&self, rng: &mut R) -> Tuple {
+        if rng.gen::<f32>() < self.diffuse_weight() {
+            return cosine_sample_hemisphere(self.normal, rng.gen(), rng.gen());
+        }
+        let cos_alpha = rng.gen::<f32>().powf(1. / (self.shininess + 1.));
+        let sin_alpha = f32::sqrt((1. - cos_alpha * cos_alpha).max(0.));
+        let phi = 2. * PI * rng.gen::<f32>();
+        let (tangent, bitangent) = orthonormal_basis(self.mirror);
+        tangent * (sin_alpha * phi.cos())
+            + bitangent * (sin_alpha * phi.sin())
+            + self.mirror * cos_alpha
+    }
+}
+
+// Power heuristic weight for a sample drawn with density `f`, written as a
+// ratio so the near-infinite densities at the poles don't overflow
+fn power_heuristic(f: f32, g: f32) -> f32 {
+    let ratio = g / f;
+    1. / (1. + ratio * ratio)
+}
+
+fn unoccluded(world: &World, point: Tuple, direction: Tuple) -> bool {
+    hit(intersect_world(world, Ray::new(point, direction))).is_none()
+}
+
+// Diffuse and glossy light from the environment, combining light and BRDF
+// sampling with the power heuristic
+pub fn environment_lighting<R: Rng>(world: &World, comps: &Precomputation, rng: &mut R) -> Color {
+    let black = Color::new(0., 0., 0.);
+    let Some(environment) = &world.environment else {
+        return black;
+    };
+    if environment.samples == 0 {
+        return black;
+    }
+    let surface = SurfaceResponse::new(comps);
+    let mut total = black;
+
+    for _ in 0..environment.samples {
+        let (direction, light_pdf) = environment.sample(rng.gen(), rng.gen());
+        if light_pdf > 0. {
+            let f = surface.eval(direction);
+            if f != black && unoccluded(world, comps.over_point, direction) {
+                let brdf_pdf = surface.pdf(direction);
+                let weight = power_heuristic(light_pdf, brdf_pdf);
+                total = total + f * environment.radiance(direction) * (weight / light_pdf);
+            }
+        }
+
+        let direction = surface.sample(rng);
+        let brdf_pdf = surface.pdf(direction);
+        if brdf_pdf > 0. {
+            let f = surface.eval(direction);
+            if f != black && unoccluded(world, comps.over_point, direction) {
+                let light_pdf = environment.pdf(direction);
+                let weight = power_heuristic(brdf_pdf, light_pdf);
+                total = total + f * environment.radiance(direction) * (weight / brdf_pdf);
+            }
+        }
+    }
+    total * (1. / environment.samples as f32)
+}
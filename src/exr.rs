@@ -0,0 +1,203 @@
+use std::io::Write;
+
+use flate2::{write::ZlibEncoder, Compression};
+
+use crate::canvas::{alpha_at, pixel_at, straight_pixel_at, AlphaMode, Canvas};
+
+const MAGIC: [u8; 4] = [0x76, 0x2f, 0x31, 0x01];
+const PIXEL_TYPE_FLOAT: i32 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ExrCompression {
+    #[default]
+    None,
+    // Deflate over blocks of 16 scanlines
+    Zip,
+}
+
+impl ExrCompression {
+    fn id(&self) -> u8 {
+        match self {
+            ExrCompression::None => 0,
+            ExrCompression::Zip => 3,
+        }
+    }
+
+    fn lines_per_block(&self) -> usize {
+        match self {
+            ExrCompression::None => 1,
+            ExrCompression::Zip => 16,
+        }
+    }
+}
+
+fn attribute(header: &mut Vec<u8>, name: &str, kind: &str, value: &[u8]) {
+    header.extend_from_slice(name.as_bytes());
+    header.push(0);
+    header.extend_from_slice(kind.as_bytes());
+    header.push(0);
+    header.extend_from_slice(&(value.len() as i32).to_le_bytes());
+    header.extend_from_slice(value);
+}
+
+// Channel names for a file with or without alpha
+fn channel_names(alpha: Option<AlphaMode>) -> &'static [&'static str] {
+    match alpha {
+        Some(_) => &["A", "B", "G", "R"],
+        None => &["B", "G", "R"],
+    }
+}
+
+fn header(canvas: &Canvas, compression: ExrCompression, alpha: Option<AlphaMode>) -> Vec<u8> {
+    let mut header = Vec::new();
+    header.extend_from_slice(&MAGIC);
+    // Version 2, single-part scanline file
+    header.extend_from_slice(&2u32.to_le_bytes());
+
+    // Channels must be listed in alphabetical order
+    let mut channels = Vec::new();
+    for name in channel_names(alpha) {
+        channels.extend_from_slice(name.as_bytes());
+        channels.push(0);
+        channels.extend_from_slice(&PIXEL_TYPE_FLOAT.to_le_bytes());
+        // pLinear and three reserved bytes
+        channels.extend_from_slice(&[0, 0, 0, 0]);
+        // x and y sampling
+        channels.extend_from_slice(&1i32.to_le_bytes());
+        channels.extend_from_slice(&1i32.to_le_bytes());
+    }
+    channels.push(0);
+    attribute(&mut header, "channels", "chlist", &channels);
+    attribute(
+        &mut header,
+        "compression",
+        "compression",
+        &[compression.id()],
+    );
+
+    let mut window = Vec::new();
+    for value in [0, 0, canvas.width as i32 - 1, canvas.height as i32 - 1] {
+        window.extend_from_slice(&value.to_le_bytes());
+    }
+    attribute(&mut header, "dataWindow", "box2i", &window);
+    attribute(&mut header, "displayWindow", "box2i", &window);
+    // Increasing y
+    attribute(&mut header, "lineOrder", "lineOrder", &[0]);
+    attribute(
+        &mut header,
+        "pixelAspectRatio",
+        "float",
+        &1f32.to_le_bytes(),
+    );
+    attribute(&mut header, "screenWindowCenter", "v2f", &[0; 8]);
+    attribute(
+        &mut header,
+        "screenWindowWidth",
+        "float",
+        &1f32.to_le_bytes(),
+    );
+    header.push(0);
+    header
+}
+
+// Scanlines store each channel's values in turn, in the header's channel order
+fn raw_block(canvas: &Canvas, first: usize, last: usize, alpha: Option<AlphaMode>) -> Vec<u8> {
+    let names = channel_names(alpha);
+    let mut data = Vec::with_capacity((last - first) * canvas.width * names.len() * 4);
+    for y in first..last {
+        for name in names {
+            for x in 0..canvas.width {
+                let pixel = match alpha {
+                    Some(AlphaMode::Straight) => straight_pixel_at(canvas, x, y),
+                    _ => pixel_at(canvas, x, y),
+                };
+                let value = match *name {
+                    "A" => alpha_at(canvas, x, y),
+                    "B" => pixel.blue,
+                    "G" => pixel.green,
+                    _ => pixel.red,
+                };
+                data.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+    }
+    data
+}
+
+// OpenEXR's ZIP preprocessing: split even and odd bytes into two halves, then
+// store each byte as the difference from the one before it
+fn zip_block(raw: &[u8]) -> Result<Vec<u8>, &'static str> {
+    let mut reordered: Vec<u8> = raw.iter().step_by(2).copied().collect();
+    reordered.extend(raw.iter().skip(1).step_by(2));
+    let mut previous = reordered.first().copied().unwrap_or(0);
+    for byte in reordered.iter_mut().skip(1) {
+        let current = *byte;
+        *byte = current.wrapping_sub(previous).wrapping_add(128);
+        previous = current;
+    }
+
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(&reordered)
+        .map_err(|_| "Could not compress EXR data")?;
+    encoder.finish().map_err(|_| "Could not compress EXR data")
+}
+
+// Write a canvas as a single-part scanline OpenEXR image with 32-bit float
+// channels, so values above one survive untouched
+pub fn canvas_to_exr(
+    canvas: &Canvas,
+    compression: ExrCompression,
+) -> Result<Vec<u8>, &'static str> {
+    write_exr(canvas, compression, None)
+}
+
+// Like `canvas_to_exr`, with an extra alpha channel. OpenEXR readers expect
+// premultiplied color.
+pub fn canvas_to_exr_with_alpha(
+    canvas: &Canvas,
+    compression: ExrCompression,
+    mode: AlphaMode,
+) -> Result<Vec<u8>, &'static str> {
+    write_exr(canvas, compression, Some(mode))
+}
+
+fn write_exr(
+    canvas: &Canvas,
+    compression: ExrCompression,
+    alpha: Option<AlphaMode>,
+) -> Result<Vec<u8>, &'static str> {
+    let lines = compression.lines_per_block();
+    let mut chunks = Vec::new();
+    for first in (0..canvas.height).step_by(lines) {
+        let last = (first + lines).min(canvas.height);
+        let raw = raw_block(canvas, first, last, alpha);
+        let data = match compression {
+            ExrCompression::None => raw,
+            ExrCompression::Zip => {
+                // Blocks that don't shrink are stored as they are
+                let zipped = zip_block(&raw)?;
+                if zipped.len() < raw.len() {
+                    zipped
+                } else {
+                    raw
+                }
+            }
+        };
+        chunks.push((first, data));
+    }
+
+    let mut file = header(canvas, compression, alpha);
+    // The offset table points at each chunk from the start of the file
+    let mut offset = file.len() + chunks.len() * 8;
+    for (_, data) in &chunks {
+        file.extend_from_slice(&(offset as u64).to_le_bytes());
+        offset += 8 + data.len();
+    }
+    for (first, data) in chunks {
+        file.extend_from_slice(&(first as i32).to_le_bytes());
+        file.extend_from_slice(&(data.len() as i32).to_le_bytes());
+        file.extend_from_slice(&data);
+    }
+    Ok(file)
+}
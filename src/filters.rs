@@ -0,0 +1,52 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Filter {
+    Box,
+    Tent,
+    Gaussian,
+    // Mitchell-Netravali with B = C = 1/3
+    Mitchell,
+}
+
+impl Filter {
+    // Half-width of the filter's support in pixels
+    pub fn radius(&self) -> f32 {
+        match self {
+            Filter::Box => 0.5,
+            Filter::Tent => 1.,
+            Filter::Gaussian => 1.5,
+            Filter::Mitchell => 2.,
+        }
+    }
+
+    pub fn weight(&self, dx: f32, dy: f32) -> f32 {
+        self.weight_1d(dx) * self.weight_1d(dy)
+    }
+
+    fn weight_1d(&self, x: f32) -> f32 {
+        let x = x.abs();
+        if x > self.radius() {
+            return 0.;
+        }
+        match self {
+            Filter::Box => 1.,
+            Filter::Tent => 1. - x,
+            Filter::Gaussian => {
+                let sigma: f32 = 0.5;
+                let gaussian = |v: f32| f32::exp(-v * v / (2. * sigma * sigma));
+                (gaussian(x) - gaussian(self.radius())).max(0.)
+            }
+            Filter::Mitchell => mitchell(x, 1. / 3., 1. / 3.),
+        }
+    }
+}
+
+fn mitchell(x: f32, b: f32, c: f32) -> f32 {
+    let x2 = x * x;
+    let x3 = x2 * x;
+    let value = if x < 1. {
+        (12. - 9. * b - 6. * c) * x3 + (-18. + 12. * b + 6. * c) * x2 + (6. - 2. * b)
+    } else {
+        (-b - 6. * c) * x3 + (6. * b + 30. * c) * x2 + (-12. * b - 48. * c) * x + (8. * b + 24. * c)
+    };
+    value / 6.
+}
@@ -0,0 +1,144 @@
+use std::{fs, path::Path};
+
+use crate::{
+    camera::{render_with, Camera, RenderSettings},
+    canvas::{canvas_from_pfm, canvas_to_pfm, canvas_to_ppm, Canvas},
+    metrics::{compare, difference_image, ImageMetrics},
+    world::World,
+};
+
+// Set to anything to write new references instead of comparing against them
+pub const REGENERATE_ENV: &str = "TRACER_REGENERATE_GOLDEN";
+
+// How far an image may drift from its reference before the test fails
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerance {
+    pub min_psnr: f32,
+    pub min_ssim: f32,
+    pub max_difference: f32,
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Tolerance {
+            min_psnr: 50.,
+            min_ssim: 0.999,
+            max_difference: 0.02,
+        }
+    }
+}
+
+impl Tolerance {
+    pub fn accepts(&self, metrics: &ImageMetrics) -> bool {
+        metrics.psnr >= self.min_psnr
+            && metrics.ssim >= self.min_ssim
+            && metrics.max_difference <= self.max_difference
+    }
+}
+
+fn write(path: &Path, data: &[u8]) -> Result<(), String> {
+    fs::write(path, data).map_err(|e| format!("Could not write {}: {}", path.display(), e))
+}
+
+pub fn regenerate_requested() -> bool {
+    std::env::var_os(REGENERATE_ENV).is_some()
+}
+
+// Compare `actual` with the reference `<dir>/<name>.pfm`. On failure the
+// rendered image and a false-color difference are written next to it as
+// `<name>.actual.pfm` and `<name>.diff.ppm`. With `regenerate`, the reference
+// is replaced by `actual` instead.
+pub fn compare_to_reference(
+    dir: &Path,
+    name: &str,
+    actual: &Canvas,
+    tolerance: Tolerance,
+    regenerate: bool,
+) -> Result<ImageMetrics, String> {
+    let reference_path = dir.join(format!("{}.pfm", name));
+    let actual_path = dir.join(format!("{}.actual.pfm", name));
+    let diff_path = dir.join(format!("{}.diff.ppm", name));
+
+    if regenerate {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Could not create {}: {}", dir.display(), e))?;
+        write(&reference_path, &canvas_to_pfm(actual))?;
+        // Stale failure output would only confuse the next look
+        let _ = fs::remove_file(&actual_path);
+        let _ = fs::remove_file(&diff_path);
+        return compare(actual, actual).map_err(String::from);
+    }
+
+    let reference = match fs::read(&reference_path) {
+        Ok(data) => {
+            canvas_from_pfm(&data).map_err(|e| format!("{}: {}", reference_path.display(), e))?
+        }
+        Err(_) => {
+            write(&actual_path, &canvas_to_pfm(actual))?;
+            return Err(format!(
+                "Missing reference {}; rerun with {}=1 to create it",
+                reference_path.display(),
+                REGENERATE_ENV
+            ));
+        }
+    };
+    if (reference.width, reference.height) != (actual.width, actual.height) {
+        write(&actual_path, &canvas_to_pfm(actual))?;
+        return Err(format!(
+            "{} is {}x{} but the render is {}x{}",
+            reference_path.display(),
+            reference.width,
+            reference.height,
+            actual.width,
+            actual.height
+        ));
+    }
+
+    let metrics = compare(&reference, actual).map_err(String::from)?;
+    if tolerance.accepts(&metrics) {
+        let _ = fs::remove_file(&actual_path);
+        let _ = fs::remove_file(&diff_path);
+        return Ok(metrics);
+    }
+    write(&actual_path, &canvas_to_pfm(actual))?;
+    let diff = difference_image(&reference, actual, tolerance.max_difference.max(1e-6))
+        .map_err(String::from)?;
+    write(
+        &diff_path,
+        (canvas_to_ppm(&diff).join("\n") + "\n").as_bytes(),
+    )?;
+    Err(format!(
+        "{} differs from its reference: PSNR {:.2} dB, SSIM {:.5}, max difference {:.5} \
+         (see {} and {})",
+        name,
+        metrics.psnr,
+        metrics.ssim,
+        metrics.max_difference,
+        actual_path.display(),
+        diff_path.display()
+    ))
+}
+
+// `compare_to_reference`, regenerating when `REGENERATE_ENV` is set
+pub fn check_reference(
+    dir: &Path,
+    name: &str,
+    actual: &Canvas,
+    tolerance: Tolerance,
+) -> Result<ImageMetrics, String> {
+    compare_to_reference(dir, name, actual, tolerance, regenerate_requested())
+}
+
+// Render `world` and check it against its reference. Keep the camera small;
+// references are stored as uncompressed floats.
+pub fn check_render(
+    dir: &Path,
+    name: &str,
+    camera: Camera,
+    world: &World,
+    settings: &RenderSettings,
+    tolerance: Tolerance,
+) -> Result<ImageMetrics, String> {
+    let actual = render_with(camera, world, settings).map_err(String::from)?;
+    check_reference(dir, name, &actual, tolerance)
+}
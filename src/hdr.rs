@@ -0,0 +1,252 @@
+use crate::{
+    canvas::{pixel_at, write_pixel, Canvas},
+    Color,
+};
+
+// Shared 8-bit exponent, as in Radiance's colr_color
+pub fn rgbe_to_color(rgbe: [u8; 4]) -> Color {
+    if rgbe[3] == 0 {
+        return Color::new(0., 0., 0.);
+    }
+    let scale = f32::powi(2., rgbe[3] as i32 - (128 + 8));
+    Color::new(
+        (rgbe[0] as f32 + 0.5) * scale,
+        (rgbe[1] as f32 + 0.5) * scale,
+        (rgbe[2] as f32 + 0.5) * scale,
+    )
+}
+
+// Inverse of `rgbe_to_color`: the mantissas share the exponent of the
+// brightest channel. Negative and non-finite values are written as zero, and
+// anything too bright for the 8-bit exponent saturates.
+pub fn color_to_rgbe(color: Color) -> [u8; 4] {
+    const MAX_EXPONENT: i32 = 127;
+    let channel = |c: f32| if c.is_finite() { c.max(0.) } else { 0. };
+    let (red, green, blue) = (
+        channel(color.red),
+        channel(color.green),
+        channel(color.blue),
+    );
+    let brightest = red.max(green).max(blue);
+    if brightest < 1e-32 {
+        return [0, 0, 0, 0];
+    }
+    let mut exponent = brightest.log2().floor() as i32 + 1;
+    // log2 can round up to the next power of two
+    if brightest / f32::powi(2., exponent) >= 1. {
+        exponent += 1;
+    }
+    let exponent = exponent.min(MAX_EXPONENT);
+    let scale = 256. / f32::powi(2., exponent);
+    [
+        (red * scale).min(255.) as u8,
+        (green * scale).min(255.) as u8,
+        (blue * scale).min(255.) as u8,
+        (exponent + 128) as u8,
+    ]
+}
+
+fn read_line<'a>(data: &'a [u8], pos: &mut usize) -> Result<&'a str, &'static str> {
+    let start = *pos;
+    let end = data[start..]
+        .iter()
+        .position(|&b| b == b'\n')
+        .map(|offset| start + offset)
+        .ok_or("Unexpected end of HDR header")?;
+    *pos = end + 1;
+    std::str::from_utf8(&data[start..end]).map_err(|_| "Invalid HDR header")
+}
+
+fn next_byte(data: &[u8], pos: &mut usize) -> Result<u8, &'static str> {
+    let byte = *data.get(*pos).ok_or("Unexpected end of HDR data")?;
+    *pos += 1;
+    Ok(byte)
+}
+
+// Adaptive run-length scanline: each channel is stored separately as a series
+// of runs (count > 128) and literal dumps
+fn read_rle_scanline(
+    data: &[u8],
+    pos: &mut usize,
+    width: usize,
+) -> Result<Vec<[u8; 4]>, &'static str> {
+    let mut scanline = vec![[0u8; 4]; width];
+    for channel in 0..4 {
+        let mut x = 0;
+        while x < width {
+            let count = next_byte(data, pos)? as usize;
+            if count > 128 {
+                let run = count - 128;
+                if x + run > width {
+                    return Err("HDR run overflows the scanline");
+                }
+                let value = next_byte(data, pos)?;
+                for pixel in &mut scanline[x..x + run] {
+                    pixel[channel] = value;
+                }
+                x += run;
+            } else {
+                if count == 0 || x + count > width {
+                    return Err("Invalid HDR literal run");
+                }
+                for pixel in &mut scanline[x..x + count] {
+                    pixel[channel] = next_byte(data, pos)?;
+                }
+                x += count;
+            }
+        }
+    }
+    Ok(scanline)
+}
+
+// Flat pixels, possibly with old-style repeat markers (1, 1, 1, count)
+fn read_flat_scanline(
+    data: &[u8],
+    pos: &mut usize,
+    width: usize,
+) -> Result<Vec<[u8; 4]>, &'static str> {
+    let mut scanline: Vec<[u8; 4]> = Vec::with_capacity(width);
+    let mut shift = 0;
+    while scanline.len() < width {
+        let mut pixel = [0u8; 4];
+        for value in pixel.iter_mut() {
+            *value = next_byte(data, pos)?;
+        }
+        if pixel[..3] == [1, 1, 1] {
+            let previous = *scanline.last().ok_or("HDR repeat before any pixel")?;
+            // Each further marker adds a higher byte to the count
+            if shift + 8 > usize::BITS {
+                return Err("HDR run overflows the scanline");
+            }
+            let count = (pixel[3] as usize) << shift;
+            if count > width - scanline.len() {
+                return Err("HDR run overflows the scanline");
+            }
+            scanline.extend(std::iter::repeat_n(previous, count));
+            shift += 8;
+        } else {
+            scanline.push(pixel);
+            shift = 0;
+        }
+    }
+    Ok(scanline)
+}
+
+// Read a Radiance RGBE (.hdr) image into a float canvas
+pub fn canvas_from_hdr(data: &[u8]) -> Result<Canvas, &'static str> {
+    let mut pos = 0;
+    if !read_line(data, &mut pos)?.starts_with("#?") {
+        return Err("Not a Radiance HDR file");
+    }
+
+    loop {
+        let line = read_line(data, &mut pos)?;
+        if line.is_empty() {
+            break;
+        }
+        if let Some(format) = line.strip_prefix("FORMAT=") {
+            if format.trim() != "32-bit_rle_rgbe" {
+                return Err("Only 32-bit_rle_rgbe HDR images are supported");
+            }
+        }
+    }
+
+    let resolution: Vec<&str> = read_line(data, &mut pos)?.split_whitespace().collect();
+    let (height, width) = match resolution[..] {
+        ["-Y", height, "+X", width] => (
+            height.parse::<usize>().map_err(|_| "Invalid HDR height")?,
+            width.parse::<usize>().map_err(|_| "Invalid HDR width")?,
+        ),
+        _ => return Err("Only -Y +X HDR orientation is supported"),
+    };
+
+    let mut canvas = Canvas::new(width, height);
+    for y in 0..height {
+        let is_rle = (8..32768).contains(&width)
+            && data.len() >= pos + 4
+            && data[pos] == 2
+            && data[pos + 1] == 2
+            && data[pos + 2] < 128;
+        let scanline = if is_rle {
+            let encoded_width = ((data[pos + 2] as usize) << 8) | data[pos + 3] as usize;
+            if encoded_width != width {
+                return Err("HDR scanline width mismatch");
+            }
+            pos += 4;
+            read_rle_scanline(data, &mut pos, width)?
+        } else {
+            read_flat_scanline(data, &mut pos, width)?
+        };
+        for (x, rgbe) in scanline.into_iter().enumerate() {
+            write_pixel(&mut canvas, x, y, rgbe_to_color(rgbe));
+        }
+    }
+    Ok(canvas)
+}
+
+// One channel of an adaptive run-length scanline. Runs shorter than four
+// bytes are cheaper as part of a literal dump.
+fn write_rle_channel(out: &mut Vec<u8>, values: &[u8]) {
+    const MIN_RUN: usize = 4;
+    let mut current = 0;
+    while current < values.len() {
+        // Find the start of the next run worth encoding
+        let mut run_start = current;
+        let mut run = 0;
+        let mut previous_run = 0;
+        while run < MIN_RUN && run_start < values.len() {
+            run_start += run;
+            previous_run = run;
+            run = 1;
+            while run_start + run < values.len()
+                && run < 127
+                && values[run_start + run] == values[run_start]
+            {
+                run += 1;
+            }
+        }
+        // A short run right before it can still be a run of its own
+        if previous_run > 1 && previous_run == run_start - current {
+            out.extend_from_slice(&[128 + previous_run as u8, values[current]]);
+            current = run_start;
+        }
+        while current < run_start {
+            let count = (run_start - current).min(128);
+            out.push(count as u8);
+            out.extend_from_slice(&values[current..current + count]);
+            current += count;
+        }
+        if run >= MIN_RUN {
+            out.extend_from_slice(&[128 + run as u8, values[run_start]]);
+            current += run;
+        }
+    }
+}
+
+// Write a canvas as a Radiance RGBE (.hdr) image, keeping values above one.
+// Scanlines are run-length encoded when the width allows it.
+pub fn canvas_to_hdr(canvas: &Canvas) -> Vec<u8> {
+    let mut data = format!(
+        "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y {} +X {}\n",
+        canvas.height, canvas.width
+    )
+    .into_bytes();
+    let use_rle = (8..32768).contains(&canvas.width);
+    for y in 0..canvas.height {
+        let scanline: Vec<[u8; 4]> = (0..canvas.width)
+            .map(|x| color_to_rgbe(pixel_at(canvas, x, y)))
+            .collect();
+        if use_rle {
+            data.extend_from_slice(&[2, 2, (canvas.width >> 8) as u8, (canvas.width & 0xff) as u8]);
+            for channel in 0..4 {
+                let values: Vec<u8> = scanline.iter().map(|rgbe| rgbe[channel]).collect();
+                write_rle_channel(&mut data, &values);
+            }
+        } else {
+            for rgbe in scanline {
+                data.extend_from_slice(&rgbe);
+            }
+        }
+    }
+    data
+}
@@ -0,0 +1,228 @@
+use rand::{rngs::SmallRng, Rng};
+
+use crate::{
+    aov::{trace_aovs, trace_features, AovSample},
+    dot,
+    environment::environment_lighting,
+    intersections::{hit, prepare_computations, shlick, Precomputation},
+    lights::{lighting_directional, lighting_occluded},
+    magnitude,
+    medium::{has_media, light_transmittance, lit_through_media, march},
+    normalize,
+    photon::caustic_lighting,
+    ray::Ray,
+    sampling::cosine_sample_hemisphere,
+    world::{color_at, intersect_world, is_shadowed, is_sun_shadowed, World},
+    Color, Tuple,
+};
+
+pub trait Integrator: Sync {
+    // Radiance arriving along the ray
+    fn li(&self, world: &World, ray: Ray, rng: &mut SmallRng) -> Color;
+
+    // The same radiance together with the AOVs of the ray. Integrators that
+    // don't keep the lighting terms apart report all of it as direct light.
+    fn li_aovs(&self, world: &World, ray: Ray, rng: &mut SmallRng) -> (Color, AovSample) {
+        let features = trace_features(world, ray);
+        let color = self.li(world, ray, rng);
+        (
+            color,
+            AovSample {
+                direct: color,
+                ..features
+            },
+        )
+    }
+}
+
+// The classic recursive tracer: direct light from the point light plus perfect
+// mirror and refraction rays, with the ambient term standing in for bounce light
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Whitted {
+    pub max_depth: u16,
+}
+
+impl Whitted {
+    pub fn new(max_depth: u16) -> Self {
+        Whitted { max_depth }
+    }
+}
+
+impl Integrator for Whitted {
+    fn li(&self, world: &World, ray: Ray, _: &mut SmallRng) -> Color {
+        color_at(world, ray, self.max_depth)
+    }
+
+    fn li_aovs(&self, world: &World, ray: Ray, _: &mut SmallRng) -> (Color, AovSample) {
+        trace_aovs(world, ray, self.max_depth)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PathTracer {
+    pub max_depth: u16,
+    // Bounces before Russian roulette may start terminating paths
+    pub russian_roulette_depth: u16,
+}
+
+impl Default for PathTracer {
+    fn default() -> Self {
+        PathTracer {
+            max_depth: 8,
+            russian_roulette_depth: 3,
+        }
+    }
+}
+
+// Direct light from the point light, sun and any environment light, plus
+// caustics from the photon map, leaving out the ambient approximation
+fn direct_lighting(world: &World, comps: &Precomputation, rng: &mut SmallRng) -> Color {
+    let environment = environment_lighting(world, comps, rng);
+    let point_light = match world.light {
+        Some(light) => {
+            let mut material = comps.object.material().clone();
+            material.ambient = 0.;
+            let lit = |in_shadow| {
+                lighting_occluded(
+                    &material,
+                    &comps.object,
+                    light,
+                    comps.over_point,
+                    comps.eyev,
+                    comps.normalv,
+                    in_shadow,
+                    1.,
+                )
+            };
+            if has_media(world) {
+                let v = light.position - comps.over_point;
+                let transmittance =
+                    light_transmittance(world, comps.over_point, normalize(v), magnitude(v));
+                lit_through_media(lit, transmittance)
+            } else {
+                lit(is_shadowed(world, comps.over_point))
+            }
+        }
+        None => Color::new(0., 0., 0.),
+    };
+    let sun = match world.sun {
+        Some(sun) => {
+            let mut material = comps.object.material().clone();
+            material.ambient = 0.;
+            let lit = |in_shadow| {
+                lighting_directional(
+                    &material,
+                    &comps.object,
+                    sun,
+                    comps.over_point,
+                    comps.eyev,
+                    comps.normalv,
+                    in_shadow,
+                    1.,
+                )
+            };
+            if has_media(world) {
+                let transmittance =
+                    light_transmittance(world, comps.over_point, sun.direction, f32::INFINITY);
+                lit_through_media(lit, transmittance)
+            } else {
+                lit(is_sun_shadowed(world, comps.over_point))
+            }
+        }
+        None => Color::new(0., 0., 0.),
+    };
+    point_light + sun + environment + caustic_lighting(world, comps)
+}
+
+pub fn refract_direction(comps: &Precomputation) -> Option<Tuple> {
+    let n_ratio = comps.n1 / comps.n2;
+    let cos_i = dot(comps.eyev, comps.normalv);
+    let sin2_t = n_ratio * n_ratio * (1. - cos_i * cos_i);
+    if sin2_t > 1. {
+        return None;
+    }
+    let cos_t = f32::sqrt(1. - sin2_t);
+    Some(comps.normalv * (n_ratio * cos_i - cos_t) - comps.eyev * n_ratio)
+}
+
+impl Integrator for PathTracer {
+    fn li(&self, world: &World, ray: Ray, rng: &mut SmallRng) -> Color {
+        let black = Color::new(0., 0., 0.);
+        let mut radiance = black;
+        let mut throughput = Color::new(1., 1., 1.);
+        let mut ray = ray;
+        let mut diffuse_bounce = false;
+
+        for depth in 0..self.max_depth {
+            let intersections = intersect_world(world, ray);
+            let closest = hit(intersections.clone());
+            if has_media(world) {
+                let distance = closest
+                    .as_ref()
+                    .map_or(f32::INFINITY, |intersection| intersection.t);
+                let (scattered, transmittance) = march(world, ray, distance, rng);
+                radiance = radiance + throughput * scattered;
+                throughput = throughput * transmittance;
+            }
+            let Some(intersection) = closest else {
+                // After a diffuse bounce the environment light was already
+                // counted by next-event estimation
+                if !(diffuse_bounce && world.environment.is_some()) {
+                    radiance = radiance + throughput * world.background.color_at(ray.direction);
+                }
+                break;
+            };
+            let comps = prepare_computations(intersection, ray, intersections);
+
+            // Next-event estimation: point lights can only be reached this way
+            radiance = radiance + throughput * direct_lighting(world, &comps, rng);
+
+            let material = comps.object.material();
+            let albedo = material.color_at(&comps.object, comps.over_point);
+            let diffuse = material.diffuse_at(&comps.object, comps.over_point);
+            let mut reflective = material.reflective_at(&comps.object, comps.over_point);
+            let mut transparency = material.transparency_at(&comps.object, comps.under_point);
+            if reflective > 0. && transparency > 0. {
+                let reflectance = shlick(comps.clone());
+                reflective *= reflectance;
+                transparency *= 1. - reflectance;
+            }
+
+            // Pick one lobe in proportion to its weight
+            let total = diffuse + reflective + transparency;
+            if total <= 0. {
+                break;
+            }
+            let choice = rng.gen::<f32>() * total;
+            diffuse_bounce = choice >= reflective + transparency;
+            let (direction, origin, weight) = if choice < reflective {
+                (comps.reflectv, comps.over_point, Color::new(1., 1., 1.))
+            } else if choice < reflective + transparency {
+                match refract_direction(&comps) {
+                    Some(direction) => (direction, comps.under_point, Color::new(1., 1., 1.)),
+                    None => (comps.reflectv, comps.over_point, Color::new(1., 1., 1.)),
+                }
+            } else {
+                // Cosine-weighted sampling cancels the cosine and 1/pi of the Lambertian lobe
+                let direction = cosine_sample_hemisphere(comps.normalv, rng.gen(), rng.gen());
+                (direction, comps.over_point, albedo)
+            };
+            throughput = throughput * weight * total;
+
+            if depth + 1 >= self.russian_roulette_depth {
+                let survival = throughput
+                    .red
+                    .max(throughput.green)
+                    .max(throughput.blue)
+                    .clamp(0.05, 1.);
+                if rng.gen::<f32>() > survival {
+                    break;
+                }
+                throughput = throughput * (1. / survival);
+            }
+
+            ray = Ray::new(origin, direction);
+        }
+        radiance
+    }
+}
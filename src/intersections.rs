@@ -1,4 +1,5 @@
 use crate::{
+    bump::perturb_normal,
     dot,
     ray::{position, Ray},
     reflect,
@@ -7,7 +8,7 @@ use crate::{
     Tuple,
 };
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct Intersection {
     pub t: f32,
     pub object: ShapeEnum,
@@ -24,8 +25,8 @@ pub fn hit(intersections: Vec<Intersection>) -> Option<Intersection> {
         .into_iter()
         .filter(|x| x.t >= 0.)
         .collect::<Vec<Intersection>>();
-    if valid_intersections.len() > 0 {
-        let mut min_intersection = valid_intersections[0];
+    if !valid_intersections.is_empty() {
+        let mut min_intersection = valid_intersections[0].clone();
         for intersect in valid_intersections {
             if intersect.t < min_intersection.t {
                 min_intersection = intersect;
@@ -36,7 +37,7 @@ pub fn hit(intersections: Vec<Intersection>) -> Option<Intersection> {
     None
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 pub struct Precomputation {
     pub t: f32,
     pub object: ShapeEnum,
@@ -57,22 +58,28 @@ pub fn prepare_computations(
     intersections: Vec<Intersection>,
 ) -> Precomputation {
     let pos = position(ray, intersection.t);
-    let mut normal = match intersection.object {
-        ShapeEnum::Plane(plane) => normal_at(plane, pos),
-        ShapeEnum::Sphere(sphere) => normal_at(sphere, pos),
-        ShapeEnum::Cube(cube) => normal_at(cube, pos),
-        ShapeEnum::Cylinder(cylinder) => normal_at(cylinder, pos),
-        ShapeEnum::Cone(cone) => normal_at(cone, pos),
+    let geometric = match &intersection.object {
+        ShapeEnum::Plane(plane) => normal_at(plane.clone(), pos),
+        ShapeEnum::Sphere(sphere) => normal_at(sphere.clone(), pos),
+        ShapeEnum::Cube(cube) => normal_at(cube.clone(), pos),
+        ShapeEnum::Cylinder(cylinder) => normal_at(cylinder.clone(), pos),
+        ShapeEnum::Cone(cone) => normal_at(cone.clone(), pos),
     };
+    // Bump and normal maps only shade, so the offsets below stay on the
+    // geometric normal and can't push the point back through the surface
+    // `normal_at` has already inverted the same transform, so this can't fail
+    let mut normal = perturb_normal(&intersection.object, pos, geometric).unwrap_or(geometric);
+    let mut offset = geometric;
     let eye = -ray.direction;
     let mut inside = false;
-    if dot(normal, eye) < 0. {
+    if dot(geometric, eye) < 0. {
         normal = -normal;
+        offset = -offset;
         inside = true;
     }
     // Based on experiments, seems like this amount of perturbation is needed to avoid acne
-    let over_point = pos + normal * 1e-4;
-    let under_point = pos - normal * 1e-4;
+    let over_point = pos + offset * 1e-4;
+    let under_point = pos - offset * 1e-4;
 
     let reflection = reflect(ray.direction, normal);
 
@@ -83,7 +90,7 @@ pub fn prepare_computations(
 
     for inter in intersections {
         if inter == intersection {
-            if containers.len() == 0 {
+            if containers.is_empty() {
                 n1 = 1.0;
             } else {
                 n1 = match containers.last().unwrap() {
@@ -97,13 +104,13 @@ pub fn prepare_computations(
         }
 
         if containers.contains(&inter.object) {
-            containers.retain(|&x| x != inter.object);
+            containers.retain(|x| *x != inter.object);
         } else {
-            containers.push(inter.object);
+            containers.push(inter.object.clone());
         }
 
         if inter == intersection {
-            if containers.len() == 0 {
+            if containers.is_empty() {
                 n2 = 1.;
             } else {
                 n2 = match containers.last().unwrap() {
@@ -3,20 +3,43 @@ use std::fmt;
 use std::fmt::Display;
 use std::ops::{Add, Div, Mul, Neg, Sub};
 
+pub mod aov;
+pub mod background;
+pub mod bump;
 pub mod camera;
 pub mod canvas;
+pub mod compositing;
 pub mod cube;
 pub mod cylinder;
+pub mod debug;
+pub mod denoise;
+pub mod display;
+pub mod environment;
+pub mod exr;
+pub mod filters;
+pub mod golden;
+pub mod hdr;
+pub mod integrators;
 pub mod intersections;
 pub mod lights;
+pub mod lut;
 pub mod materials;
 pub mod matrix;
+pub mod medium;
+pub mod metrics;
+pub mod occlusion;
 pub mod patterns;
+pub mod photon;
 pub mod plane;
+pub mod png;
+pub mod post;
 pub mod ray;
+pub mod sampling;
 pub mod shape;
+pub mod sky;
 pub mod sphere;
 pub mod transforms;
+pub mod uv;
 pub mod world;
 pub mod cone;
 
@@ -242,6 +265,10 @@ impl Color {
     pub fn new(red: f32, green: f32, blue: f32) -> Self {
         Color { red, green, blue }
     }
+
+    pub fn luminance(&self) -> f32 {
+        0.2126 * self.red + 0.7152 * self.green + 0.0722 * self.blue
+    }
 }
 
 pub fn reflect(vector: Tuple, normal: Tuple) -> Tuple {
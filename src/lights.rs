@@ -1,7 +1,4 @@
-use crate::{
-    dot, materials::Material, normalize, patterns::pattern_at_shape, reflect, world::ShapeEnum,
-    Color, Tuple,
-};
+use crate::{dot, materials::Material, normalize, reflect, world::ShapeEnum, Color, Tuple};
 
 #[derive(Debug, PartialEq, Copy, Clone)]
 pub struct PointLight {
@@ -18,6 +15,23 @@ impl PointLight {
     }
 }
 
+// Infinitely distant light such as the sun, shining along -direction
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct DirectionalLight {
+    // Unit vector pointing towards the light
+    pub direction: Tuple,
+    pub intensity: Color,
+}
+
+impl DirectionalLight {
+    pub fn new(direction: Tuple, intensity: Color) -> Self {
+        DirectionalLight {
+            direction: normalize(direction),
+            intensity,
+        }
+    }
+}
+
 pub fn lighting(
     material: Material,
     object: ShapeEnum,
@@ -27,14 +41,77 @@ pub fn lighting(
     normalv: Tuple,
     in_shadow: bool,
 ) -> Color {
-    let mut color = material.color;
-    if let Some(pattern) = material.pattern {
-        color = pattern_at_shape(pattern, object, point);
-    }
+    lighting_occluded(
+        &material, &object, light, point, eyev, normalv, in_shadow, 1.,
+    )
+}
 
-    let effective_color = color * light.intensity;
+// As `lighting`, with the ambient term scaled by an ambient occlusion visibility
+#[allow(clippy::too_many_arguments)]
+pub fn lighting_occluded(
+    material: &Material,
+    object: &ShapeEnum,
+    light: PointLight,
+    point: Tuple,
+    eyev: Tuple,
+    normalv: Tuple,
+    in_shadow: bool,
+    visibility: f32,
+) -> Color {
     let lightv = normalize(light.position - point);
-    let ambient = effective_color * material.ambient;
+    phong(
+        material,
+        object,
+        light.intensity,
+        lightv,
+        point,
+        eyev,
+        normalv,
+        in_shadow,
+        visibility,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn lighting_directional(
+    material: &Material,
+    object: &ShapeEnum,
+    light: DirectionalLight,
+    point: Tuple,
+    eyev: Tuple,
+    normalv: Tuple,
+    in_shadow: bool,
+    visibility: f32,
+) -> Color {
+    phong(
+        material,
+        object,
+        light.intensity,
+        light.direction,
+        point,
+        eyev,
+        normalv,
+        in_shadow,
+        visibility,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn phong(
+    material: &Material,
+    object: &ShapeEnum,
+    intensity: Color,
+    lightv: Tuple,
+    point: Tuple,
+    eyev: Tuple,
+    normalv: Tuple,
+    in_shadow: bool,
+    visibility: f32,
+) -> Color {
+    let color = material.color_at(object, point);
+
+    let effective_color = color * intensity;
+    let ambient = effective_color * material.ambient * visibility;
     let black = Color::new(0., 0., 0.);
 
     let light_dot_normal = dot(lightv, normalv);
@@ -42,13 +119,13 @@ pub fn lighting(
     let mut specular = black;
 
     if light_dot_normal >= 0. {
-        diffuse = effective_color * material.diffuse * light_dot_normal;
+        diffuse = effective_color * material.diffuse_at(object, point) * light_dot_normal;
         let reflectv = reflect(-lightv, normalv);
         let reflect_dot_eye = dot(reflectv, eyev);
 
         if reflect_dot_eye > 0. {
-            let factor = f32::powf(reflect_dot_eye, material.shininess);
-            specular = light.intensity * material.specular * factor;
+            let factor = f32::powf(reflect_dot_eye, material.shininess_at(object, point));
+            specular = intensity * material.specular_at(object, point) * factor;
         }
     }
 
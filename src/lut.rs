@@ -0,0 +1,228 @@
+use crate::{
+    canvas::{alpha_at, straight_pixel_at, write_pixel, Canvas},
+    Color,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum LutInterpolation {
+    Trilinear,
+    // Splits each lattice cell into six tetrahedra; keeps neutrals neutral
+    #[default]
+    Tetrahedral,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LutTable {
+    // One curve per channel, sampled at evenly spaced inputs
+    OneD(Vec<Color>),
+    // `size`^3 entries with red changing fastest, then green, then blue
+    ThreeD { size: usize, entries: Vec<Color> },
+}
+
+// An Adobe/Resolve `.cube` lookup table
+#[derive(Debug, Clone, PartialEq)]
+pub struct CubeLut {
+    pub title: String,
+    pub domain_min: Color,
+    pub domain_max: Color,
+    pub table: LutTable,
+}
+
+fn parse_color(values: &[&str]) -> Result<Color, &'static str> {
+    if values.len() != 3 {
+        return Err("Expected three values in LUT");
+    }
+    let mut channels = [0.; 3];
+    for (channel, value) in channels.iter_mut().zip(values) {
+        *channel = value.parse::<f32>().map_err(|_| "Invalid number in LUT")?;
+    }
+    Ok(Color::new(channels[0], channels[1], channels[2]))
+}
+
+fn parse_size(value: Option<&&str>) -> Result<usize, &'static str> {
+    let size = value
+        .ok_or("Missing LUT size")?
+        .parse::<usize>()
+        .map_err(|_| "Invalid LUT size")?;
+    if size < 2 {
+        return Err("LUT size must be at least 2");
+    }
+    Ok(size)
+}
+
+pub fn parse_cube(text: &str) -> Result<CubeLut, &'static str> {
+    let mut title = String::new();
+    let mut domain_min = Color::new(0., 0., 0.);
+    let mut domain_max = Color::new(1., 1., 1.);
+    let mut size_1d = None;
+    let mut size_3d = None;
+    let mut entries = Vec::new();
+
+    for line in text.lines() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(rest) = line.strip_prefix("TITLE") {
+            title = rest.trim().trim_matches('"').to_string();
+            continue;
+        }
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        match tokens[0] {
+            "LUT_1D_SIZE" => size_1d = Some(parse_size(tokens.get(1))?),
+            "LUT_3D_SIZE" => size_3d = Some(parse_size(tokens.get(1))?),
+            "DOMAIN_MIN" => domain_min = parse_color(&tokens[1..])?,
+            "DOMAIN_MAX" => domain_max = parse_color(&tokens[1..])?,
+            // Resolve's single-range form of the domain
+            "LUT_1D_INPUT_RANGE" | "LUT_3D_INPUT_RANGE" => {
+                if tokens.len() != 3 {
+                    return Err("Expected two values in LUT input range");
+                }
+                let min = tokens[1]
+                    .parse::<f32>()
+                    .map_err(|_| "Invalid number in LUT")?;
+                let max = tokens[2]
+                    .parse::<f32>()
+                    .map_err(|_| "Invalid number in LUT")?;
+                domain_min = Color::new(min, min, min);
+                domain_max = Color::new(max, max, max);
+            }
+            _ => entries.push(parse_color(&tokens)?),
+        }
+    }
+
+    if domain_max.red <= domain_min.red
+        || domain_max.green <= domain_min.green
+        || domain_max.blue <= domain_min.blue
+    {
+        return Err("LUT domain is empty");
+    }
+    let table = match (size_1d, size_3d) {
+        (Some(size), None) => {
+            if entries.len() != size {
+                return Err("LUT entry count doesn't match LUT_1D_SIZE");
+            }
+            LutTable::OneD(entries)
+        }
+        (None, Some(size)) => {
+            if entries.len() != size * size * size {
+                return Err("LUT entry count doesn't match LUT_3D_SIZE");
+            }
+            LutTable::ThreeD { size, entries }
+        }
+        (None, None) => return Err("LUT has no LUT_1D_SIZE or LUT_3D_SIZE"),
+        (Some(_), Some(_)) => return Err("Combined 1D and 3D LUTs are not supported"),
+    };
+    Ok(CubeLut {
+        title,
+        domain_min,
+        domain_max,
+        table,
+    })
+}
+
+// Position of `value` on a lattice of `size` points spanning [min, max]:
+// the lower index and the fraction towards the next one
+fn lattice(value: f32, min: f32, max: f32, size: usize) -> (usize, f32) {
+    let x = ((value - min) / (max - min)).clamp(0., 1.) * (size - 1) as f32;
+    let index = (x.floor() as usize).min(size - 2);
+    (index, x - index as f32)
+}
+
+impl CubeLut {
+    pub fn apply(&self, color: Color, interpolation: LutInterpolation) -> Color {
+        let (min, max) = (self.domain_min, self.domain_max);
+        match &self.table {
+            LutTable::OneD(curve) => {
+                let size = curve.len();
+                let (r, fr) = lattice(color.red, min.red, max.red, size);
+                let (g, fg) = lattice(color.green, min.green, max.green, size);
+                let (b, fb) = lattice(color.blue, min.blue, max.blue, size);
+                Color::new(
+                    curve[r].red + (curve[r + 1].red - curve[r].red) * fr,
+                    curve[g].green + (curve[g + 1].green - curve[g].green) * fg,
+                    curve[b].blue + (curve[b + 1].blue - curve[b].blue) * fb,
+                )
+            }
+            LutTable::ThreeD { size, entries } => {
+                let size = *size;
+                let (r, fr) = lattice(color.red, min.red, max.red, size);
+                let (g, fg) = lattice(color.green, min.green, max.green, size);
+                let (b, fb) = lattice(color.blue, min.blue, max.blue, size);
+                // Corner of the cell, offset by 0 or 1 along each axis
+                let c = |dr: usize, dg: usize, db: usize| {
+                    entries[(b + db) * size * size + (g + dg) * size + r + dr]
+                };
+                match interpolation {
+                    LutInterpolation::Trilinear => {
+                        let lerp = |a: Color, b: Color, t: f32| a + (b - a) * t;
+                        let c00 = lerp(c(0, 0, 0), c(1, 0, 0), fr);
+                        let c10 = lerp(c(0, 1, 0), c(1, 1, 0), fr);
+                        let c01 = lerp(c(0, 0, 1), c(1, 0, 1), fr);
+                        let c11 = lerp(c(0, 1, 1), c(1, 1, 1), fr);
+                        lerp(lerp(c00, c10, fg), lerp(c01, c11, fg), fb)
+                    }
+                    LutInterpolation::Tetrahedral => {
+                        let c000 = c(0, 0, 0);
+                        let c111 = c(1, 1, 1);
+                        // Walk from the origin corner to the far corner along
+                        // the axes in order of decreasing fraction
+                        let (first, second, f1, f2, f3) = if fr > fg {
+                            if fg > fb {
+                                (c(1, 0, 0), c(1, 1, 0), fr, fg, fb)
+                            } else if fr > fb {
+                                (c(1, 0, 0), c(1, 0, 1), fr, fb, fg)
+                            } else {
+                                (c(0, 0, 1), c(1, 0, 1), fb, fr, fg)
+                            }
+                        } else if fb > fg {
+                            (c(0, 0, 1), c(0, 1, 1), fb, fg, fr)
+                        } else if fb > fr {
+                            (c(0, 1, 0), c(0, 1, 1), fg, fb, fr)
+                        } else {
+                            (c(0, 1, 0), c(1, 1, 0), fg, fr, fb)
+                        };
+                        c000 * (1. - f1) + first * (f1 - f2) + second * (f2 - f3) + c111 * f3
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Grades the straight color, so partly covered pixels keep their alpha
+pub fn apply_lut(canvas: &Canvas, lut: &CubeLut, interpolation: LutInterpolation) -> Canvas {
+    let mut result = canvas.clone();
+    for y in 0..canvas.height {
+        for x in 0..canvas.width {
+            let alpha = alpha_at(canvas, x, y);
+            if alpha <= 0. {
+                continue;
+            }
+            let color = lut.apply(straight_pixel_at(canvas, x, y), interpolation);
+            write_pixel(&mut result, x, y, color * alpha);
+        }
+    }
+    result
+}
+
+// A LUT and how to sample it. Grades like this are built for display-encoded
+// color, so `DisplayTransform` applies them after tone mapping and encoding.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Look {
+    pub lut: CubeLut,
+    pub interpolation: LutInterpolation,
+}
+
+impl Look {
+    pub fn new(lut: CubeLut) -> Self {
+        Look {
+            lut,
+            interpolation: LutInterpolation::default(),
+        }
+    }
+
+    pub fn apply(&self, color: Color) -> Color {
+        self.lut.apply(color, self.interpolation)
+    }
+}
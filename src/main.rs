@@ -3,80 +3,49 @@ use std::fs::File;
 use std::io::Write;
 use tracer::camera::render;
 use tracer::camera::Camera;
-use tracer::canvas::canvas_to_ppm;
 use tracer::cube::Cube;
+use tracer::display::{canvas_to_ppm_with, DisplayTransform};
 use tracer::lights::PointLight;
+use tracer::lut::{parse_cube, Look, LutInterpolation};
 use tracer::materials::Material;
-use tracer::patterns::StripePattern;
 use tracer::patterns::{CheckerPattern, Pattern, PatternType};
 use tracer::plane::Plane;
 use tracer::shape::Shape;
-use tracer::sphere::Sphere;
 use tracer::cylinder::Cylinder;
-use tracer::transforms::rotation_z;
-use tracer::transforms::{rotation_x, scaling, translation, view_transform};
+use tracer::transforms::{scaling, translation, view_transform};
 use tracer::world::{ShapeEnum, World};
 use tracer::{point, vector, Color};
 
 fn main() -> Result<(), &'static str> {
-    // Middle Sphere
-    let mut middle = Sphere::default();
-    middle.transform = translation(-0.5, 1., 0.5);
-    middle.material = Material::default();
-    middle.material.color = Color::new(0.1, 1., 0.5);
-    middle.material.diffuse = 0.7;
-    middle.material.specular = 0.3;
-    middle.material.reflective = 1.;
-
-    let stripe = PatternType::Stripe(StripePattern::new(
-        Color::new(1.0, 0.5, 0.0),
-        Color::new(0., 0.5, 0.5),
-    ));
-    let mut stripe_pattern = Pattern::new(stripe);
-    stripe_pattern.set_transform(scaling(0.5, 0.5, 0.5));
-    middle.material.pattern = Some(stripe_pattern);
-    middle.material.transparency = 0.9;
-    middle.material.reflective = 0.9;
-
-    // Interior Sphere
-    let mut interior = Sphere::default();
-    interior.transform = translation(-0.5, 1., 1.5) * scaling(0.5, 0.5, 0.5);
-    interior.material = Material::default();
-    interior.material.color = Color::new(0., 0.5, 0.8);
-    interior.material.reflective = 0.9;
-    interior.material.transparency = 0.2;
-
-    let checker = PatternType::Checker(CheckerPattern::new(
-        Color::new(0., 1.0, 0.5),
-        Color::new(0.5, 0., 0.9),
-    ));
-    let mut checker_pattern = Pattern::new(checker);
-    checker_pattern
-        .set_transform(rotation_x(PI / 4.) * rotation_z(PI / 4.) * scaling(0.2, 0.5, 2.0));
-    interior.material.pattern = Some(checker_pattern);
-
     // Floor
-    let mut floor = Plane::default();
-    floor.transform = translation(0., 0., -3.);
-
     let checker = PatternType::Checker(CheckerPattern::new(
         Color::new(1., 1., 1.),
         Color::new(0.0, 0.0, 0.0),
     ));
-    floor.material.pattern = Some(Pattern::new(checker));
-    floor.material.reflective = 0.9;
+    let floor = Plane {
+        transform: translation(0., 0., -3.),
+        material: Material {
+            pattern: Some(Pattern::new(checker)),
+            reflective: 0.9,
+            ..Material::default()
+        },
+    };
 
     // Table
 
     let table_color = Color::new(0.8, 0.5, 0.1);
-    let mut base_cube = Cube::default();
-    base_cube.material = Material::default();
-    base_cube.material.color = table_color;
+    let base_cube = Cube {
+        material: Material {
+            color: table_color,
+            ..Material::default()
+        },
+        ..Cube::default()
+    };
 
-    let mut cube = base_cube;
-    let mut cube2 = base_cube;
-    let mut cube3 = base_cube;
-    let mut cube4 = base_cube;
+    let mut cube = base_cube.clone();
+    let mut cube2 = base_cube.clone();
+    let mut cube3 = base_cube.clone();
+    let mut cube4 = base_cube.clone();
     let mut cube5 = base_cube;
 
     let scale_factor = scaling(0.2, 3., 0.2);
@@ -88,33 +57,63 @@ fn main() -> Result<(), &'static str> {
 
     let mut cylinder = Cylinder::default();
     cylinder.set_transform(translation(-2., 0., 0.));
-    cylinder.material.reflective = 0.9;
-    cylinder.material.refractive_index = 0.5;
-    cylinder.material.transparency = 0.7;
+    cylinder.material = Material {
+        reflective: 0.9,
+        refractive_index: 0.5,
+        transparency: 0.7,
+        ..Material::default()
+    };
 
 
-    let mut world = World::default();
-    world.objects = vec![
-        ShapeEnum::Plane(floor),
-        ShapeEnum::Cube(cube),
-        ShapeEnum::Cube(cube2),
-        ShapeEnum::Cube(cube3),
-        ShapeEnum::Cube(cube4),
-        ShapeEnum::Cube(cube5),
-        ShapeEnum::Cylinder(cylinder),
-    ];
-    world.light = Some(PointLight::new(
-        point(-10., 10., -10.),
-        Color::new(1., 1., 1.),
-    ));
+    let world = World {
+        objects: vec![
+            ShapeEnum::Plane(floor),
+            ShapeEnum::Cube(cube),
+            ShapeEnum::Cube(cube2),
+            ShapeEnum::Cube(cube3),
+            ShapeEnum::Cube(cube4),
+            ShapeEnum::Cube(cube5),
+            ShapeEnum::Cylinder(cylinder),
+        ],
+        light: Some(PointLight::new(
+            point(-10., 10., -10.),
+            Color::new(1., 1., 1.),
+        )),
+        ..World::default()
+    };
     let mut camera = Camera::new(400, 200, PI / 3.);
     camera.transform = view_transform(point(0., 5., -10.), point(2., 3., 1.), vector(0., 1., 0.));
     let canvas = render(camera, world)?;
-    let ppm = canvas_to_ppm(&canvas);
+
+    // Optional display grade from --lut <file.cube>, sampled with
+    // --lut-interpolation trilinear|tetrahedral when given
+    let args: Vec<String> = std::env::args().collect();
+    let option = |name: &str| {
+        args.iter()
+            .position(|arg| arg == name)
+            .and_then(|i| args.get(i + 1))
+    };
+    let mut look = None;
+    if let Some(path) = option("--lut") {
+        let text = std::fs::read_to_string(path).map_err(|_| "Could not read LUT file")?;
+        look = Some(Look::new(parse_cube(&text)?));
+    }
+    if let (Some(look), Some(name)) = (look.as_mut(), option("--lut-interpolation")) {
+        look.interpolation = match name.as_str() {
+            "tetrahedral" => LutInterpolation::Tetrahedral,
+            "trilinear" => LutInterpolation::Trilinear,
+            _ => return Err("Unknown LUT interpolation"),
+        };
+    }
+    let display = DisplayTransform {
+        look,
+        ..DisplayTransform::default()
+    };
+    let ppm = canvas_to_ppm_with(&canvas, &display);
     let mut f = File::create("output.svg").expect("Could not create file");
     for row in ppm {
         f.write_all(row.as_bytes()).expect("Could not write row.");
-        f.write("\n".as_bytes()).expect("Could not write new line");
+        f.write_all("\n".as_bytes()).expect("Could not write new line");
     }
     Ok(())
 }
@@ -1,6 +1,12 @@
-use crate::{patterns::Pattern, Color};
+use crate::{
+    bump::{BumpMap, NormalMap},
+    medium::Medium,
+    patterns::{pattern_at_object, Pattern},
+    world::ShapeEnum,
+    Color, Tuple,
+};
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Material {
     pub color: Color,
     pub ambient: f32,
@@ -11,6 +17,20 @@ pub struct Material {
     pub refractive_index: f32,
     pub transparency: f32,
     pub pattern: Option<Pattern>,
+    pub diffuse_pattern: Option<Pattern>,
+    pub specular_pattern: Option<Pattern>,
+    pub shininess_pattern: Option<Pattern>,
+    pub reflective_pattern: Option<Pattern>,
+    pub transparency_pattern: Option<Pattern>,
+    pub bump_map: Option<BumpMap>,
+    pub normal_map: Option<NormalMap>,
+    // Fills the shape's interior; pair with `medium_boundary` for an invisible surface
+    pub medium: Option<Medium>,
+    // Invisible apart from the shadows and reflections it receives, with
+    // alpha holding the shadow density
+    pub shadow_catcher: bool,
+    // Cuts a fully transparent hole wherever the camera sees it
+    pub holdout: bool,
 }
 
 impl Default for Material {
@@ -25,6 +45,59 @@ impl Default for Material {
             transparency: 0.,
             refractive_index: 1.,
             pattern: None,
+            diffuse_pattern: None,
+            specular_pattern: None,
+            shininess_pattern: None,
+            reflective_pattern: None,
+            transparency_pattern: None,
+            bump_map: None,
+            normal_map: None,
+            medium: None,
+            shadow_catcher: false,
+            holdout: false,
         }
     }
 }
+
+pub const MIN_SHININESS: f32 = 1.;
+
+// Scalar patterns scale the base value by the luminance of the pattern color,
+// so a black/white checker switches a property between zero and its full value
+fn scalar_at(base: f32, pattern: &Option<Pattern>, object: &ShapeEnum, point: Tuple) -> f32 {
+    match pattern {
+        Some(pattern) => base * pattern_at_object(pattern, object, point).luminance(),
+        None => base,
+    }
+}
+
+impl Material {
+    pub fn color_at(&self, object: &ShapeEnum, point: Tuple) -> Color {
+        match &self.pattern {
+            Some(pattern) => pattern_at_object(pattern, object, point),
+            None => self.color,
+        }
+    }
+
+    pub fn diffuse_at(&self, object: &ShapeEnum, point: Tuple) -> f32 {
+        scalar_at(self.diffuse, &self.diffuse_pattern, object, point)
+    }
+
+    pub fn specular_at(&self, object: &ShapeEnum, point: Tuple) -> f32 {
+        scalar_at(self.specular, &self.specular_pattern, object, point)
+    }
+
+    // A zero exponent would light the whole surface with the full highlight,
+    // so the pattern maps into [MIN_SHININESS, shininess] instead
+    pub fn shininess_at(&self, object: &ShapeEnum, point: Tuple) -> f32 {
+        let min = MIN_SHININESS.min(self.shininess);
+        min + scalar_at(self.shininess - min, &self.shininess_pattern, object, point)
+    }
+
+    pub fn reflective_at(&self, object: &ShapeEnum, point: Tuple) -> f32 {
+        scalar_at(self.reflective, &self.reflective_pattern, object, point)
+    }
+
+    pub fn transparency_at(&self, object: &ShapeEnum, point: Tuple) -> f32 {
+        scalar_at(self.transparency, &self.transparency_pattern, object, point)
+    }
+}
@@ -196,10 +196,9 @@ impl Matrix<f32, 4, 4> {
         let det = self.determinant();
         let mut inverse = [[0.; 4]; 4];
 
-        for r in 0..4 {
-            for c in 0..4 {
-                let cof = self.cofactor(r, c);
-                inverse[c][r] = cof / det;
+        for (c, column) in inverse.iter_mut().enumerate() {
+            for (r, value) in column.iter_mut().enumerate() {
+                *value = self.cofactor(r, c) / det;
             }
         }
         Ok(Self::new(inverse))
@@ -0,0 +1,321 @@
+use std::f32::consts::PI;
+
+use rand::Rng;
+
+use crate::{
+    dot,
+    intersections::hit,
+    magnitude,
+    materials::Material,
+    normalize,
+    patterns::Pattern,
+    ray::{position, Ray},
+    world::{intersect_shape, intersect_world, ShapeEnum, World},
+    Color, Tuple,
+};
+
+// A participating medium, either filling the whole world as fog or the
+// interior of a closed shape. Coefficients are per unit distance at density 1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Medium {
+    pub absorption: Color,
+    pub scattering: Color,
+    pub density: f32,
+    // Scales the density by the pattern's luminance, e.g. a noise pattern for smoke
+    pub density_pattern: Option<Pattern>,
+    // Henyey-Greenstein asymmetry: 0 scatters evenly, positive values favour
+    // forward scattering
+    pub anisotropy: f32,
+    // Ray-marching step length. Steps that aren't positive and finite use the
+    // default, and no segment takes more than `MAX_MARCH_STEPS`
+    pub step_size: f32,
+    // How far global fog reaches along a ray, including rays that miss everything
+    pub max_distance: f32,
+}
+
+pub const MAX_MARCH_STEPS: usize = 10_000;
+
+impl Default for Medium {
+    fn default() -> Self {
+        Medium {
+            absorption: Color::new(0.01, 0.01, 0.01),
+            scattering: Color::new(0.1, 0.1, 0.1),
+            density: 1.,
+            density_pattern: None,
+            anisotropy: 0.,
+            step_size: 0.25,
+            max_distance: 100.,
+        }
+    }
+}
+
+impl Medium {
+    pub fn new(absorption: Color, scattering: Color) -> Self {
+        Medium {
+            absorption,
+            scattering,
+            ..Medium::default()
+        }
+    }
+
+    pub fn extinction(&self) -> Color {
+        self.absorption + self.scattering
+    }
+
+    // A medium inside a shape follows the shape's transform like any other
+    // pattern; global fog only uses the pattern's own transform
+    pub fn density_at(
+        &self,
+        object: Option<&ShapeEnum>,
+        point: Tuple,
+    ) -> Result<f32, &'static str> {
+        match (&self.density_pattern, object) {
+            (None, _) => Ok(self.density),
+            (Some(pattern), Some(object)) => {
+                let object_point = object.transform().inverse()? * point;
+                let local = pattern.transform.inverse()? * object_point;
+                Ok(self.density * pattern.local_at(local).luminance())
+            }
+            (Some(pattern), None) => {
+                let local = pattern.transform.inverse()? * point;
+                Ok(self.density * pattern.local_at(local).luminance())
+            }
+        }
+    }
+
+    // Marching can't stop for errors, so a pattern that can't be placed
+    // falls back to the uniform density
+    fn density_or_uniform(&self, object: Option<&ShapeEnum>, point: Tuple) -> f32 {
+        self.density_at(object, point).unwrap_or(self.density)
+    }
+
+    // Number of marching steps across `length`
+    fn steps(&self, length: f32) -> usize {
+        let step_size = if self.step_size > 0. && self.step_size.is_finite() {
+            self.step_size
+        } else {
+            Medium::default().step_size
+        };
+        ((length / step_size).ceil().min(MAX_MARCH_STEPS as f32) as usize).max(1)
+    }
+
+    // Henyey-Greenstein phase function, normalised over the sphere
+    pub fn phase(&self, cos_theta: f32) -> f32 {
+        let g = self.anisotropy;
+        let denominator = 1. + g * g - 2. * g * cos_theta;
+        (1. - g * g) / (4. * PI * denominator * denominator.sqrt())
+    }
+}
+
+// A fully transparent, unlit boundary so only the medium inside shows
+pub fn medium_boundary(medium: Medium) -> Material {
+    Material {
+        ambient: 0.,
+        diffuse: 0.,
+        specular: 0.,
+        transparency: 1.,
+        refractive_index: 1.,
+        medium: Some(medium),
+        ..Material::default()
+    }
+}
+
+// A stretch of ray inside one medium
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment {
+    pub start: f32,
+    pub end: f32,
+    pub medium: Medium,
+    // The shape holding the medium, or None for global fog
+    pub object: Option<ShapeEnum>,
+}
+
+pub fn has_media(world: &World) -> bool {
+    world.medium.is_some()
+        || world
+            .objects
+            .iter()
+            .any(|object| object.material().medium.is_some())
+}
+
+// Parts of the ray between 0 and `t_max` inside media, ordered by start.
+// Global fog fills the gaps between shape media up to its `max_distance`.
+pub fn media_segments(world: &World, ray: Ray, t_max: f32) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    for object in &world.objects {
+        let Some(medium) = &object.material().medium else {
+            continue;
+        };
+        let mut ts: Vec<f32> = intersect_shape(object.clone(), ray)
+            .iter()
+            .map(|i| i.t)
+            .collect();
+        ts.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        // An odd number of crossings behind the origin means it starts inside
+        let mut inside = ts.iter().filter(|&&t| t < 0.).count() % 2 == 1;
+        let mut start = 0.;
+        for &t in ts.iter().filter(|&&t| t >= 0.) {
+            if inside {
+                segments.push(Segment {
+                    start,
+                    end: t.min(t_max),
+                    medium: medium.clone(),
+                    object: Some(object.clone()),
+                });
+            }
+            if t >= t_max {
+                inside = false;
+                break;
+            }
+            start = t;
+            inside = !inside;
+        }
+        if inside {
+            segments.push(Segment {
+                start,
+                end: t_max,
+                medium: medium.clone(),
+                object: Some(object.clone()),
+            });
+        }
+    }
+    segments.sort_by(|a, b| a.start.partial_cmp(&b.start).unwrap());
+
+    if let Some(medium) = &world.medium {
+        let end = t_max.min(medium.max_distance);
+        let mut fog = Vec::new();
+        let mut start = 0.;
+        for segment in &segments {
+            if segment.start > start {
+                fog.push((start, segment.start.min(end)));
+            }
+            start = start.max(segment.end);
+        }
+        fog.push((start, end));
+        segments.extend(
+            fog.into_iter()
+                .filter(|(start, end)| end > start)
+                .map(|(start, end)| Segment {
+                    start,
+                    end,
+                    medium: medium.clone(),
+                    object: None,
+                }),
+        );
+        segments.sort_by(|a, b| a.start.partial_cmp(&b.start).unwrap());
+    }
+    segments
+}
+
+fn exp(color: Color) -> Color {
+    Color::new(color.red.exp(), color.green.exp(), color.blue.exp())
+}
+
+fn optical_depth(segment: &Segment, ray: Ray) -> Color {
+    let length = segment.end - segment.start;
+    let medium = &segment.medium;
+    if medium.density_pattern.is_none() {
+        return medium.extinction() * (medium.density * length);
+    }
+    // Midpoint rule over the marching steps
+    let steps = medium.steps(length);
+    let dt = length / steps as f32;
+    let mut density = 0.;
+    for i in 0..steps {
+        let t = segment.start + (i as f32 + 0.5) * dt;
+        density += medium.density_or_uniform(segment.object.as_ref(), position(ray, t));
+    }
+    medium.extinction() * (density * dt)
+}
+
+// Fraction of light surviving the media between the ray origin and `t_max`
+pub fn transmittance(world: &World, ray: Ray, t_max: f32) -> Color {
+    let depth = media_segments(world, ray, t_max)
+        .iter()
+        .fold(Color::new(0., 0., 0.), |depth, segment| {
+            depth + optical_depth(segment, ray)
+        });
+    exp(depth * -1.)
+}
+
+// Fraction of a light reaching `point` from `direction` at `distance`: black
+// when a surface is in the way, otherwise attenuated by media. Shapes holding
+// a medium don't block light themselves; their interior does.
+pub fn light_transmittance(world: &World, point: Tuple, direction: Tuple, distance: f32) -> Color {
+    let ray = Ray::new(point, direction);
+    let intersections = intersect_world(world, ray);
+    if !has_media(world) {
+        return match hit(intersections) {
+            Some(intersection) if intersection.t < distance => Color::new(0., 0., 0.),
+            _ => Color::new(1., 1., 1.),
+        };
+    }
+    let blocked = intersections.iter().any(|intersection| {
+        intersection.t >= 0.
+            && intersection.t < distance
+            && intersection.object.material().medium.is_none()
+    });
+    if blocked {
+        return Color::new(0., 0., 0.);
+    }
+    transmittance(world, ray, distance)
+}
+
+// Direction, distance and intensity of each light as seen from `point`
+pub fn lights_at(world: &World, point: Tuple) -> Vec<(Tuple, f32, Color)> {
+    let mut lights = Vec::new();
+    if let Some(light) = world.light {
+        let v = light.position - point;
+        lights.push((normalize(v), magnitude(v), light.intensity));
+    }
+    if let Some(sun) = world.sun {
+        lights.push((sun.direction, f32::INFINITY, sun.intensity));
+    }
+    lights
+}
+
+// Ray-march the media up to `t_max`, returning the light single-scattered
+// towards the ray origin and the transmittance along the way. Each step is
+// jittered by the same random offset.
+pub fn march<R: Rng>(world: &World, ray: Ray, t_max: f32, rng: &mut R) -> (Color, Color) {
+    let mut scattered = Color::new(0., 0., 0.);
+    let mut transmittance = Color::new(1., 1., 1.);
+    let offset = rng.gen::<f32>();
+    let view = normalize(ray.direction);
+    for segment in media_segments(world, ray, t_max) {
+        let medium = &segment.medium;
+        let length = segment.end - segment.start;
+        if length <= 0. || !length.is_finite() {
+            continue;
+        }
+        let steps = medium.steps(length);
+        let dt = length / steps as f32;
+        for i in 0..steps {
+            let t = segment.start + (i as f32 + offset) * dt;
+            let point = position(ray, t);
+            let density = medium.density_or_uniform(segment.object.as_ref(), point);
+            if density <= 0. {
+                continue;
+            }
+            for (direction, distance, intensity) in lights_at(world, point) {
+                let visible = light_transmittance(world, point, direction, distance);
+                let phase = medium.phase(dot(view, direction));
+                scattered = scattered
+                    + transmittance
+                        * medium.scattering
+                        * intensity
+                        * visible
+                        * (density * phase * dt);
+            }
+            transmittance = transmittance * exp(medium.extinction() * (-density * dt));
+        }
+    }
+    (scattered, transmittance)
+}
+
+// Surface lighting from one light seen through `transmittance`, where `lit`
+// shades with or without the light in shadow. The ambient term is left alone.
+pub fn lit_through_media(lit: impl Fn(bool) -> Color, transmittance: Color) -> Color {
+    let ambient = lit(true);
+    ambient + (lit(false) - ambient) * transmittance
+}
@@ -0,0 +1,180 @@
+use crate::{
+    canvas::{pixel_at, write_pixel, Canvas},
+    debug::heat_color,
+    Color,
+};
+
+fn check_sizes(a: &Canvas, b: &Canvas) -> Result<(), &'static str> {
+    if a.width != b.width || a.height != b.height {
+        return Err("Canvas sizes differ");
+    }
+    Ok(())
+}
+
+fn per_pixel(a: &Canvas, b: &Canvas, error: impl Fn(f32) -> f32) -> Result<Canvas, &'static str> {
+    check_sizes(a, b)?;
+    let mut result = Canvas::new(a.width, a.height);
+    for y in 0..a.height {
+        for x in 0..a.width {
+            let d = pixel_at(a, x, y) - pixel_at(b, x, y);
+            write_pixel(
+                &mut result,
+                x,
+                y,
+                Color::new(error(d.red), error(d.green), error(d.blue)),
+            );
+        }
+    }
+    Ok(result)
+}
+
+pub fn absolute_error(a: &Canvas, b: &Canvas) -> Result<Canvas, &'static str> {
+    per_pixel(a, b, f32::abs)
+}
+
+pub fn squared_error(a: &Canvas, b: &Canvas) -> Result<Canvas, &'static str> {
+    per_pixel(a, b, |d| d * d)
+}
+
+// Mean squared error over every channel of every pixel
+pub fn mse(a: &Canvas, b: &Canvas) -> Result<f32, &'static str> {
+    let squared = squared_error(a, b)?;
+    let count = (a.width * a.height * 3).max(1) as f64;
+    let total: f64 = squared
+        .pixels
+        .iter()
+        .flatten()
+        .map(|c| (c.red + c.green + c.blue) as f64)
+        .sum();
+    Ok((total / count) as f32)
+}
+
+// Peak signal-to-noise ratio in decibels for values that top out at `peak`;
+// infinite for identical images
+pub fn psnr(a: &Canvas, b: &Canvas, peak: f32) -> Result<f32, &'static str> {
+    let error = mse(a, b)?;
+    if error == 0. {
+        return Ok(f32::INFINITY);
+    }
+    Ok(10. * f32::log10(peak * peak / error))
+}
+
+pub fn max_difference(a: &Canvas, b: &Canvas) -> Result<f32, &'static str> {
+    let absolute = absolute_error(a, b)?;
+    Ok(absolute
+        .pixels
+        .iter()
+        .flatten()
+        .fold(0., |max: f32, c| max.max(c.red).max(c.green).max(c.blue)))
+}
+
+// Separable Gaussian blur with clamped edges
+fn gaussian_blur(values: &[f32], width: usize, height: usize, sigma: f32) -> Vec<f32> {
+    let radius = (3. * sigma).ceil() as isize;
+    let kernel: Vec<f32> = (-radius..=radius)
+        .map(|i| f32::exp(-((i * i) as f32) / (2. * sigma * sigma)))
+        .collect();
+    let norm: f32 = kernel.iter().sum();
+    let blur = |values: &[f32], horizontal: bool| {
+        let mut out = vec![0.; values.len()];
+        for y in 0..height {
+            for x in 0..width {
+                let mut total = 0.;
+                for (k, weight) in kernel.iter().enumerate() {
+                    let offset = k as isize - radius;
+                    let (sx, sy) = if horizontal {
+                        (
+                            (x as isize + offset).clamp(0, width as isize - 1) as usize,
+                            y,
+                        )
+                    } else {
+                        (
+                            x,
+                            (y as isize + offset).clamp(0, height as isize - 1) as usize,
+                        )
+                    };
+                    total += weight * values[sy * width + sx];
+                }
+                out[y * width + x] = total / norm;
+            }
+        }
+        out
+    };
+    blur(&blur(values, true), false)
+}
+
+// Structural similarity of the luminance, using the usual 11x11 Gaussian
+// window (sigma 1.5) and constants for values in [0, 1]. 1 means identical.
+pub fn ssim(a: &Canvas, b: &Canvas) -> Result<f32, &'static str> {
+    check_sizes(a, b)?;
+    let (width, height) = (a.width, a.height);
+    if width == 0 || height == 0 {
+        return Ok(1.);
+    }
+    let luminance = |canvas: &Canvas| -> Vec<f32> {
+        canvas
+            .pixels
+            .iter()
+            .flatten()
+            .map(|c| c.luminance())
+            .collect()
+    };
+    let (x, y) = (luminance(a), luminance(b));
+    let product =
+        |p: &[f32], q: &[f32]| -> Vec<f32> { p.iter().zip(q).map(|(p, q)| p * q).collect() };
+    let blur = |values: &[f32]| gaussian_blur(values, width, height, 1.5);
+    let (mean_x, mean_y) = (blur(&x), blur(&y));
+    let (xx, yy, xy) = (
+        blur(&product(&x, &x)),
+        blur(&product(&y, &y)),
+        blur(&product(&x, &y)),
+    );
+
+    let c1 = 0.01f32.powi(2);
+    let c2 = 0.03f32.powi(2);
+    let mut total = 0.;
+    for i in 0..x.len() {
+        let (mx, my) = (mean_x[i], mean_y[i]);
+        let variance_x = xx[i] - mx * mx;
+        let variance_y = yy[i] - my * my;
+        let covariance = xy[i] - mx * my;
+        total += ((2. * mx * my + c1) * (2. * covariance + c2))
+            / ((mx * mx + my * my + c1) * (variance_x + variance_y + c2));
+    }
+    Ok(total / x.len() as f32)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImageMetrics {
+    pub mse: f32,
+    // Against a peak of 1
+    pub psnr: f32,
+    pub ssim: f32,
+    pub max_difference: f32,
+}
+
+pub fn compare(a: &Canvas, b: &Canvas) -> Result<ImageMetrics, &'static str> {
+    Ok(ImageMetrics {
+        mse: mse(a, b)?,
+        psnr: psnr(a, b, 1.)?,
+        ssim: ssim(a, b)?,
+        max_difference: max_difference(a, b)?,
+    })
+}
+
+// False-color map of the largest channel difference per pixel, from blue for
+// small differences to red at `scale` and above. Identical pixels stay black.
+pub fn difference_image(a: &Canvas, b: &Canvas, scale: f32) -> Result<Canvas, &'static str> {
+    let absolute = absolute_error(a, b)?;
+    let mut result = Canvas::new(a.width, a.height);
+    for y in 0..a.height {
+        for x in 0..a.width {
+            let d = pixel_at(&absolute, x, y);
+            let difference = d.red.max(d.green).max(d.blue);
+            if difference > 0. {
+                write_pixel(&mut result, x, y, heat_color(difference / scale));
+            }
+        }
+    }
+    Ok(result)
+}
@@ -0,0 +1,95 @@
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+use crate::{
+    camera::{render_with_integrator, Camera, RenderSettings},
+    canvas::Canvas,
+    integrators::Integrator,
+    intersections::{hit, prepare_computations},
+    ray::Ray,
+    sampling::cosine_sample_hemisphere,
+    world::{intersect_world, World},
+    Color, Tuple,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AmbientOcclusion {
+    pub samples: usize,
+    // Occluders further away than this don't darken the point
+    pub max_distance: f32,
+}
+
+impl Default for AmbientOcclusion {
+    fn default() -> Self {
+        AmbientOcclusion {
+            samples: 16,
+            max_distance: 1.,
+        }
+    }
+}
+
+impl AmbientOcclusion {
+    pub fn new(samples: usize, max_distance: f32) -> Self {
+        AmbientOcclusion {
+            samples,
+            max_distance,
+        }
+    }
+
+    // Fraction of cosine-weighted rays that escape within max_distance:
+    // 1 for a fully open point, 0 for a fully enclosed one
+    pub fn visibility<R: Rng>(
+        &self,
+        world: &World,
+        point: Tuple,
+        normal: Tuple,
+        rng: &mut R,
+    ) -> f32 {
+        if self.samples == 0 {
+            return 1.;
+        }
+
+        let mut unoccluded = 0;
+        for _ in 0..self.samples {
+            let direction = cosine_sample_hemisphere(normal, rng.gen(), rng.gen());
+            let occluder = hit(intersect_world(world, Ray::new(point, direction)));
+            match occluder {
+                Some(intersection) if intersection.t < self.max_distance => {}
+                _ => unoccluded += 1,
+            }
+        }
+        unoccluded as f32 / self.samples as f32
+    }
+}
+
+// Seed from the shading point so the Whitted tracer stays deterministic
+pub fn point_rng(point: Tuple) -> SmallRng {
+    let seed = (point.x.to_bits() as u64)
+        ^ (point.y.to_bits() as u64).rotate_left(21)
+        ^ (point.z.to_bits() as u64).rotate_left(42);
+    SmallRng::seed_from_u64(seed)
+}
+
+// Clay render: white where the surface is open, darker in creases and contact areas
+impl Integrator for AmbientOcclusion {
+    fn li(&self, world: &World, ray: Ray, rng: &mut SmallRng) -> Color {
+        let intersections = intersect_world(world, ray);
+        match hit(intersections.clone()) {
+            Some(intersection) => {
+                let comps = prepare_computations(intersection, ray, intersections);
+                let v = self.visibility(world, comps.over_point, comps.normalv, rng);
+                Color::new(v, v, v)
+            }
+            None => Color::new(1., 1., 1.),
+        }
+    }
+}
+
+pub fn render_ambient_occlusion(
+    camera: Camera,
+    world: &World,
+    settings: &RenderSettings,
+    occlusion: AmbientOcclusion,
+) -> Result<Canvas, &'static str> {
+    let (image, _) = render_with_integrator(camera, world, settings, &occlusion)?;
+    Ok(image)
+}
@@ -1,16 +1,28 @@
-use crate::{magnitude, matrix::Matrix, shape::Shape, world::ShapeEnum, Color, Tuple};
+use std::sync::Arc;
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+use crate::{
+    canvas::Canvas,
+    magnitude,
+    matrix::Matrix,
+    shape::Shape,
+    uv::{uv_sample, UvMapping},
+    world::ShapeEnum,
+    Color, Tuple,
+};
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum PatternType {
     Ring(RingPattern),
     Gradient(GradientPattern),
     Stripe(StripePattern),
     Checker(CheckerPattern),
     Radial(RadialGradient),
+    Image(ImagePattern),
+    Noise(NoisePattern),
     Test(),
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Pattern {
     pattern: PatternType,
     pub transform: Matrix<f32, 4, 4>,
@@ -32,20 +44,23 @@ impl Pattern {
     }
 
     pub fn pattern_at(&mut self, point: Tuple) -> Color {
+        self.local_at(point)
+    }
+
+    pub(crate) fn local_at(&self, point: Tuple) -> Color {
         match self.pattern {
             PatternType::Gradient(grad) => grad.local_pattern_at(point),
             PatternType::Stripe(stripe) => stripe.local_pattern_at(point),
             PatternType::Checker(checker) => checker.local_pattern_at(point),
             PatternType::Ring(ring) => ring.local_pattern_at(point),
             PatternType::Radial(radial) => radial.local_pattern_at(point),
+            PatternType::Image(ref image) => image.local_pattern_at(point),
+            PatternType::Noise(noise) => noise.local_pattern_at(point),
             PatternType::Test() => Color::new(point.x, point.y, point.z),
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-struct TestPattern {}
-
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct StripePattern {
     pub a: Color,
@@ -124,7 +139,12 @@ impl CheckerPattern {
     }
 }
 
-pub fn pattern_at_shape(mut pattern: Pattern, shape: ShapeEnum, point: Tuple) -> Color {
+pub fn pattern_at_shape(pattern: Pattern, shape: ShapeEnum, point: Tuple) -> Color {
+    pattern_at_object(&pattern, &shape, point)
+}
+
+// `pattern_at_shape` without taking ownership of the pattern and shape
+pub fn pattern_at_object(pattern: &Pattern, shape: &ShapeEnum, point: Tuple) -> Color {
     let pattern_inv = pattern.transform.inverse().unwrap();
     let shape_inv = match shape {
         ShapeEnum::Plane(plane) => plane.get_transform().inverse().unwrap(),
@@ -135,7 +155,7 @@ pub fn pattern_at_shape(mut pattern: Pattern, shape: ShapeEnum, point: Tuple) ->
     };
     let world_point = shape_inv * point;
     let pattern_point = pattern_inv * world_point;
-    pattern.pattern_at(pattern_point)
+    pattern.local_at(pattern_point)
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -157,3 +177,141 @@ impl RadialGradient {
         self.a + distance * fraction
     }
 }
+
+#[derive(Debug, Clone)]
+pub struct ImagePattern {
+    // Shared, so one texture can be used by many materials
+    pub canvas: Arc<Canvas>,
+    pub mapping: UvMapping,
+}
+
+impl ImagePattern {
+    pub fn new(canvas: Arc<Canvas>, mapping: UvMapping) -> Self {
+        Self { canvas, mapping }
+    }
+
+    pub fn local_pattern_at(&self, point: Tuple) -> Color {
+        let (u, v) = self.mapping.map(point);
+        uv_sample(&self.canvas, u, v)
+    }
+}
+
+impl PartialEq for ImagePattern {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.canvas, &other.canvas) && self.mapping == other.mapping
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NoisePattern {
+    pub a: Color,
+    pub b: Color,
+    pub octaves: u32,
+}
+
+impl NoisePattern {
+    pub fn new(a: Color, b: Color) -> Self {
+        Self { a, b, octaves: 1 }
+    }
+
+    pub fn local_pattern_at(&self, point: Tuple) -> Color {
+        let mut total = 0.;
+        let mut amplitude = 1.;
+        let mut frequency = 1.;
+        let mut norm = 0.;
+        for _ in 0..self.octaves.max(1) {
+            total += perlin(point * frequency) * amplitude;
+            norm += amplitude;
+            amplitude *= 0.5;
+            frequency *= 2.;
+        }
+        let fraction = (total / norm * 0.5 + 0.5).clamp(0., 1.);
+        self.a + (self.b - self.a) * fraction
+    }
+}
+
+const PERMUTATION: [u8; 256] = [
+    151, 160, 137, 91, 90, 15, 131, 13, 201, 95, 96, 53, 194, 233, 7, 225, 140, 36, 103, 30, 69,
+    142, 8, 99, 37, 240, 21, 10, 23, 190, 6, 148, 247, 120, 234, 75, 0, 26, 197, 62, 94, 252, 219,
+    203, 117, 35, 11, 32, 57, 177, 33, 88, 237, 149, 56, 87, 174, 20, 125, 136, 171, 168, 68, 175,
+    74, 165, 71, 134, 139, 48, 27, 166, 77, 146, 158, 231, 83, 111, 229, 122, 60, 211, 133, 230,
+    220, 105, 92, 41, 55, 46, 245, 40, 244, 102, 143, 54, 65, 25, 63, 161, 1, 216, 80, 73, 209, 76,
+    132, 187, 208, 89, 18, 169, 200, 196, 135, 130, 116, 188, 159, 86, 164, 100, 109, 198, 173,
+    186, 3, 64, 52, 217, 226, 250, 124, 123, 5, 202, 38, 147, 118, 126, 255, 82, 85, 212, 207, 206,
+    59, 227, 47, 16, 58, 17, 182, 189, 28, 42, 223, 183, 170, 213, 119, 248, 152, 2, 44, 154, 163,
+    70, 221, 153, 101, 155, 167, 43, 172, 9, 129, 22, 39, 253, 19, 98, 108, 110, 79, 113, 224, 232,
+    178, 185, 112, 104, 218, 246, 97, 228, 251, 34, 242, 193, 238, 210, 144, 12, 191, 179, 162,
+    241, 81, 51, 145, 235, 249, 14, 239, 107, 49, 192, 214, 31, 181, 199, 106, 157, 184, 84, 204,
+    176, 115, 121, 50, 45, 127, 4, 150, 254, 138, 236, 205, 93, 222, 114, 67, 29, 24, 72, 243, 141,
+    128, 195, 78, 66, 215, 61, 156, 180,
+];
+
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6. - 15.) + 10.)
+}
+
+fn lerp(t: f32, a: f32, b: f32) -> f32 {
+    a + t * (b - a)
+}
+
+fn grad(hash: u8, x: f32, y: f32, z: f32) -> f32 {
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = if h < 4 {
+        y
+    } else if h == 12 || h == 14 {
+        x
+    } else {
+        z
+    };
+    let u = if h & 1 == 0 { u } else { -u };
+    let v = if h & 2 == 0 { v } else { -v };
+    u + v
+}
+
+// Improved Perlin noise, roughly in [-1, 1]
+pub fn perlin(point: Tuple) -> f32 {
+    let p = |i: usize| PERMUTATION[i & 255] as usize;
+
+    let xi = point.x.floor() as i64 as usize & 255;
+    let yi = point.y.floor() as i64 as usize & 255;
+    let zi = point.z.floor() as i64 as usize & 255;
+    let x = point.x - point.x.floor();
+    let y = point.y - point.y.floor();
+    let z = point.z - point.z.floor();
+    let (u, v, w) = (fade(x), fade(y), fade(z));
+
+    let a = p(xi) + yi;
+    let aa = p(a) + zi;
+    let ab = p(a + 1) + zi;
+    let b = p(xi + 1) + yi;
+    let ba = p(b) + zi;
+    let bb = p(b + 1) + zi;
+
+    let hash = |i: usize| PERMUTATION[i & 255];
+    lerp(
+        w,
+        lerp(
+            v,
+            lerp(u, grad(hash(aa), x, y, z), grad(hash(ba), x - 1., y, z)),
+            lerp(
+                u,
+                grad(hash(ab), x, y - 1., z),
+                grad(hash(bb), x - 1., y - 1., z),
+            ),
+        ),
+        lerp(
+            v,
+            lerp(
+                u,
+                grad(hash(aa + 1), x, y, z - 1.),
+                grad(hash(ba + 1), x - 1., y, z - 1.),
+            ),
+            lerp(
+                u,
+                grad(hash(ab + 1), x, y - 1., z - 1.),
+                grad(hash(bb + 1), x - 1., y - 1., z - 1.),
+            ),
+        ),
+    )
+}
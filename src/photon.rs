@@ -0,0 +1,231 @@
+use std::f32::consts::PI;
+
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+use rayon::prelude::*;
+
+use crate::{
+    dot,
+    integrators::refract_direction,
+    intersections::{hit, prepare_computations, shlick, Precomputation},
+    ray::Ray,
+    sampling::uniform_sample_sphere,
+    world::{intersect_world, World},
+    Color, Tuple,
+};
+
+// Specular bounces a photon may take before it is dropped
+const MAX_BOUNCES: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Photon {
+    pub position: Tuple,
+    // Direction of travel when the photon landed
+    pub direction: Tuple,
+    pub power: Color,
+}
+
+fn axis_value(point: Tuple, axis: usize) -> f32 {
+    match axis {
+        0 => point.x,
+        1 => point.y,
+        _ => point.z,
+    }
+}
+
+// Balanced kd-tree stored in place: the median of each slice is the node that
+// splits it, along the axis recorded for that index
+#[derive(Debug, Clone, PartialEq)]
+pub struct KdTree {
+    photons: Vec<Photon>,
+    axes: Vec<usize>,
+}
+
+fn build(photons: &mut [Photon], axes: &mut [usize]) {
+    if photons.is_empty() {
+        return;
+    }
+    // Split along the widest extent
+    let extent = |axis| {
+        let values = photons.iter().map(|p| axis_value(p.position, axis));
+        let min = values.clone().fold(f32::INFINITY, f32::min);
+        let max = values.fold(f32::NEG_INFINITY, f32::max);
+        max - min
+    };
+    let axis = (0..3)
+        .max_by(|&a, &b| extent(a).partial_cmp(&extent(b)).unwrap())
+        .unwrap();
+
+    let mid = photons.len() / 2;
+    photons.select_nth_unstable_by(mid, |a, b| {
+        axis_value(a.position, axis)
+            .partial_cmp(&axis_value(b.position, axis))
+            .unwrap()
+    });
+    axes[mid] = axis;
+    let (left, right) = photons.split_at_mut(mid);
+    let (left_axes, right_axes) = axes.split_at_mut(mid);
+    build(left, left_axes);
+    build(&mut right[1..], &mut right_axes[1..]);
+}
+
+fn search<'a>(
+    photons: &'a [Photon],
+    axes: &[usize],
+    point: Tuple,
+    radius: f32,
+    found: &mut Vec<&'a Photon>,
+) {
+    if photons.is_empty() {
+        return;
+    }
+    let mid = photons.len() / 2;
+    let photon = &photons[mid];
+    let offset = point - photon.position;
+    if dot(offset, offset) <= radius * radius {
+        found.push(photon);
+    }
+    let delta = axis_value(point, axes[mid]) - axis_value(photon.position, axes[mid]);
+    if delta - radius <= 0. {
+        search(&photons[..mid], &axes[..mid], point, radius, found);
+    }
+    if delta + radius >= 0. {
+        search(&photons[mid + 1..], &axes[mid + 1..], point, radius, found);
+    }
+}
+
+impl KdTree {
+    pub fn new(mut photons: Vec<Photon>) -> Self {
+        let mut axes = vec![0; photons.len()];
+        build(&mut photons, &mut axes);
+        KdTree { photons, axes }
+    }
+
+    pub fn len(&self) -> usize {
+        self.photons.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.photons.is_empty()
+    }
+
+    // Photons no further than `radius` from `point`
+    pub fn within(&self, point: Tuple, radius: f32) -> Vec<&Photon> {
+        let mut found = Vec::new();
+        search(&self.photons, &self.axes, point, radius, &mut found);
+        found
+    }
+}
+
+// Follow one photon from the point light through mirrors and glass, keeping
+// it where it comes to rest on a diffuse surface. Paths that reach a diffuse
+// surface directly are already covered by `lighting`.
+fn trace_photon(world: &World, index: usize, power: Color) -> Option<Photon> {
+    let light = world.light?;
+    let mut rng = SmallRng::seed_from_u64(index as u64);
+    let mut ray = Ray::new(light.position, uniform_sample_sphere(rng.gen(), rng.gen()));
+    let mut power = power;
+    for bounce in 0..MAX_BOUNCES {
+        let intersections = intersect_world(world, ray);
+        let intersection = hit(intersections.clone())?;
+        let t = intersection.t;
+        let comps = prepare_computations(intersection, ray, intersections);
+        if bounce == 0 {
+            // `lighting` has no distance falloff, so neither do photons
+            power = power * (t * t);
+        }
+
+        let material = comps.object.material();
+        let mut reflective = material.reflective_at(&comps.object, comps.over_point);
+        let mut transparency = material.transparency_at(&comps.object, comps.under_point);
+        if reflective > 0. && transparency > 0. {
+            let reflectance = shlick(comps.clone());
+            reflective *= reflectance;
+            transparency *= 1. - reflectance;
+        }
+
+        // Russian roulette between the specular lobes and coming to rest
+        let choice = rng.gen::<f32>();
+        if choice < reflective {
+            ray = Ray::new(comps.over_point, comps.reflectv);
+        } else if choice < reflective + transparency {
+            ray = match refract_direction(&comps) {
+                Some(direction) => Ray::new(comps.under_point, direction),
+                None => Ray::new(comps.over_point, comps.reflectv),
+            };
+        } else {
+            let rest = 1. - reflective - transparency;
+            let diffuse = material.diffuse_at(&comps.object, comps.over_point);
+            if bounce == 0 || diffuse <= 0. || rest <= 0. {
+                return None;
+            }
+            return Some(Photon {
+                position: comps.over_point,
+                direction: ray.direction,
+                power: power * (1. / rest),
+            });
+        }
+    }
+    None
+}
+
+// Caustic photon map: light that reached a diffuse surface via at least one
+// mirror or refraction, gathered within `radius` of each shading point
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhotonMap {
+    tree: KdTree,
+    pub radius: f32,
+}
+
+impl PhotonMap {
+    // Emits `photons` photons from the world's point light
+    pub fn new(world: &World, photons: usize, radius: f32) -> Self {
+        let Some(light) = world.light else {
+            return PhotonMap {
+                tree: KdTree::new(Vec::new()),
+                radius,
+            };
+        };
+        let power = light.intensity * (4. * PI / photons.max(1) as f32);
+        let stored: Vec<Photon> = (0..photons)
+            .into_par_iter()
+            .filter_map(|index| trace_photon(world, index, power))
+            .collect();
+        PhotonMap {
+            tree: KdTree::new(stored),
+            radius,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    // Density estimate over a disc of `radius`, counting only photons that
+    // arrived on the side the normal faces
+    pub fn irradiance(&self, point: Tuple, normal: Tuple) -> Color {
+        let area = PI * self.radius * self.radius;
+        self.tree
+            .within(point, self.radius)
+            .into_iter()
+            .filter(|photon| dot(photon.direction, normal) < 0.)
+            .fold(Color::new(0., 0., 0.), |total, photon| total + photon.power)
+            * (1. / area)
+    }
+}
+
+// Caustic light reflected by the diffuse term at a shading point
+pub fn caustic_lighting(world: &World, comps: &Precomputation) -> Color {
+    match &world.caustics {
+        Some(map) => {
+            let material = comps.object.material();
+            let color = material.color_at(&comps.object, comps.over_point);
+            let diffuse = material.diffuse_at(&comps.object, comps.over_point);
+            color * map.irradiance(comps.over_point, comps.normalv) * diffuse
+        }
+        None => Color::new(0., 0., 0.),
+    }
+}
@@ -6,7 +6,7 @@ use crate::shape::Shape;
 use crate::world::ShapeEnum;
 use crate::Tuple;
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Plane {
     pub transform: Matrix<f32, 4, 4>,
     pub material: Material,
@@ -36,7 +36,7 @@ impl Shape for Plane {
             Vec::new()
         } else {
             let t = -ray.origin.y / ray.direction.y;
-            vec![Intersection::new(t, ShapeEnum::Plane(*self))]
+            vec![Intersection::new(t, ShapeEnum::Plane(self.clone()))]
         }
     }
 
@@ -0,0 +1,72 @@
+use std::io::Write;
+
+use flate2::{write::ZlibEncoder, Compression};
+
+use crate::{
+    canvas::{alpha_at, pixel_at, straight_pixel_at, AlphaMode, Canvas},
+    display::DisplayTransform,
+};
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+// 8 bits per channel, RGBA
+const BIT_DEPTH: u8 = 8;
+const COLOR_TYPE_RGBA: u8 = 6;
+
+fn chunk(file: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    file.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = file.len();
+    file.extend_from_slice(kind);
+    file.extend_from_slice(data);
+    // The checksum covers the chunk type and data
+    let crc = crc32fast::hash(&file[start..]);
+    file.extend_from_slice(&crc.to_be_bytes());
+}
+
+// Write a canvas as an 8-bit RGBA PNG, encoding color through `transform`.
+// PNG defines alpha as straight; `Premultiplied` writes the canvas's own
+// premultiplied color, so coverage is applied in linear light before the
+// display transform rather than to the encoded bytes.
+pub fn canvas_to_png(
+    canvas: &Canvas,
+    transform: &DisplayTransform,
+    mode: AlphaMode,
+) -> Result<Vec<u8>, &'static str> {
+    if canvas.width == 0 || canvas.height == 0 {
+        return Err("PNG images can't be empty");
+    }
+
+    // Each scanline starts with its filter type, always 0 (none) here
+    let mut raw = Vec::with_capacity(canvas.height * (1 + canvas.width * 4));
+    for y in 0..canvas.height {
+        raw.push(0);
+        for x in 0..canvas.width {
+            let alpha = alpha_at(canvas, x, y).clamp(0., 1.);
+            let color = match mode {
+                AlphaMode::Straight => straight_pixel_at(canvas, x, y),
+                AlphaMode::Premultiplied => pixel_at(canvas, x, y),
+            };
+            let rgb = transform.to_bytes(color, x, y);
+            raw.extend_from_slice(&rgb);
+            raw.push((alpha * 255.).round() as u8);
+        }
+    }
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(&raw)
+        .map_err(|_| "Could not compress PNG data")?;
+    let compressed = encoder
+        .finish()
+        .map_err(|_| "Could not compress PNG data")?;
+
+    let mut header = Vec::new();
+    header.extend_from_slice(&(canvas.width as u32).to_be_bytes());
+    header.extend_from_slice(&(canvas.height as u32).to_be_bytes());
+    // Compression, filter and interlace methods are all 0
+    header.extend_from_slice(&[BIT_DEPTH, COLOR_TYPE_RGBA, 0, 0, 0]);
+
+    let mut file = SIGNATURE.to_vec();
+    chunk(&mut file, b"IHDR", &header);
+    chunk(&mut file, b"IDAT", &compressed);
+    chunk(&mut file, b"IEND", &[]);
+    Ok(file)
+}
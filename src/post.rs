@@ -0,0 +1,232 @@
+use std::f32::consts::PI;
+
+use rayon::prelude::*;
+
+use crate::{
+    canvas::{alpha_at, pixel_at, Canvas},
+    Color,
+};
+
+// Lens and film effects applied to the linear image before display. Light
+// above 1.0 is kept, so bright highlights bloom rather than clip. Bloom and
+// glare are emissive: the light they add lands in transparent pixels without
+// raising alpha, which a premultiplied `over` composite adds on top of
+// whatever is behind.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PostEffect {
+    // Light above `threshold` blurred at `levels` scales, each twice as wide
+    // as the last starting from `radius` pixels, and added back. Scales stop
+    // growing once the blur spans the whole image
+    Bloom {
+        threshold: f32,
+        intensity: f32,
+        radius: f32,
+        levels: u32,
+    },
+    // Light above `threshold` smeared into `streaks` lines through each
+    // highlight, fading over about `length` pixels
+    Glare {
+        threshold: f32,
+        intensity: f32,
+        length: f32,
+        streaks: u32,
+        // Rotation of the first streak, in radians from horizontal
+        angle: f32,
+    },
+    // Darkening towards the corners; 1 turns the corners black
+    Vignette {
+        strength: f32,
+    },
+    // Red is scaled away from the center and blue towards it by `amount` of
+    // the distance, as with lateral color in a simple lens
+    ChromaticAberration {
+        amount: f32,
+    },
+}
+
+// Build a new canvas one row at a time in parallel, keeping the alpha
+fn map_pixels(canvas: &Canvas, f: impl Fn(usize, usize) -> Color + Sync) -> Canvas {
+    let mut result = canvas.clone();
+    result.pixels = (0..canvas.height)
+        .into_par_iter()
+        .map(|y| (0..canvas.width).map(|x| f(x, y)).collect())
+        .collect();
+    result
+}
+
+// The part of each pixel above `threshold`, keeping its hue
+fn highlights(canvas: &Canvas, threshold: f32) -> Canvas {
+    map_pixels(canvas, |x, y| {
+        let color = pixel_at(canvas, x, y);
+        let brightness = color.red.max(color.green).max(color.blue);
+        if brightness <= threshold {
+            return Color::new(0., 0., 0.);
+        }
+        color * ((brightness - threshold) / brightness)
+    })
+}
+
+fn gaussian_kernel(sigma: f32) -> Vec<f32> {
+    let radius = (3. * sigma).ceil() as isize;
+    let kernel: Vec<f32> = (-radius..=radius)
+        .map(|i| f32::exp(-((i * i) as f32) / (2. * sigma * sigma)))
+        .collect();
+    let total: f32 = kernel.iter().sum();
+    kernel.iter().map(|weight| weight / total).collect()
+}
+
+// Separable Gaussian blur with clamped edges
+fn blur(canvas: &Canvas, sigma: f32) -> Canvas {
+    let kernel = gaussian_kernel(sigma);
+    let radius = (kernel.len() / 2) as isize;
+    let (width, height) = (canvas.width as isize, canvas.height as isize);
+    let pass = |source: &Canvas, horizontal: bool| {
+        map_pixels(source, |x, y| {
+            let mut total = Color::new(0., 0., 0.);
+            for (k, weight) in kernel.iter().enumerate() {
+                let offset = k as isize - radius;
+                let (sx, sy) = if horizontal {
+                    ((x as isize + offset).clamp(0, width - 1), y as isize)
+                } else {
+                    (x as isize, (y as isize + offset).clamp(0, height - 1))
+                };
+                total = total + pixel_at(source, sx as usize, sy as usize) * *weight;
+            }
+            total
+        })
+    };
+    pass(&pass(canvas, true), false)
+}
+
+// Bilinear lookup of color and alpha with clamped edges, in pixel coordinates
+fn sample(canvas: &Canvas, x: f32, y: f32) -> (Color, f32) {
+    let x = (x - 0.5).clamp(0., (canvas.width - 1) as f32);
+    let y = (y - 0.5).clamp(0., (canvas.height - 1) as f32);
+    let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+    let (x1, y1) = (
+        (x0 + 1).min(canvas.width - 1),
+        (y0 + 1).min(canvas.height - 1),
+    );
+    let (fx, fy) = (x - x0 as f32, y - y0 as f32);
+    let lerp = |x0: usize, x1: usize, y: usize| {
+        (
+            pixel_at(canvas, x0, y) * (1. - fx) + pixel_at(canvas, x1, y) * fx,
+            alpha_at(canvas, x0, y) * (1. - fx) + alpha_at(canvas, x1, y) * fx,
+        )
+    };
+    let (top, bottom) = (lerp(x0, x1, y0), lerp(x0, x1, y1));
+    (
+        top.0 * (1. - fy) + bottom.0 * fy,
+        top.1 * (1. - fy) + bottom.1 * fy,
+    )
+}
+
+impl PostEffect {
+    pub fn apply(&self, canvas: &Canvas) -> Canvas {
+        if canvas.width == 0 || canvas.height == 0 {
+            return canvas.clone();
+        }
+        let (cx, cy) = (canvas.width as f32 / 2., canvas.height as f32 / 2.);
+        match *self {
+            PostEffect::Bloom {
+                threshold,
+                intensity,
+                radius,
+                levels,
+            } => {
+                let bright = highlights(canvas, threshold);
+                let levels = levels.max(1);
+                // A kernel reaching past both edges only repeats the clamped border
+                let max_sigma = canvas.width.max(canvas.height) as f32 / 3.;
+                let blurred: Vec<Canvas> = (0..levels)
+                    .map(|level| {
+                        let sigma = radius.max(0.1) * f32::powi(2., level.min(126) as i32);
+                        blur(&bright, sigma.min(max_sigma))
+                    })
+                    .collect();
+                let scale = intensity / levels as f32;
+                map_pixels(canvas, |x, y| {
+                    blurred.iter().fold(pixel_at(canvas, x, y), |color, level| {
+                        color + pixel_at(level, x, y) * scale
+                    })
+                })
+            }
+            PostEffect::Glare {
+                threshold,
+                intensity,
+                length,
+                streaks,
+                angle,
+            } => {
+                let bright = highlights(canvas, threshold);
+                let steps = (3. * length).ceil().max(1.) as usize;
+                // Each streak runs both ways through the highlight
+                let directions: Vec<(f32, f32)> = (0..streaks.max(1) * 2)
+                    .map(|i| {
+                        let theta = angle + i as f32 * PI / streaks.max(1) as f32;
+                        (theta.cos(), theta.sin())
+                    })
+                    .collect();
+                let falloff: Vec<f32> = (1..=steps)
+                    .map(|step| f32::exp(-(step as f32) / length.max(0.1)))
+                    .collect();
+                let norm = intensity / (directions.len() as f32 * falloff.iter().sum::<f32>());
+                map_pixels(canvas, |x, y| {
+                    let mut streak = Color::new(0., 0., 0.);
+                    for (dx, dy) in directions.iter() {
+                        for (step, weight) in falloff.iter().enumerate() {
+                            let distance = (step + 1) as f32;
+                            let sx = (x as f32 - dx * distance).round();
+                            let sy = (y as f32 - dy * distance).round();
+                            if sx < 0.
+                                || sy < 0.
+                                || sx >= canvas.width as f32
+                                || sy >= canvas.height as f32
+                            {
+                                break;
+                            }
+                            streak = streak + pixel_at(&bright, sx as usize, sy as usize) * *weight;
+                        }
+                    }
+                    pixel_at(canvas, x, y) + streak * norm
+                })
+            }
+            PostEffect::Vignette { strength } => {
+                let corner = (cx * cx + cy * cy).sqrt();
+                map_pixels(canvas, |x, y| {
+                    let (dx, dy) = (x as f32 + 0.5 - cx, y as f32 + 0.5 - cy);
+                    let r2 = (dx * dx + dy * dy) / (corner * corner);
+                    pixel_at(canvas, x, y) * (1. - strength * r2).max(0.)
+                })
+            }
+            PostEffect::ChromaticAberration { amount } => map_pixels(canvas, |x, y| {
+                let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+                // Shift straight color, then cover it with this pixel's alpha,
+                // so fringes never spill into transparent areas
+                let at = |scale: f32| {
+                    let (color, alpha) =
+                        sample(canvas, cx + (px - cx) * scale, cy + (py - cy) * scale);
+                    if alpha <= 0. {
+                        Color::new(0., 0., 0.)
+                    } else {
+                        color * (1. / alpha)
+                    }
+                };
+                let alpha = alpha_at(canvas, x, y);
+                // Sampling closer to the center spreads a channel outwards
+                Color::new(
+                    at(1. - amount).red * alpha,
+                    pixel_at(canvas, x, y).green,
+                    at(1. + amount).blue * alpha,
+                )
+            }),
+        }
+    }
+}
+
+// Run the effects in the order given
+pub fn apply_effects(canvas: &Canvas, effects: &[PostEffect]) -> Canvas {
+    effects
+        .iter()
+        .fold(canvas.clone(), |image, effect| effect.apply(&image))
+}
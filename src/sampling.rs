@@ -0,0 +1,203 @@
+use std::f32::consts::PI;
+
+use rand::Rng;
+
+use crate::{cross, normalize, vector, Tuple};
+
+// Map the unit square onto the unit disk, keeping strata roughly intact
+pub fn concentric_sample_disk(u: f32, v: f32) -> (f32, f32) {
+    let ox = 2. * u - 1.;
+    let oy = 2. * v - 1.;
+    if ox == 0. && oy == 0. {
+        return (0., 0.);
+    }
+
+    let (r, theta) = if ox.abs() > oy.abs() {
+        (ox, PI / 4. * (oy / ox))
+    } else {
+        (oy, PI / 2. - PI / 4. * (ox / oy))
+    };
+    (r * theta.cos(), r * theta.sin())
+}
+
+// Uniformly sample a regular polygon inscribed in the unit circle
+pub fn sample_polygon(sides: u32, u: f32, v: f32) -> (f32, f32) {
+    let sides = sides.max(3);
+    let scaled = u * sides as f32;
+    let sector = (scaled.floor() as u32).min(sides - 1);
+    let u = scaled - sector as f32;
+
+    let angle = 2. * PI / sides as f32;
+    let start = sector as f32 * angle;
+    let (ax, ay) = (start.cos(), start.sin());
+    let (bx, by) = ((start + angle).cos(), (start + angle).sin());
+
+    // Uniform point in the triangle formed by the center and one edge
+    let su = u.sqrt();
+    let b0 = su * (1. - v);
+    let b1 = su * v;
+    (ax * b0 + bx * b1, ay * b0 + by * b1)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SamplePattern {
+    Grid,
+    Jittered,
+    Random,
+}
+
+// Sample positions in the unit square. Grid and jittered patterns round the
+// count up to the next square number so every stratum gets one sample.
+pub fn pixel_samples<R: Rng>(pattern: SamplePattern, count: usize, rng: &mut R) -> Vec<(f32, f32)> {
+    let count = count.max(1);
+    let side = (count as f32).sqrt().ceil() as usize;
+    let cell = 1. / side as f32;
+
+    match pattern {
+        SamplePattern::Grid => (0..side * side)
+            .map(|i| {
+                let (sx, sy) = (i % side, i / side);
+                ((sx as f32 + 0.5) * cell, (sy as f32 + 0.5) * cell)
+            })
+            .collect(),
+        SamplePattern::Jittered => (0..side * side)
+            .map(|i| {
+                let (sx, sy) = (i % side, i / side);
+                (
+                    (sx as f32 + rng.gen::<f32>()) * cell,
+                    (sy as f32 + rng.gen::<f32>()) * cell,
+                )
+            })
+            .collect(),
+        SamplePattern::Random => (0..count)
+            .map(|_| (rng.gen::<f32>(), rng.gen::<f32>()))
+            .collect(),
+    }
+}
+
+// Build a right-handed orthonormal basis around a unit normal
+pub fn orthonormal_basis(normal: Tuple) -> (Tuple, Tuple) {
+    let helper = if normal.x.abs() > 0.9 {
+        vector(0., 1., 0.)
+    } else {
+        vector(1., 0., 0.)
+    };
+    let tangent = normalize(cross(helper, normal));
+    let bitangent = cross(normal, tangent);
+    (tangent, bitangent)
+}
+
+// Cosine-weighted direction in the hemisphere around the normal, pdf = cos / pi
+pub fn cosine_sample_hemisphere(normal: Tuple, u: f32, v: f32) -> Tuple {
+    let (x, y) = concentric_sample_disk(u, v);
+    let z = f32::sqrt((1. - x * x - y * y).max(0.));
+    let (tangent, bitangent) = orthonormal_basis(normal);
+    normalize(tangent * x + bitangent * y + normal * z)
+}
+
+// Uniformly distributed direction over the whole sphere, pdf = 1 / 4pi
+pub fn uniform_sample_sphere(u: f32, v: f32) -> Tuple {
+    let z = 1. - 2. * u;
+    let r = f32::sqrt((1. - z * z).max(0.));
+    let phi = 2. * PI * v;
+    vector(r * phi.cos(), r * phi.sin(), z)
+}
+
+// Piecewise-constant distribution over [0, 1) for importance sampling
+#[derive(Debug, Clone, PartialEq)]
+pub struct Distribution1D {
+    pub func: Vec<f32>,
+    cdf: Vec<f32>,
+    pub integral: f32,
+}
+
+impl Distribution1D {
+    pub fn new(func: Vec<f32>) -> Self {
+        let n = func.len().max(1);
+        let mut cdf = vec![0.; n + 1];
+        for i in 0..func.len() {
+            cdf[i + 1] = cdf[i] + func[i].max(0.) / n as f32;
+        }
+        let integral = cdf[n];
+        for (i, value) in cdf.iter_mut().enumerate() {
+            // An all-zero function falls back to uniform sampling
+            *value = if integral > 0. {
+                *value / integral
+            } else {
+                i as f32 / n as f32
+            };
+        }
+        Distribution1D {
+            func,
+            cdf,
+            integral,
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.func.len()
+    }
+
+    // Density of the segment containing x
+    pub fn pdf(&self, x: f32) -> f32 {
+        let n = self.count();
+        if n == 0 {
+            return 0.;
+        }
+        let i = ((x * n as f32) as usize).min(n - 1);
+        if self.integral > 0. {
+            self.func[i].max(0.) / self.integral
+        } else {
+            1.
+        }
+    }
+
+    // Returns the sampled position, its density and the segment it fell in
+    pub fn sample(&self, u: f32) -> (f32, f32, usize) {
+        let n = self.count().max(1);
+        let offset = (self.cdf.partition_point(|&c| c <= u).max(1) - 1).min(n - 1);
+        let width = self.cdf[offset + 1] - self.cdf[offset];
+        let du = if width > 0. {
+            ((u - self.cdf[offset]) / width).clamp(0., 1.)
+        } else {
+            0.
+        };
+        let x = ((offset as f32 + du) / n as f32).min(1. - f32::EPSILON);
+        (x, self.pdf(x), offset)
+    }
+}
+
+// Piecewise-constant distribution over the unit square, given one row of
+// values per v segment
+#[derive(Debug, Clone, PartialEq)]
+pub struct Distribution2D {
+    conditional: Vec<Distribution1D>,
+    marginal: Distribution1D,
+}
+
+impl Distribution2D {
+    pub fn new(rows: Vec<Vec<f32>>) -> Self {
+        let conditional: Vec<Distribution1D> = rows.into_iter().map(Distribution1D::new).collect();
+        let marginal = Distribution1D::new(conditional.iter().map(|row| row.integral).collect());
+        Distribution2D {
+            conditional,
+            marginal,
+        }
+    }
+
+    // Returns (u, v) and the density with respect to area on the unit square
+    pub fn sample(&self, u0: f32, u1: f32) -> ((f32, f32), f32) {
+        let (v, pdf_v, row) = self.marginal.sample(u1);
+        let (u, pdf_u, _) = self.conditional[row].sample(u0);
+        ((u, v), pdf_u * pdf_v)
+    }
+
+    pub fn pdf(&self, u: f32, v: f32) -> f32 {
+        let rows = self.conditional.len();
+        if rows == 0 {
+            return 0.;
+        }
+        let row = ((v * rows as f32) as usize).min(rows - 1);
+        self.conditional[row].pdf(u) * self.marginal.pdf(v)
+    }
+}
@@ -0,0 +1,155 @@
+use crate::{dot, lights::DirectionalLight, normalize, vector, Color, Tuple};
+
+// Perez et al. sky luminance distribution
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Perez {
+    a: f32,
+    b: f32,
+    c: f32,
+    d: f32,
+    e: f32,
+}
+
+impl Perez {
+    // theta is the view zenith angle, gamma the angle between view and sun
+    fn eval(&self, cos_theta: f32, gamma: f32) -> f32 {
+        (1. + self.a * f32::exp(self.b / cos_theta))
+            * (1. + self.c * f32::exp(self.d * gamma) + self.e * gamma.cos() * gamma.cos())
+    }
+}
+
+fn cubic(coefficients: [f32; 4], theta: f32) -> f32 {
+    let [a, b, c, d] = coefficients;
+    ((a * theta + b) * theta + c) * theta + d
+}
+
+fn xyz_to_rgb(x: f32, y: f32, z: f32) -> Color {
+    Color::new(
+        3.2406 * x - 1.5372 * y - 0.4986 * z,
+        -0.9689 * x + 1.8758 * y + 0.0415 * z,
+        0.0557 * x - 0.2040 * y + 1.0570 * z,
+    )
+}
+
+// Preetham, Shirley and Smits' analytic daylight model. Radiance is relative
+// to the zenith, whose luminance equals `intensity`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PreethamSky {
+    // Unit vector pointing towards the sun
+    pub sun_direction: Tuple,
+    // Haziness: 2 is a very clear sky, 10 is hazy
+    pub turbidity: f32,
+    pub intensity: f32,
+    luminance: Perez,
+    x: Perez,
+    y: Perez,
+    // Zenith chromaticity
+    zenith: (f32, f32),
+}
+
+impl PreethamSky {
+    pub fn new(sun_direction: Tuple, turbidity: f32) -> Self {
+        let sun_direction = normalize(sun_direction);
+        let t = turbidity;
+        // The model breaks down once the sun sets
+        let theta_s = f32::acos(sun_direction.y.clamp(0., 1.));
+
+        let luminance = Perez {
+            a: 0.1787 * t - 1.4630,
+            b: -0.3554 * t + 0.4275,
+            c: -0.0227 * t + 5.3251,
+            d: 0.1206 * t - 2.5771,
+            e: -0.0670 * t + 0.3703,
+        };
+        let x = Perez {
+            a: -0.0193 * t - 0.2592,
+            b: -0.0665 * t + 0.0008,
+            c: -0.0004 * t + 0.2125,
+            d: -0.0641 * t - 0.8989,
+            e: -0.0033 * t + 0.0452,
+        };
+        let y = Perez {
+            a: -0.0167 * t - 0.2608,
+            b: -0.0950 * t + 0.0092,
+            c: -0.0079 * t + 0.2102,
+            d: -0.0441 * t - 1.6537,
+            e: -0.0109 * t + 0.0529,
+        };
+
+        let zenith_x = t * t * cubic([0.00166, -0.00375, 0.00209, 0.], theta_s)
+            + t * cubic([-0.02903, 0.06377, -0.03202, 0.00394], theta_s)
+            + cubic([0.11693, -0.21196, 0.06052, 0.25886], theta_s);
+        let zenith_y = t * t * cubic([0.00275, -0.00610, 0.00317, 0.], theta_s)
+            + t * cubic([-0.04214, 0.08970, -0.04153, 0.00516], theta_s)
+            + cubic([0.15346, -0.26756, 0.06670, 0.26688], theta_s);
+
+        PreethamSky {
+            sun_direction,
+            turbidity,
+            intensity: 1.,
+            luminance,
+            x,
+            y,
+            zenith: (zenith_x, zenith_y),
+        }
+    }
+
+    pub fn radiance(&self, direction: Tuple) -> Color {
+        // Below the horizon, repeat the horizon in the same compass direction
+        let mut d = normalize(direction);
+        if d.y < 0.01 {
+            let horizontal = if d.x == 0. && d.z == 0. {
+                vector(1., 0., 0.)
+            } else {
+                normalize(vector(d.x, 0., d.z))
+            };
+            d = normalize(horizontal + vector(0., 0.01, 0.));
+        }
+        let cos_theta = d.y;
+        let gamma = dot(d, self.sun_direction).clamp(-1., 1.).acos();
+        let theta_s = f32::acos(self.sun_direction.y.clamp(0., 1.));
+
+        let relative = |perez: &Perez| perez.eval(cos_theta, gamma) / perez.eval(1., theta_s);
+        let (zenith_x, zenith_y) = self.zenith;
+        let luminance = self.intensity * relative(&self.luminance);
+        let x = zenith_x * relative(&self.x);
+        let y = zenith_y * relative(&self.y);
+        if y <= 0. {
+            return Color::new(0., 0., 0.);
+        }
+
+        let rgb = xyz_to_rgb(x / y * luminance, luminance, (1. - x - y) / y * luminance);
+        Color::new(rgb.red.max(0.), rgb.green.max(0.), rgb.blue.max(0.))
+    }
+
+    // Color of direct sunlight after Rayleigh and aerosol extinction along the
+    // path through the atmosphere, normalised to unit luminance. Black once the
+    // sun is below the horizon.
+    pub fn sun_color(&self) -> Color {
+        if self.sun_direction.y <= 0. {
+            return Color::new(0., 0., 0.);
+        }
+        let theta_s = self.sun_direction.y.acos();
+        let relative_air_mass =
+            1. / (theta_s.cos() + 0.15 * f32::powf(93.885 - theta_s.to_degrees(), -1.253));
+        let beta = 0.046_083_66 * self.turbidity - 0.045_860_26;
+
+        // Representative wavelengths in micrometres for red, green and blue
+        let transmittance = |lambda: f32| {
+            let rayleigh = f32::exp(-0.008735 * lambda.powf(-4.08) * relative_air_mass);
+            let aerosol = f32::exp(-beta * lambda.powf(-1.3) * relative_air_mass);
+            rayleigh * aerosol
+        };
+        let color = Color::new(
+            transmittance(0.68),
+            transmittance(0.55),
+            transmittance(0.44),
+        );
+        color * (1. / color.luminance())
+    }
+
+    // A directional light from the sun's position, tinted to match the sky
+    pub fn sun_light(&self, intensity: f32) -> DirectionalLight {
+        DirectionalLight::new(self.sun_direction, self.sun_color() * intensity)
+    }
+}
@@ -6,7 +6,7 @@ use crate::shape::Shape;
 use crate::world::ShapeEnum;
 use crate::{dot, normalize, point, Tuple};
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct Sphere {
     pub center: Tuple,
     pub radius: f32,
@@ -46,8 +46,8 @@ impl Shape for Sphere {
             let t1 = (-b - f32::sqrt(discriminant)) / (2. * a);
             let t2 = (-b + f32::sqrt(discriminant)) / (2. * a);
             vec![
-                Intersection::new(t1, ShapeEnum::Sphere(*self)),
-                Intersection::new(t2, ShapeEnum::Sphere(*self)),
+                Intersection::new(t1, ShapeEnum::Sphere(self.clone())),
+                Intersection::new(t2, ShapeEnum::Sphere(self.clone())),
             ]
         }
     }
@@ -0,0 +1,132 @@
+use std::f32::consts::PI;
+
+use crate::{canvas::Canvas, magnitude, Color, Tuple};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UvMapping {
+    Planar,
+    Spherical,
+    Cylindrical,
+    Cubic,
+}
+
+impl UvMapping {
+    pub fn map(&self, point: Tuple) -> (f32, f32) {
+        match self {
+            UvMapping::Planar => planar_map(point),
+            UvMapping::Spherical => spherical_map(point),
+            UvMapping::Cylindrical => cylindrical_map(point),
+            UvMapping::Cubic => cubic_map(point),
+        }
+    }
+}
+
+pub fn spherical_map(point: Tuple) -> (f32, f32) {
+    let theta = f32::atan2(point.x, point.z);
+    let radius = magnitude(Tuple::new(point.x, point.y, point.z, 0.));
+    let phi = f32::acos((point.y / radius).clamp(-1., 1.));
+
+    let raw_u = theta / (2. * PI);
+    let u = 1. - (raw_u + 0.5);
+    let v = 1. - phi / PI;
+    (u, v)
+}
+
+pub fn planar_map(point: Tuple) -> (f32, f32) {
+    (point.x.rem_euclid(1.), point.z.rem_euclid(1.))
+}
+
+pub fn cylindrical_map(point: Tuple) -> (f32, f32) {
+    let theta = f32::atan2(point.x, point.z);
+    let raw_u = theta / (2. * PI);
+    let u = 1. - (raw_u + 0.5);
+    let v = point.y.rem_euclid(1.);
+    (u, v)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CubeFace {
+    Left,
+    Right,
+    Front,
+    Back,
+    Up,
+    Down,
+}
+
+pub fn face_from_point(point: Tuple) -> CubeFace {
+    let abs_x = point.x.abs();
+    let abs_y = point.y.abs();
+    let abs_z = point.z.abs();
+    let coord = abs_x.max(abs_y).max(abs_z);
+
+    if coord == point.x {
+        CubeFace::Right
+    } else if coord == -point.x {
+        CubeFace::Left
+    } else if coord == point.y {
+        CubeFace::Up
+    } else if coord == -point.y {
+        CubeFace::Down
+    } else if coord == point.z {
+        CubeFace::Front
+    } else {
+        CubeFace::Back
+    }
+}
+
+pub fn cube_uv(face: CubeFace, point: Tuple) -> (f32, f32) {
+    let (u, v) = match face {
+        CubeFace::Front => ((point.x + 1.) / 2., (point.y + 1.) / 2.),
+        CubeFace::Back => ((1. - point.x) / 2., (point.y + 1.) / 2.),
+        CubeFace::Left => ((point.z + 1.) / 2., (point.y + 1.) / 2.),
+        CubeFace::Right => ((1. - point.z) / 2., (point.y + 1.) / 2.),
+        CubeFace::Up => ((point.x + 1.) / 2., (1. - point.z) / 2.),
+        CubeFace::Down => ((point.x + 1.) / 2., (point.z + 1.) / 2.),
+    };
+    (u.rem_euclid(1.), v.rem_euclid(1.))
+}
+
+pub fn cubic_map(point: Tuple) -> (f32, f32) {
+    // Lay the six faces out in a 4x3 cross so a single image covers the whole cube
+    let face = face_from_point(point);
+    let (u, v) = cube_uv(face, point);
+    let (col, row) = match face {
+        CubeFace::Left => (0., 1.),
+        CubeFace::Front => (1., 1.),
+        CubeFace::Right => (2., 1.),
+        CubeFace::Back => (3., 1.),
+        CubeFace::Up => (1., 2.),
+        CubeFace::Down => (1., 0.),
+    };
+    ((col + u) / 4., (row + v) / 3.)
+}
+
+pub fn uv_sample(canvas: &Canvas, u: f32, v: f32) -> Color {
+    // An empty texture has no texels to blend
+    if canvas.width == 0 || canvas.height == 0 {
+        return Color::new(0., 0., 0.);
+    }
+
+    // Bilinear lookup with v pointing up the image, wrapping horizontally
+    let x = u.rem_euclid(1.) * canvas.width as f32 - 0.5;
+    let y = (1. - v.clamp(0., 1.)) * canvas.height as f32 - 0.5;
+
+    let x0 = x.floor();
+    let y0 = y.floor();
+    let fx = x - x0;
+    let fy = y - y0;
+
+    let width = canvas.width as i64;
+    let height = canvas.height as i64;
+    let texel = |tx: i64, ty: i64| -> Color {
+        let tx = tx.rem_euclid(width) as usize;
+        let ty = ty.clamp(0, height - 1) as usize;
+        canvas.pixels[ty][tx]
+    };
+
+    let (x0, y0) = (x0 as i64, y0 as i64);
+    let top = texel(x0, y0) * (1. - fx) + texel(x0 + 1, y0) * fx;
+    let bottom = texel(x0, y0 + 1) * (1. - fx) + texel(x0 + 1, y0 + 1) * fx;
+    top * (1. - fy) + bottom * fy
+}
@@ -1,23 +1,30 @@
 use crate::{
+    background::Background,
+    compositing::shadow_catcher_color,
+    cone::Cone,
     cube::Cube,
     cylinder::Cylinder,
     dot,
+    environment::{environment_lighting, EnvironmentLight},
     intersections::{hit, prepare_computations, shlick, Intersection, Precomputation},
-    lights::{lighting, PointLight},
+    lights::{lighting_directional, lighting_occluded, DirectionalLight, PointLight},
     magnitude,
     materials::Material,
+    matrix::Matrix,
+    medium::{has_media, light_transmittance, lit_through_media, march, Medium},
     normalize,
+    occlusion::{point_rng, AmbientOcclusion},
+    photon::{caustic_lighting, PhotonMap},
     plane::Plane,
     point,
     ray::Ray,
-    shape::intersect,
+    shape::{intersect, Shape},
     sphere::Sphere,
-    cone::Cone,
     transforms::scaling,
     Color, Tuple,
 };
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum ShapeEnum {
     Sphere(Sphere),
     Plane(Plane),
@@ -26,28 +33,73 @@ pub enum ShapeEnum {
     Cone(Cone),
 }
 
+impl ShapeEnum {
+    pub fn material(&self) -> &Material {
+        match self {
+            ShapeEnum::Sphere(sphere) => &sphere.material,
+            ShapeEnum::Plane(plane) => &plane.material,
+            ShapeEnum::Cube(cube) => &cube.material,
+            ShapeEnum::Cylinder(cylinder) => &cylinder.material,
+            ShapeEnum::Cone(cone) => &cone.material,
+        }
+    }
+
+    pub fn transform(&self) -> Matrix<f32, 4, 4> {
+        match self {
+            ShapeEnum::Sphere(sphere) => sphere.get_transform(),
+            ShapeEnum::Plane(plane) => plane.get_transform(),
+            ShapeEnum::Cube(cube) => cube.get_transform(),
+            ShapeEnum::Cylinder(cylinder) => cylinder.get_transform(),
+            ShapeEnum::Cone(cone) => cone.get_transform(),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct World {
     pub objects: Vec<ShapeEnum>,
     pub light: Option<PointLight>,
+    // Scales each material's ambient term by hemisphere visibility when set
+    pub ambient_occlusion: Option<AmbientOcclusion>,
+    // Seen by any ray that escapes the scene, including reflections and refractions
+    pub background: Background,
+    // Image-based lighting on top of the point light
+    pub environment: Option<EnvironmentLight>,
+    // Distant light alongside the point light, e.g. from `PreethamSky::sun_light`
+    pub sun: Option<DirectionalLight>,
+    // Global fog; shapes can hold their own medium through their material
+    pub medium: Option<Medium>,
+    // Caustics from `PhotonMap::new`, built once the rest of the world is set up
+    pub caustics: Option<PhotonMap>,
 }
 
 impl Default for World {
     fn default() -> Self {
         let light = PointLight::new(point(-10., 10., -10.), Color::new(1., 1., 1.));
-        let mut s1 = Sphere::default();
-        let mut m = Material::default();
-        m.color = Color::new(0.8, 1.0, 0.6);
-        m.diffuse = 0.7;
-        m.specular = 0.2;
-        s1.material = m;
-
-        let mut s2 = Sphere::default();
-        s2.transform = scaling(0.5, 0.5, 0.5);
+        let s1 = Sphere {
+            material: Material {
+                color: Color::new(0.8, 1.0, 0.6),
+                diffuse: 0.7,
+                specular: 0.2,
+                ..Material::default()
+            },
+            ..Sphere::default()
+        };
+
+        let s2 = Sphere {
+            transform: scaling(0.5, 0.5, 0.5),
+            ..Sphere::default()
+        };
         let objects = vec![ShapeEnum::Sphere(s1), ShapeEnum::Sphere(s2)];
         World {
             objects,
             light: Some(light),
+            ambient_occlusion: None,
+            background: Background::default(),
+            environment: None,
+            sun: None,
+            medium: None,
+            caustics: None,
         }
     }
 }
@@ -57,6 +109,12 @@ impl World {
         World {
             objects: Vec::new(),
             light: None,
+            ambient_occlusion: None,
+            background: Background::default(),
+            environment: None,
+            sun: None,
+            medium: None,
+            caustics: None,
         }
     }
 }
@@ -70,61 +128,155 @@ pub fn contains(world: &World, object: ShapeEnum) -> bool {
     false
 }
 
+pub fn intersect_shape(object: ShapeEnum, ray: Ray) -> Vec<Intersection> {
+    match object {
+        ShapeEnum::Plane(plane) => intersect(plane, ray),
+        ShapeEnum::Sphere(sphere) => intersect(sphere, ray),
+        ShapeEnum::Cube(cube) => intersect(cube, ray),
+        ShapeEnum::Cylinder(cylinder) => intersect(cylinder, ray),
+        ShapeEnum::Cone(cone) => intersect(cone, ray),
+    }
+}
+
 pub fn intersect_world(world: &World, ray: Ray) -> Vec<Intersection> {
     let mut intersections = Vec::new();
     for obj in &world.objects {
-        let obj_intersects = match *obj {
-            ShapeEnum::Plane(plane) => intersect(plane, ray),
-            ShapeEnum::Sphere(sphere) => intersect(sphere, ray),
-            ShapeEnum::Cube(cube) => intersect(cube, ray),
-            ShapeEnum::Cylinder(cylinder) => intersect(cylinder, ray),
-            ShapeEnum::Cone(cone) => intersect(cone, ray),
-        };
-        intersections.extend(obj_intersects);
+        intersections.extend(intersect_shape(obj.clone(), ray));
     }
-    intersections.sort_by(|&a, &b| (a.t).partial_cmp(&b.t).unwrap());
+    intersections.sort_by(|a, b| (a.t).partial_cmp(&b.t).unwrap());
     intersections
 }
 
-pub fn shade_hit(world: &World, comps: Precomputation, remaining: u16) -> Color {
-    let material = match comps.object {
-        ShapeEnum::Sphere(sphere) => sphere.material,
-        ShapeEnum::Plane(plane) => plane.material,
-        ShapeEnum::Cube(cube) => cube.material,
-        ShapeEnum::Cylinder(cylinder) => cylinder.material,
-        ShapeEnum::Cone(cone) => cone.material,
+// The three contributions `shade_hit` adds together, with the Fresnel split
+// between reflection and refraction already applied
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShadeTerms {
+    pub light: Color,
+    pub reflected: Color,
+    pub refracted: Color,
+}
+
+pub fn shade_terms(world: &World, comps: Precomputation, remaining: u16) -> ShadeTerms {
+    let material = comps.object.material();
+
+    let reflected = reflected_color(world, comps.clone(), remaining);
+    let refracted = refracted_color(world, comps.clone(), remaining);
+    let visibility = match world.ambient_occlusion {
+        Some(occlusion) => occlusion.visibility(
+            world,
+            comps.over_point,
+            comps.normalv,
+            &mut point_rng(comps.over_point),
+        ),
+        None => 1.,
+    };
+    // The ambient term scales with the point light, so an environment-only
+    // scene gets no ambient at all
+    let point_light = match world.light {
+        Some(light) => {
+            let lit = |in_shadow| {
+                lighting_occluded(
+                    material,
+                    &comps.object,
+                    light,
+                    comps.over_point,
+                    comps.eyev,
+                    comps.normalv,
+                    in_shadow,
+                    visibility,
+                )
+            };
+            if has_media(world) {
+                let v = light.position - comps.over_point;
+                let transmittance =
+                    light_transmittance(world, comps.over_point, normalize(v), magnitude(v));
+                lit_through_media(lit, transmittance)
+            } else {
+                lit(is_shadowed(world, comps.over_point))
+            }
+        }
+        None => Color::new(0., 0., 0.),
+    };
+    let sun = match world.sun {
+        Some(sun) => {
+            let lit = |in_shadow| {
+                lighting_directional(
+                    material,
+                    &comps.object,
+                    sun,
+                    comps.over_point,
+                    comps.eyev,
+                    comps.normalv,
+                    in_shadow,
+                    visibility,
+                )
+            };
+            if has_media(world) {
+                let transmittance =
+                    light_transmittance(world, comps.over_point, sun.direction, f32::INFINITY);
+                lit_through_media(lit, transmittance)
+            } else {
+                lit(is_sun_shadowed(world, comps.over_point))
+            }
+        }
+        None => Color::new(0., 0., 0.),
     };
+    let light = point_light
+        + sun
+        + environment_lighting(world, &comps, &mut point_rng(comps.over_point))
+        + caustic_lighting(world, &comps);
 
-    let reflected = reflected_color(world, comps, remaining);
-    let refracted = refracted_color(world, comps, remaining);
-    let light = lighting(
-        material,
-        comps.object,
-        world.light.unwrap(),
-        comps.over_point,
-        comps.eyev,
-        comps.normalv,
-        is_shadowed(world, comps.over_point),
-    );
-
-    if material.reflective > 0. && material.transparency > 0. {
+    let reflective = material.reflective_at(&comps.object, comps.over_point);
+    let transparency = material.transparency_at(&comps.object, comps.over_point);
+    if reflective > 0. && transparency > 0. {
         let reflectance = shlick(comps);
-        light + reflected * reflectance + refracted * (1. - reflectance)
+        ShadeTerms {
+            light,
+            reflected: reflected * reflectance,
+            refracted: refracted * (1. - reflectance),
+        }
     } else {
-        light + reflected + refracted
+        ShadeTerms {
+            light,
+            reflected,
+            refracted,
+        }
+    }
+}
+
+pub fn shade_hit(world: &World, comps: Precomputation, remaining: u16) -> Color {
+    let material = comps.object.material();
+    if material.holdout {
+        return Color::new(0., 0., 0.);
+    }
+    if material.shadow_catcher {
+        return shadow_catcher_color(world, comps.clone(), remaining);
     }
+    let terms = shade_terms(world, comps, remaining);
+    terms.light + terms.reflected + terms.refracted
 }
 
 pub fn color_at(world: &World, ray: Ray, remaining: u16) -> Color {
     let intersections = intersect_world(world, ray);
     let hits = hit(intersections.clone());
 
-    if hits.is_none() {
-        return Color::new(0., 0., 0.);
+    let (color, distance) = match hits {
+        Some(intersection) => {
+            let t = intersection.t;
+            let comps = prepare_computations(intersection, ray, intersections);
+            (shade_hit(world, comps, remaining), t)
+        }
+        None => (world.background.color_at(ray.direction), f32::INFINITY),
+    };
+    if !has_media(world) {
+        return color;
     }
 
-    let comps = prepare_computations(hits.unwrap(), ray, intersections);
-    shade_hit(world, comps, remaining)
+    // Light scattered in by media in front of the surface, seeded from the ray
+    // so renders stay deterministic
+    let mut rng = point_rng(ray.origin + ray.direction);
+    let (scattered, transmittance) = march(world, ray, distance, &mut rng);
+    color * transmittance + scattered
 }
 
 pub fn is_shadowed(world: &World, point: Tuple) -> bool {
@@ -138,12 +290,19 @@ pub fn is_shadowed(world: &World, point: Tuple) -> bool {
 
         let h = hit(intersections);
         if let Some(intersect) = h {
-            return intersect.t < distance;
+            intersect.t < distance
         } else {
-            return false;
+            false
         }
     } else {
-        return false;
+        false
+    }
+}
+
+pub fn is_sun_shadowed(world: &World, point: Tuple) -> bool {
+    match world.sun {
+        Some(sun) => hit(intersect_world(world, Ray::new(point, sun.direction))).is_some(),
+        None => false,
     }
 }
 
@@ -152,28 +311,24 @@ pub fn reflected_color(w: &World, comps: Precomputation, remaining: u16) -> Colo
         return Color::new(0., 0., 0.);
     }
 
-    let material = match comps.object {
-        ShapeEnum::Sphere(sphere) => sphere.material,
-        ShapeEnum::Plane(plane) => plane.material,
-        ShapeEnum::Cube(cube) => cube.material,
-        ShapeEnum::Cylinder(cylinder) => cylinder.material,
-        ShapeEnum::Cone(cone) => cone.material,
-    };
-
-    if material.reflective == 0. {
-        Color::new(0., 0., 0.);
+    let material = comps.object.material();
+    let reflective = material.reflective_at(&comps.object, comps.over_point);
+    if reflective == 0. {
+        return Color::new(0., 0., 0.);
     }
 
     let reflect_ray = Ray::new(comps.over_point, comps.reflectv);
-    let color = color_at(&w, reflect_ray, remaining - 1);
+    let color = color_at(w, reflect_ray, remaining - 1);
 
-    color * material.reflective
+    color * reflective
 }
 
 pub fn refracted_color(w: &World, comps: Precomputation, remaining: u16) -> Color {
     let black = Color::new(0., 0., 0.);
 
-    if remaining < 1 {
+    let material = comps.object.material();
+    let transparency = material.transparency_at(&comps.object, comps.under_point);
+    if remaining < 1 || transparency == 0. {
         return black;
     }
 
@@ -193,14 +348,5 @@ pub fn refracted_color(w: &World, comps: Precomputation, remaining: u16) -> Colo
     let direction = comps.normalv * (n_ratio * cos_i - cos_t) - comps.eyev * n_ratio;
     let refract_ray = Ray::new(comps.under_point, direction);
 
-    let material = match comps.object {
-        ShapeEnum::Sphere(sphere) => sphere.material,
-        ShapeEnum::Plane(plane) => plane.material,
-        ShapeEnum::Cube(cube) => cube.material,
-        ShapeEnum::Cylinder(cylinder) => cylinder.material,
-        ShapeEnum::Cone(cone) => cone.material,
-    };
-
-    let color = color_at(w, refract_ray, remaining - 1) * material.transparency;
-    color
+    color_at(w, refract_ray, remaining - 1) * transparency
 }
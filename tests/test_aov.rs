@@ -0,0 +1,265 @@
+mod tests {
+    use std::f32::consts::PI;
+
+    use tracer::aov::{trace_aovs, trace_features, Aov, AovImages};
+    use tracer::camera::{
+        render_with, render_with_aovs, render_with_integrator, AdaptiveSampling, Camera,
+        RenderSettings,
+    };
+    use tracer::debug::false_color;
+    use tracer::integrators::{PathTracer, Whitted};
+    use tracer::intersections::{prepare_computations, Intersection};
+    use tracer::medium::{medium_boundary, Medium};
+    use tracer::plane::Plane;
+    use tracer::ray::Ray;
+    use tracer::sphere::Sphere;
+    use tracer::transforms::{translation, view_transform};
+    use tracer::world::{color_at, shade_hit, shade_terms, ShapeEnum, World};
+    use tracer::{point, vector, Color};
+
+    fn glass_floor_world() -> World {
+        let mut w = World::default();
+        let mut floor = Plane {
+            transform: translation(0., -1., 0.),
+            ..Plane::default()
+        };
+        floor.material.reflective = 0.5;
+        floor.material.transparency = 0.5;
+        floor.material.refractive_index = 1.5;
+        w.objects.push(ShapeEnum::Plane(floor));
+
+        let mut ball = Sphere {
+            transform: translation(0., -3.5, -0.5),
+            ..Sphere::default()
+        };
+        ball.material.color = Color::new(1., 0., 0.);
+        ball.material.ambient = 0.5;
+        w.objects.push(ShapeEnum::Sphere(ball));
+        w
+    }
+
+    fn camera() -> Camera {
+        let mut c = Camera::new(11, 11, PI / 2.);
+        c.transform = view_transform(point(0., 0., -5.), point(0., 0., 0.), vector(0., 1., 0.));
+        c
+    }
+
+    #[test]
+    fn test_shade_terms_add_up_to_shade_hit() {
+        let w = glass_floor_world();
+        let floor = w.objects[2].clone();
+        let r = Ray::new(
+            point(0., 0., -3.),
+            vector(0., -f32::sqrt(2.) / 2., f32::sqrt(2.) / 2.),
+        );
+        let xs = vec![Intersection::new(f32::sqrt(2.), floor)];
+        let comps = prepare_computations(xs[0].clone(), r, xs);
+        let terms = shade_terms(&w, comps.clone(), 5);
+        assert!(terms.reflected != Color::new(0., 0., 0.));
+        assert!(terms.refracted != Color::new(0., 0., 0.));
+        assert_eq!(
+            terms.light + terms.reflected + terms.refracted,
+            shade_hit(&w, comps, 5)
+        );
+    }
+
+    #[test]
+    fn test_trace_aovs_at_first_hit() {
+        let w = World::default();
+        let r = Ray::new(point(0., 0., -5.), vector(0., 0., 1.));
+        let (color, sample) = trace_aovs(&w, r, 5);
+        assert_eq!(color, color_at(&w, r, 5));
+        assert_eq!(sample.albedo, Color::new(0.8, 1., 0.6));
+        assert_eq!(sample.normal, Color::new(0., 0., -1.));
+        assert_eq!(sample.depth, Color::new(4., 4., 4.));
+        assert_eq!(sample.object_id, false_color(0));
+        assert_eq!(sample.direct, color_at(&w, r, 5));
+        assert_eq!(sample.reflection, Color::new(0., 0., 0.));
+    }
+
+    #[test]
+    fn test_trace_features_skips_shading() {
+        let w = glass_floor_world();
+        let r = Ray::new(
+            point(0., 0., -3.),
+            vector(0., -f32::sqrt(2.) / 2., f32::sqrt(2.) / 2.),
+        );
+        let (_, shaded) = trace_aovs(&w, r, 5);
+        let features = trace_features(&w, r);
+        for aov in [Aov::Albedo, Aov::Normal, Aov::Depth, Aov::ObjectId] {
+            assert_eq!(features.get(aov), shaded.get(aov));
+        }
+        assert!(shaded.reflection != Color::new(0., 0., 0.));
+        for aov in [Aov::Direct, Aov::Reflection, Aov::Refraction] {
+            assert_eq!(features.get(aov), Color::new(0., 0., 0.));
+        }
+    }
+
+    #[test]
+    fn test_trace_aovs_miss_is_black() {
+        let w = World::default();
+        let r = Ray::new(point(0., 0., -5.), vector(0., 1., 0.));
+        let (_, sample) = trace_aovs(&w, r, 5);
+        for aov in [
+            Aov::Albedo,
+            Aov::Normal,
+            Aov::Depth,
+            Aov::ObjectId,
+            Aov::Direct,
+        ] {
+            assert_eq!(sample.get(aov), Color::new(0., 0., 0.));
+        }
+    }
+
+    #[test]
+    fn test_render_with_aovs_matches_beauty_render() -> Result<(), String> {
+        let w = glass_floor_world();
+        let settings = RenderSettings {
+            samples_per_pixel: 4,
+            ..RenderSettings::default()
+        };
+        let images = render_with_aovs(camera(), &w, &settings, &Whitted::new(5), &[Aov::Depth])?;
+        let beauty = render_with(camera(), &w, &settings)?;
+        assert_eq!(images.beauty.pixels, beauty.pixels);
+        assert!(images.get(Aov::Depth).is_some());
+        assert!(images.get(Aov::Albedo).is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_with_aovs_uses_integrator_and_settings() -> Result<(), String> {
+        let w = glass_floor_world();
+        let settings = RenderSettings {
+            adaptive: Some(AdaptiveSampling::default()),
+            transparent_background: true,
+            ..RenderSettings::default()
+        };
+        let tracer = PathTracer::default();
+        let images = render_with_aovs(camera(), &w, &settings, &tracer, &[Aov::Normal])?;
+        let (beauty, _) = render_with_integrator(camera(), &w, &settings, &tracer)?;
+        assert_eq!(images.beauty.pixels, beauty.pixels);
+        assert_eq!(images.beauty.alpha, beauty.alpha);
+        assert_eq!(images.beauty.alpha[0][0], 0.);
+        Ok(())
+    }
+
+    #[test]
+    fn test_light_passes_recombine_into_beauty() -> Result<(), String> {
+        let w = glass_floor_world();
+        let aovs = [Aov::Direct, Aov::Reflection, Aov::Refraction];
+        let images = render_with_aovs(
+            camera(),
+            &w,
+            &RenderSettings::default(),
+            &Whitted::new(5),
+            &aovs,
+        )?;
+        let direct = images.get(Aov::Direct).unwrap();
+        let reflection = images.get(Aov::Reflection).unwrap();
+        let refraction = images.get(Aov::Refraction).unwrap();
+
+        let mut reflective_pixels = 0;
+        for y in 0..11 {
+            for x in 0..11 {
+                let sum = direct.pixels[y][x] + reflection.pixels[y][x] + refraction.pixels[y][x];
+                assert_eq!(sum, images.beauty.pixels[y][x]);
+                if reflection.pixels[y][x] != Color::new(0., 0., 0.) {
+                    reflective_pixels += 1;
+                }
+            }
+        }
+        assert!(reflective_pixels > 0);
+        Ok(())
+    }
+
+    // The lighting buffers of every pixel add back up to the beauty image
+    fn assert_passes_recombine(images: &AovImages) {
+        let direct = images.get(Aov::Direct).unwrap();
+        let reflection = images.get(Aov::Reflection).unwrap();
+        let refraction = images.get(Aov::Refraction).unwrap();
+        for y in 0..images.beauty.height {
+            for x in 0..images.beauty.width {
+                let sum = direct.pixels[y][x] + reflection.pixels[y][x] + refraction.pixels[y][x];
+                let diff = sum - images.beauty.pixels[y][x];
+                assert!(diff.red.abs() < 1e-5, "{:?}", diff);
+                assert!(diff.green.abs() < 1e-5, "{:?}", diff);
+                assert!(diff.blue.abs() < 1e-5, "{:?}", diff);
+                for canvas in [direct, reflection, refraction] {
+                    assert_eq!(canvas.alpha[y][x], images.beauty.alpha[y][x]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_aovs_match_beauty_under_adaptive_sampling() -> Result<(), String> {
+        let w = glass_floor_world();
+        let settings = RenderSettings {
+            adaptive: Some(AdaptiveSampling {
+                threshold: 0.05,
+                max_depth: 3,
+            }),
+            transparent_background: true,
+            ..RenderSettings::default()
+        };
+        let aovs = [Aov::Direct, Aov::Reflection, Aov::Refraction, Aov::Depth];
+        let images = render_with_aovs(camera(), &w, &settings, &Whitted::new(5), &aovs)?;
+        let (beauty, _) = render_with_integrator(camera(), &w, &settings, &Whitted::new(5))?;
+        assert_eq!(images.beauty.pixels, beauty.pixels);
+        assert_eq!(images.beauty.alpha, beauty.alpha);
+        assert_passes_recombine(&images);
+
+        // Silhouettes get partial coverage in the buffers too
+        let depth = images.get(Aov::Depth).unwrap();
+        assert_eq!(depth.alpha, beauty.alpha);
+        assert!(beauty.alpha.iter().flatten().any(|a| *a > 0. && *a < 1.));
+        for y in 0..11 {
+            for x in 0..11 {
+                if beauty.alpha[y][x] == 0. {
+                    assert_eq!(depth.pixels[y][x], Color::new(0., 0., 0.));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_lighting_passes_include_media() -> Result<(), String> {
+        let mut w = glass_floor_world();
+        let medium = Medium::new(Color::new(0.1, 0.1, 0.1), Color::new(0.5, 0.5, 0.5));
+        w.objects.push(ShapeEnum::Sphere(Sphere {
+            transform: translation(0.5, 0., -2.),
+            material: medium_boundary(medium),
+            ..Sphere::default()
+        }));
+        let settings = RenderSettings {
+            samples_per_pixel: 4,
+            ..RenderSettings::default()
+        };
+        let aovs = [Aov::Direct, Aov::Reflection, Aov::Refraction];
+        let images = render_with_aovs(camera(), &w, &settings, &Whitted::new(5), &aovs)?;
+        assert_eq!(
+            images.beauty.pixels,
+            render_with(camera(), &w, &settings)?.pixels
+        );
+        assert_passes_recombine(&images);
+        Ok(())
+    }
+
+    #[test]
+    fn test_object_id_buffer() -> Result<(), String> {
+        let w = glass_floor_world();
+        let images = render_with_aovs(
+            camera(),
+            &w,
+            &RenderSettings::default(),
+            &Whitted::new(5),
+            &[Aov::ObjectId],
+        )?;
+        let ids = images.get(Aov::ObjectId).unwrap();
+        assert_eq!(ids.pixels[5][5], false_color(0));
+        assert_eq!(ids.pixels[10][0], false_color(2));
+        assert_eq!(ids.pixels[0][0], Color::new(0., 0., 0.));
+        Ok(())
+    }
+}
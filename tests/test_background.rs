@@ -0,0 +1,157 @@
+mod tests {
+    use std::sync::Arc;
+
+    use rand::{rngs::SmallRng, SeedableRng};
+    use tracer::background::{equirectangular_uv, Background, CubeMap};
+    use tracer::canvas::{write_pixel, Canvas};
+    use tracer::integrators::{Integrator, PathTracer};
+    use tracer::intersections::{prepare_computations, Intersection};
+    use tracer::materials::Material;
+    use tracer::plane::Plane;
+    use tracer::ray::Ray;
+    use tracer::transforms::translation;
+    use tracer::world::{color_at, reflected_color, ShapeEnum, World};
+    use tracer::{point, vector, Color};
+
+    fn solid(color: Color) -> Arc<Canvas> {
+        let mut canvas = Canvas::new(2, 2);
+        for y in 0..2 {
+            for x in 0..2 {
+                write_pixel(&mut canvas, x, y, color);
+            }
+        }
+        Arc::new(canvas)
+    }
+
+    #[test]
+    fn test_default_background_is_black() {
+        let w = World::default();
+        assert_eq!(w.background, Background::Color(Color::new(0., 0., 0.)));
+        let r = Ray::new(point(0., 0., -5.), vector(0., 1., 0.));
+        assert_eq!(color_at(&w, r, 5), Color::new(0., 0., 0.));
+    }
+
+    #[test]
+    fn test_missed_ray_sees_background_color() {
+        let w = World {
+            background: Background::Color(Color::new(0.2, 0.4, 0.6)),
+            ..World::default()
+        };
+        let r = Ray::new(point(0., 0., -5.), vector(0., 1., 0.));
+        assert_eq!(color_at(&w, r, 5), Color::new(0.2, 0.4, 0.6));
+    }
+
+    #[test]
+    fn test_sky_gradient() {
+        let sky = Background::Gradient {
+            bottom: Color::new(1., 1., 1.),
+            top: Color::new(0., 0., 1.),
+        };
+        assert_eq!(sky.color_at(vector(0., 1., 0.)), Color::new(0., 0., 1.));
+        assert_eq!(sky.color_at(vector(0., -2., 0.)), Color::new(1., 1., 1.));
+        assert_eq!(sky.color_at(vector(1., 0., 0.)), Color::new(0.5, 0.5, 1.));
+    }
+
+    #[test]
+    fn test_equirectangular_uv() {
+        let cases = [
+            (vector(0., 0., -1.), (0.5, 0.5)),
+            (vector(1., 0., 0.), (0.25, 0.5)),
+            (vector(-1., 0., 0.), (0.75, 0.5)),
+        ];
+        for (direction, (u, v)) in cases {
+            let (eu, ev) = equirectangular_uv(direction);
+            assert!((eu - u).abs() < 1e-5 && (ev - v).abs() < 1e-5);
+        }
+
+        // Longitude is arbitrary at the poles
+        assert_eq!(equirectangular_uv(vector(0., 1., 0.)).1, 1.);
+        assert_eq!(equirectangular_uv(vector(0., -1., 0.)).1, 0.);
+    }
+
+    #[test]
+    fn test_equirectangular_background() {
+        // Left half red, right half green
+        let mut canvas = Canvas::new(4, 2);
+        for y in 0..2 {
+            for x in 0..4 {
+                let color = if x < 2 {
+                    Color::new(1., 0., 0.)
+                } else {
+                    Color::new(0., 1., 0.)
+                };
+                write_pixel(&mut canvas, x, y, color);
+            }
+        }
+        let background = Background::Equirectangular(Arc::new(canvas));
+        assert_eq!(
+            background.color_at(vector(1., 0., 0.)),
+            Color::new(1., 0., 0.)
+        );
+        assert_eq!(
+            background.color_at(vector(-1., 0., 0.)),
+            Color::new(0., 1., 0.)
+        );
+    }
+
+    #[test]
+    fn test_cube_map_background() {
+        let cube = CubeMap {
+            left: solid(Color::new(1., 0., 0.)),
+            right: solid(Color::new(0., 1., 0.)),
+            front: solid(Color::new(0., 0., 1.)),
+            back: solid(Color::new(1., 1., 0.)),
+            up: solid(Color::new(0., 1., 1.)),
+            down: solid(Color::new(1., 0., 1.)),
+        };
+        let background = Background::CubeMap(cube.clone());
+        let cases = [
+            (vector(-1., 0.2, 0.1), cube.left),
+            (vector(3., -0.5, 0.5), cube.right),
+            (vector(0.1, 0.1, 2.), cube.front),
+            (vector(0., 0., -1.), cube.back),
+            (vector(0.3, 1., -0.3), cube.up),
+            (vector(0., -1., 0.), cube.down),
+        ];
+        for (direction, face) in cases {
+            assert_eq!(background.color_at(direction), face.pixels[0][0]);
+        }
+    }
+
+    #[test]
+    fn test_reflection_picks_up_background() {
+        let mut w = World::new();
+        w.light = World::default().light;
+        w.background = Background::Color(Color::new(0.2, 0.4, 0.6));
+        let shape = ShapeEnum::Plane(Plane {
+            transform: translation(0., -1., 0.),
+            material: Material {
+                reflective: 0.5,
+                ..Material::default()
+            },
+        });
+        w.objects.push(shape.clone());
+
+        let r = Ray::new(
+            point(0., 0., -3.),
+            vector(0., -f32::sqrt(2.) / 2., f32::sqrt(2.) / 2.),
+        );
+        let i = Intersection::new(f32::sqrt(2.), shape);
+        let comps = prepare_computations(i.clone(), r, vec![i]);
+        assert_eq!(reflected_color(&w, comps, 5), Color::new(0.1, 0.2, 0.3));
+    }
+
+    #[test]
+    fn test_path_tracer_escaping_ray_sees_background() {
+        let w = World {
+            background: Background::Color(Color::new(0.2, 0.4, 0.6)),
+            ..World::default()
+        };
+        let r = Ray::new(point(0., 0., -5.), vector(0., 1., 0.));
+        let mut rng = SmallRng::seed_from_u64(0);
+        assert_eq!(
+            PathTracer::default().li(&w, r, &mut rng),
+            Color::new(0.2, 0.4, 0.6)
+        );
+    }
+}
@@ -0,0 +1,123 @@
+mod tests {
+    use std::sync::Arc;
+
+    use tracer::bump::{perturb_normal, BumpMap, NormalMap};
+    use tracer::canvas::{write_pixel, Canvas};
+    use tracer::intersections::{prepare_computations, Intersection};
+    use tracer::patterns::{Pattern, PatternType, StripePattern};
+    use tracer::plane::Plane;
+    use tracer::ray::Ray;
+    use tracer::transforms::scaling;
+    use tracer::uv::UvMapping;
+    use tracer::world::ShapeEnum;
+    use tracer::{magnitude, normalize, point, vector, Color};
+
+    fn solid_canvas(color: Color) -> Arc<Canvas> {
+        let mut canvas = Canvas::new(4, 4);
+        for y in 0..4 {
+            for x in 0..4 {
+                write_pixel(&mut canvas, x, y, color);
+            }
+        }
+        Arc::new(canvas)
+    }
+
+    #[test]
+    fn test_default_material_keeps_normal() -> Result<(), &'static str> {
+        let object = ShapeEnum::Plane(Plane::default());
+        let n = perturb_normal(&object, point(0.3, 0., 0.7), vector(0., 1., 0.))?;
+        assert_eq!(n, vector(0., 1., 0.));
+        Ok(())
+    }
+
+    #[test]
+    fn test_bump_constant_pattern_keeps_normal() {
+        let white = Color::new(1., 1., 1.);
+        let pattern = Pattern::new(PatternType::Stripe(StripePattern::new(white, white)));
+        let bump = BumpMap::new(pattern, 1.);
+        let object = ShapeEnum::Plane(Plane::default());
+        let n = bump.perturb(&object, point(0.5, 0., 0.5), vector(0., 1., 0.));
+        assert!(magnitude(n - vector(0., 1., 0.)) < 1e-5);
+    }
+
+    #[test]
+    fn test_bump_tilts_against_gradient() {
+        // The test pattern's luminance rises linearly with x, y and z
+        let bump = BumpMap::new(Pattern::new(PatternType::Test()), 1.);
+        let object = ShapeEnum::Plane(Plane::default());
+        let n = bump.perturb(&object, point(0.5, 0., 0.5), vector(0., 1., 0.));
+        let expected = normalize(vector(-0.2126, 1., -0.0722));
+        assert!(magnitude(n - expected) < 1e-3);
+    }
+
+    #[test]
+    fn test_flat_normal_map_keeps_normal() -> Result<(), &'static str> {
+        let map = NormalMap::new(solid_canvas(Color::new(0.5, 0.5, 1.)), UvMapping::Planar);
+        let object = ShapeEnum::Plane(Plane::default());
+        let n = map.perturb(&object, point(0.25, 0., 0.25), vector(0., 1., 0.))?;
+        assert!(magnitude(n - vector(0., 1., 0.)) < 1e-5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_normal_map_follows_uv_tangent() -> Result<(), &'static str> {
+        // On a plane with planar mapping, u runs along x and v along z
+        let map = NormalMap::new(solid_canvas(Color::new(1., 0.5, 0.5)), UvMapping::Planar);
+        let object = ShapeEnum::Plane(Plane::default());
+        let n = map.perturb(&object, point(0.25, 0., 0.25), vector(0., 1., 0.))?;
+        assert!(magnitude(n - vector(1., 0., 0.)) < 1e-4);
+
+        let map = NormalMap::new(solid_canvas(Color::new(0.5, 1., 0.5)), UvMapping::Planar);
+        let n = map.perturb(&object, point(0.25, 0., 0.25), vector(0., 1., 0.))?;
+        assert!(magnitude(n - vector(0., 0., 1.)) < 1e-4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_prepare_computations_uses_bumped_normal() {
+        let mut plane = Plane::default();
+        plane.material.bump_map = Some(BumpMap::new(Pattern::new(PatternType::Test()), 1.));
+        let object = ShapeEnum::Plane(plane);
+        let r = Ray::new(point(0.5, 1., 0.5), vector(0., -1., 0.));
+        let i = Intersection::new(1., object);
+        let comps = prepare_computations(i.clone(), r, vec![i]);
+
+        let expected = normalize(vector(-0.2126, 1., -0.0722));
+        assert!(magnitude(comps.normalv - expected) < 1e-3);
+        assert!(magnitude(comps.over_point - (comps.point + vector(0., 1e-4, 0.))) < 1e-6);
+        assert!(
+            magnitude(comps.reflectv - (vector(0., -1., 0.) - expected * 2. * -expected.y)) < 1e-3
+        );
+    }
+
+    #[test]
+    fn test_normal_map_keeps_offsets_on_geometric_normal() {
+        // The map tilts the shading normal flat into the surface
+        let mut plane = Plane::default();
+        plane.material.normal_map = Some(NormalMap::new(
+            solid_canvas(Color::new(1., 0.5, 0.5)),
+            UvMapping::Planar,
+        ));
+        let object = ShapeEnum::Plane(plane);
+        let r = Ray::new(point(0.25, 1., 0.25), vector(0., -1., 0.));
+        let i = Intersection::new(1., object);
+        let comps = prepare_computations(i.clone(), r, vec![i]);
+
+        assert!(magnitude(comps.normalv - vector(1., 0., 0.)) < 1e-4);
+        assert!(!comps.inside);
+        assert!(comps.over_point.y > comps.point.y);
+        assert!(comps.under_point.y < comps.point.y);
+    }
+
+    #[test]
+    fn test_normal_map_singular_transform_is_an_error() {
+        let map = NormalMap::new(solid_canvas(Color::new(0.5, 0.5, 1.)), UvMapping::Planar);
+        let object = ShapeEnum::Plane(Plane {
+            transform: scaling(1., 0., 1.),
+            ..Plane::default()
+        });
+        assert!(map
+            .perturb(&object, point(0.25, 0., 0.25), vector(0., 1., 0.))
+            .is_err());
+    }
+}
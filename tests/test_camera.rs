@@ -1,8 +1,13 @@
 mod tests {
     use std::f32::consts::PI;
-    use tracer::camera::{ray_for_pixel, render, Camera};
-    use tracer::canvas::pixel_at;
+    use tracer::camera::{
+        ray_for_pixel, ray_for_sample, render, render_with, render_with_stats, AdaptiveSampling,
+        Aperture, Camera, Projection, RenderSettings,
+    };
+    use tracer::canvas::{alpha_at, pixel_at};
+    use tracer::filters::Filter;
     use tracer::matrix::Matrix;
+    use tracer::sampling::SamplePattern;
     use tracer::transforms::{rotation_y, translation, view_transform};
     use tracer::world::World;
     use tracer::Color;
@@ -80,4 +85,389 @@ mod tests {
         assert_eq!(pixel_at(&image, 5, 5), Color::new(0.38066, 0.47583, 0.2855));
         Ok(())
     }
+
+    #[test]
+    fn test_default_camera_is_pinhole() {
+        let c = Camera::new(160, 120, PI / 2.);
+        assert_eq!(c.aperture_radius, 0.);
+        assert_eq!(c.aperture, Aperture::Disk);
+        assert_eq!(c.lens_samples, 1);
+    }
+
+    #[test]
+    fn test_zero_aperture_ignores_lens_sample() -> Result<(), String> {
+        let c = Camera::new(201, 101, PI / 2.);
+        let pinhole = ray_for_pixel(c, 20, 30)?;
+        let r = ray_for_sample(c, 20.5, 30.5, (0.9, 0.1))?;
+        assert_eq!(r.origin, pinhole.origin);
+        assert_eq!(r.direction, pinhole.direction);
+        Ok(())
+    }
+
+    #[test]
+    fn test_lens_rays_converge_on_focal_plane() -> Result<(), String> {
+        let mut c = Camera::new(201, 101, PI / 2.);
+        c.aperture_radius = 0.5;
+        c.focal_distance = 4.;
+        let center = ray_for_sample(c, 60.5, 20.5, (0.5, 0.5))?;
+        let focus = center.origin + center.direction * (4. / -center.direction.z);
+
+        for lens in [(0., 0.), (1., 0.3), (0.2, 0.9), (0.75, 0.75)] {
+            let r = ray_for_sample(c, 60.5, 20.5, lens)?;
+            assert!(r.origin.z.abs() < 1e-6);
+            assert!(magnitude(r.origin - point(0., 0., 0.)) <= 0.5 + 1e-5);
+            let t = 4. / -r.direction.z;
+            let hit = r.origin + r.direction * t;
+            assert!(magnitude(hit - focus) < 1e-4);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_polygon_aperture_stays_inside_blades() {
+        let mut c = Camera::new(10, 10, PI / 2.);
+        c.aperture_radius = 1.;
+        c.aperture = Aperture::Polygon(6);
+        let apothem = f32::cos(PI / 6.);
+        for i in 0..20 {
+            for j in 0..20 {
+                let (x, y) = c.lens_point(i as f32 / 20., j as f32 / 20.);
+                let angle = f32::atan2(y, x).rem_euclid(PI / 3.) - PI / 6.;
+                let edge = apothem / f32::cos(angle);
+                assert!(f32::sqrt(x * x + y * y) <= edge + 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn test_render_depth_of_field_in_focus() -> Result<(), String> {
+        let w = World::default();
+        let mut c = Camera::new(11, 11, PI / 2.);
+        c.transform = view_transform(point(0., 0., -5.), point(0., 0., 0.), vector(0., 1., 0.));
+        c.aperture_radius = 0.05;
+        c.focal_distance = 4.;
+        c.lens_samples = 16;
+        let image = render(c, w)?;
+        let center = pixel_at(&image, 5, 5);
+        let pinhole = Color::new(0.38066, 0.47583, 0.2855);
+        assert!((center.red - pinhole.red).abs() < 0.05);
+        assert!((center.green - pinhole.green).abs() < 0.05);
+        assert!((center.blue - pinhole.blue).abs() < 0.05);
+        Ok(())
+    }
+
+    #[test]
+    fn test_default_projection_is_perspective() {
+        let c = Camera::new(160, 120, PI / 2.);
+        assert_eq!(c.projection, Projection::Perspective);
+    }
+
+    #[test]
+    fn test_orthographic_rays_are_parallel() -> Result<(), String> {
+        let c = Camera::orthographic(200, 100, 4.);
+        assert_eq!(c.pixel_size, 0.02);
+
+        let center = ray_for_sample(c, 100., 50., (0.5, 0.5))?;
+        assert!(magnitude(center.origin - point(0., 0., 0.)) < 1e-5);
+        assert_eq!(center.direction, vector(0., 0., -1.));
+
+        let corner = ray_for_pixel(c, 0, 0)?;
+        assert!(magnitude(corner.origin - point(1.99, 0.99, 0.)) < 1e-5);
+        assert_eq!(corner.direction, vector(0., 0., -1.));
+        Ok(())
+    }
+
+    #[test]
+    fn test_orthographic_camera_transform() -> Result<(), String> {
+        let mut c = Camera::orthographic(100, 100, 2.);
+        c.transform = view_transform(point(0., 5., 0.), point(0., 0., 0.), vector(0., 0., 1.));
+        let r = ray_for_sample(c, 50., 50., (0.5, 0.5))?;
+        assert!(magnitude(r.origin - point(0., 5., 0.)) < 1e-5);
+        assert!(magnitude(r.direction - vector(0., -1., 0.)) < 1e-5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_orthographic_extent_follows_projection() -> Result<(), String> {
+        let mut c = Camera::new(200, 100, PI / 2.);
+        c.projection = Projection::Orthographic { view_width: 8. };
+        let corner = ray_for_pixel(c, 0, 0)?;
+        assert!(magnitude(corner.origin - point(3.98, 1.98, 0.)) < 1e-5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_depth_of_field_needs_perspective() {
+        for mut c in [
+            Camera::orthographic(20, 10, 4.),
+            Camera::fisheye(20, 10, PI),
+            Camera::equirectangular(20, 10),
+        ] {
+            c.aperture_radius = 0.1;
+            assert!(ray_for_sample(c, 10., 5., (0.5, 0.5)).is_err());
+            assert!(render(c, World::default()).is_err());
+        }
+    }
+
+    #[test]
+    fn test_fisheye_angles() -> Result<(), String> {
+        let c = Camera::fisheye(200, 100, PI);
+        let center = ray_for_sample(c, 100., 50., (0.5, 0.5))?;
+        assert!(magnitude(center.direction - vector(0., 0., -1.)) < 1e-5);
+
+        // The edge of the image circle is 90 degrees off axis for a 180 degree lens
+        let top = ray_for_sample(c, 100., 0., (0.5, 0.5))?;
+        assert!(magnitude(top.direction - vector(0., 1., 0.)) < 1e-5);
+        let left = ray_for_sample(c, 75., 50., (0.5, 0.5))?;
+        let expected = vector(f32::sin(PI / 4.), 0., -f32::cos(PI / 4.));
+        assert!(magnitude(left.direction - expected) < 1e-5);
+
+        assert!(ray_for_pixel(c, 0, 0).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_equirectangular_directions() -> Result<(), String> {
+        let c = Camera::equirectangular(360, 180);
+        let forward = ray_for_sample(c, 180., 90., (0.5, 0.5))?;
+        assert!(magnitude(forward.direction - vector(0., 0., -1.)) < 1e-5);
+
+        let left = ray_for_sample(c, 90., 90., (0.5, 0.5))?;
+        assert!(magnitude(left.direction - vector(1., 0., 0.)) < 1e-5);
+
+        let behind = ray_for_sample(c, 0., 90., (0.5, 0.5))?;
+        assert!(magnitude(behind.direction - vector(0., 0., 1.)) < 1e-5);
+
+        let up = ray_for_sample(c, 180., 0., (0.5, 0.5))?;
+        assert!(magnitude(up.direction - vector(0., 1., 0.)) < 1e-5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_fisheye_outside_circle_is_black() -> Result<(), String> {
+        let w = World::default();
+        let mut c = Camera::fisheye(21, 11, PI / 2.);
+        c.transform = view_transform(point(0., 0., -5.), point(0., 0., 0.), vector(0., 1., 0.));
+        let image = render(c, w)?;
+        assert_eq!(pixel_at(&image, 0, 0), Color::new(0., 0., 0.));
+        assert_eq!(
+            pixel_at(&image, 10, 5),
+            Color::new(0.38066, 0.47583, 0.2855)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_default_render_settings() {
+        let settings = RenderSettings::default();
+        assert_eq!(settings.samples_per_pixel, 1);
+        assert_eq!(settings.sample_pattern, SamplePattern::Grid);
+        assert_eq!(settings.filter, Filter::Box);
+        assert_eq!(settings.max_depth, 5);
+    }
+
+    #[test]
+    fn test_render_with_default_settings_matches_render() -> Result<(), String> {
+        let w = World::default();
+        let mut c = Camera::new(11, 11, PI / 2.);
+        c.transform = view_transform(point(0., 0., -5.), point(0., 0., 0.), vector(0., 1., 0.));
+        let a = render(c, w.clone())?;
+        let b = render_with(c, &w, &RenderSettings::default())?;
+        assert_eq!(a.pixels, b.pixels);
+        Ok(())
+    }
+
+    #[test]
+    fn test_supersampling_softens_edges() -> Result<(), String> {
+        let w = World::default();
+        let mut c = Camera::new(11, 11, PI / 2.);
+        c.transform = view_transform(point(0., 0., -5.), point(0., 0., 0.), vector(0., 1., 0.));
+        let hard = render(c, w.clone())?;
+
+        for (pattern, filter) in [
+            (SamplePattern::Grid, Filter::Box),
+            (SamplePattern::Jittered, Filter::Tent),
+            (SamplePattern::Random, Filter::Gaussian),
+            (SamplePattern::Jittered, Filter::Mitchell),
+        ] {
+            let settings = RenderSettings {
+                samples_per_pixel: 16,
+                sample_pattern: pattern,
+                filter,
+                ..RenderSettings::default()
+            };
+            let soft = render_with(c, &w, &settings)?;
+
+            // Flat regions stay put while the silhouette gains intermediate values
+            let center = pixel_at(&soft, 5, 5);
+            assert!((center.green - pixel_at(&hard, 5, 5).green).abs() < 0.1);
+            let differs = (0..11).any(|x| {
+                (0..11).any(|y| {
+                    let diff = pixel_at(&soft, x, y) - pixel_at(&hard, x, y);
+                    diff.green.abs() > 0.01
+                })
+            });
+            assert!(differs);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_stats_count_primary_rays() -> Result<(), String> {
+        let w = World::default();
+        let c = Camera::new(8, 6, PI / 2.);
+        let (_, stats) = render_with_stats(c, &w, &RenderSettings::default())?;
+        assert_eq!(stats.rays, 48);
+
+        let settings = RenderSettings {
+            samples_per_pixel: 4,
+            ..RenderSettings::default()
+        };
+        let (_, stats) = render_with_stats(c, &w, &settings)?;
+        assert_eq!(stats.rays, 192);
+        Ok(())
+    }
+
+    #[test]
+    fn test_adaptive_flat_region_uses_corner_rays_only() -> Result<(), String> {
+        // Looking away from every object, all corners agree
+        let w = World::default();
+        let mut c = Camera::new(8, 6, PI / 4.);
+        c.transform = view_transform(point(0., 0., -5.), point(0., 0., -10.), vector(0., 1., 0.));
+        let settings = RenderSettings {
+            adaptive: Some(AdaptiveSampling::default()),
+            ..RenderSettings::default()
+        };
+        let (image, stats) = render_with_stats(c, &w, &settings)?;
+        assert_eq!(stats.rays, 9 * 7);
+        assert_eq!(pixel_at(&image, 3, 3), Color::new(0., 0., 0.));
+        Ok(())
+    }
+
+    #[test]
+    fn test_adaptive_subdivides_up_to_max_depth() -> Result<(), String> {
+        let w = World::default();
+        let mut c = Camera::new(4, 4, PI / 2.);
+        c.transform = view_transform(point(0., 0., -5.), point(0., 0., 0.), vector(0., 1., 0.));
+        let settings = RenderSettings {
+            adaptive: Some(AdaptiveSampling {
+                threshold: -1.,
+                max_depth: 1,
+            }),
+            ..RenderSettings::default()
+        };
+        let (_, stats) = render_with_stats(c, &w, &settings)?;
+        assert_eq!(stats.rays, 5 * 5 + 16 * 5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_adaptive_traces_shared_edges_once() -> Result<(), String> {
+        let w = World::default();
+        let mut c = Camera::new(4, 4, PI / 2.);
+        c.transform = view_transform(point(0., 0., -5.), point(0., 0., 0.), vector(0., 1., 0.));
+        let settings = RenderSettings {
+            adaptive: Some(AdaptiveSampling {
+                threshold: -1.,
+                max_depth: 2,
+            }),
+            ..RenderSettings::default()
+        };
+        // Each pixel fills its 5x5 grid, four corners of which are shared
+        let (_, stats) = render_with_stats(c, &w, &settings)?;
+        assert_eq!(stats.rays, 5 * 5 + 16 * (5 * 5 - 4));
+        Ok(())
+    }
+
+    #[test]
+    fn test_adaptive_spends_rays_on_edges() -> Result<(), String> {
+        let w = World::default();
+        let mut c = Camera::new(21, 21, PI / 2.);
+        c.transform = view_transform(point(0., 0., -5.), point(0., 0., 0.), vector(0., 1., 0.));
+        let loose = RenderSettings {
+            adaptive: Some(AdaptiveSampling {
+                threshold: 0.5,
+                max_depth: 3,
+            }),
+            ..RenderSettings::default()
+        };
+        let tight = RenderSettings {
+            adaptive: Some(AdaptiveSampling {
+                threshold: 0.01,
+                max_depth: 3,
+            }),
+            ..RenderSettings::default()
+        };
+        let (_, loose_stats) = render_with_stats(c, &w, &loose)?;
+        let (image, tight_stats) = render_with_stats(c, &w, &tight)?;
+        assert!(loose_stats.rays > 22 * 22);
+        assert!(tight_stats.rays > loose_stats.rays);
+        assert!(tight_stats.rays < 22 * 22 + 21 * 21 * 5 * 21);
+
+        let center = pixel_at(&image, 10, 10);
+        assert!((center.green - 0.47583).abs() < 0.05);
+        Ok(())
+    }
+
+    fn alphas(image: &tracer::canvas::Canvas) -> Vec<f32> {
+        image.alpha.iter().flatten().copied().collect()
+    }
+
+    #[test]
+    fn test_opaque_render_is_fully_covered() -> Result<(), String> {
+        let w = World::default();
+        let mut c = Camera::new(11, 11, PI / 2.);
+        c.transform = view_transform(point(0., 0., -5.), point(0., 0., 0.), vector(0., 1., 0.));
+        let image = render(c, w)?;
+        assert!(alphas(&image).iter().all(|&a| a == 1.));
+        Ok(())
+    }
+
+    #[test]
+    fn test_transparent_background_coverage() -> Result<(), String> {
+        let w = World::default();
+        let mut c = Camera::new(11, 11, PI / 2.);
+        c.transform = view_transform(point(0., 0., -5.), point(0., 0., 0.), vector(0., 1., 0.));
+        let opaque = render_with(c, &w, &RenderSettings::default())?;
+        let settings = RenderSettings {
+            transparent_background: true,
+            ..RenderSettings::default()
+        };
+        let image = render_with(c, &w, &settings)?;
+        assert_eq!(alpha_at(&image, 5, 5), 1.);
+        assert_eq!(pixel_at(&image, 5, 5), pixel_at(&opaque, 5, 5));
+        assert_eq!(alpha_at(&image, 0, 0), 0.);
+        assert_eq!(pixel_at(&image, 0, 0), Color::new(0., 0., 0.));
+        // One ray per pixel gives hard edges
+        assert!(alphas(&image).iter().all(|&a| a == 0. || a == 1.));
+        Ok(())
+    }
+
+    #[test]
+    fn test_antialiased_coverage_is_fractional() -> Result<(), String> {
+        let w = World::default();
+        let mut c = Camera::new(11, 11, PI / 2.);
+        c.transform = view_transform(point(0., 0., -5.), point(0., 0., 0.), vector(0., 1., 0.));
+        for settings in [
+            RenderSettings {
+                samples_per_pixel: 16,
+                transparent_background: true,
+                ..RenderSettings::default()
+            },
+            RenderSettings {
+                adaptive: Some(AdaptiveSampling {
+                    threshold: 0.05,
+                    max_depth: 2,
+                }),
+                transparent_background: true,
+                ..RenderSettings::default()
+            },
+        ] {
+            let image = render_with(c, &w, &settings)?;
+            assert!(alphas(&image).iter().any(|&a| a > 0.1 && a < 0.9));
+            assert_eq!(alpha_at(&image, 0, 0), 0.);
+            assert_eq!(alpha_at(&image, 5, 5), 1.);
+        }
+        Ok(())
+    }
 }
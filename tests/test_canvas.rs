@@ -1,5 +1,9 @@
 mod tests {
-    use tracer::canvas::{canvas_to_ppm, pixel_at, write_pixel, Canvas};
+    use tracer::canvas::{
+        alpha_at, alpha_over, blit, canvas_from_pfm, canvas_from_ppm, canvas_to_pfm, canvas_to_ppm,
+        crop, flip_horizontal, flip_vertical, pixel_at, resize, write_alpha, write_pixel, Canvas,
+        ResizeFilter,
+    };
     use tracer::Color;
 
     #[test]
@@ -78,4 +82,205 @@ mod tests {
         let ppm = canvas_to_ppm(&c);
         assert_eq!(ppm[3..], expected)
     }
+
+    #[test]
+    fn test_canvas_from_ppm() -> Result<(), &'static str> {
+        let ppm = "P3\n# a comment\n4 3\n255\n\
+            255 127 0  0 127 255  127 255 0  255 255 255\n\
+            0 0 0  255 0 0  0 255 0  0 0 255\n\
+            255 255 0  0 255 255  255 0 255  127 127 127\n";
+        let c = canvas_from_ppm(ppm)?;
+        assert_eq!(c.width, 4);
+        assert_eq!(c.height, 3);
+        assert_eq!(pixel_at(&c, 0, 0), Color::new(1., 127. / 255., 0.));
+        assert_eq!(
+            pixel_at(&c, 3, 2),
+            Color::new(127. / 255., 127. / 255., 127. / 255.)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_canvas_from_ppm_rejects_other_formats() {
+        assert!(canvas_from_ppm("P32\n1 1\n255\n0 0 0\n").is_err());
+        assert!(canvas_from_ppm("P3\n2 1\n255\n0 0 0\n").is_err());
+    }
+
+    #[test]
+    fn test_ppm_round_trip() -> Result<(), &'static str> {
+        let mut c = Canvas::new(3, 2);
+        write_pixel(&mut c, 1, 1, Color::new(1., 0.2, 0.6));
+        let ppm = canvas_to_ppm(&c).join("\n");
+        let read = canvas_from_ppm(&ppm)?;
+        assert_eq!(pixel_at(&read, 0, 0), Color::new(0., 0., 0.));
+        assert_eq!(pixel_at(&read, 1, 1), Color::new(1., 0.2, 0.6));
+        Ok(())
+    }
+
+    #[test]
+    fn test_canvas_to_pfm_header_and_row_order() {
+        let mut c = Canvas::new(2, 2);
+        write_pixel(&mut c, 0, 1, Color::new(1.5, -2., 0.25));
+        let pfm = canvas_to_pfm(&c);
+        assert!(pfm.starts_with(b"PF\n2 2\n-1.0\n"));
+        let header = b"PF\n2 2\n-1.0\n".len();
+        assert_eq!(pfm.len(), header + 2 * 2 * 12);
+        // The bottom row comes first
+        assert_eq!(pfm[header..header + 4], 1.5f32.to_le_bytes());
+    }
+
+    #[test]
+    fn test_pfm_round_trip() -> Result<(), &'static str> {
+        let mut c = Canvas::new(3, 2);
+        write_pixel(&mut c, 0, 0, Color::new(12.5, 0.001, 3.));
+        write_pixel(&mut c, 2, 1, Color::new(1., 0.2, 0.6));
+        let read = canvas_from_pfm(&canvas_to_pfm(&c))?;
+        assert_eq!((read.width, read.height), (3, 2));
+        assert_eq!(read.pixels, c.pixels);
+        Ok(())
+    }
+
+    #[test]
+    fn test_canvas_from_pfm_rejects_truncated_data() {
+        let pfm = canvas_to_pfm(&Canvas::new(2, 2));
+        assert!(canvas_from_pfm(&pfm[..pfm.len() - 1]).is_err());
+        assert!(canvas_from_pfm(b"Pf\n1 1\n-1.0\n\0\0\0\0").is_err());
+    }
+
+    fn numbered(width: usize, height: usize) -> Canvas {
+        let mut c = Canvas::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                write_pixel(&mut c, x, y, Color::new(x as f32, y as f32, 0.));
+            }
+        }
+        c
+    }
+
+    #[test]
+    fn test_new_canvas_is_opaque() {
+        let c = Canvas::new(3, 2);
+        assert_eq!(alpha_at(&c, 2, 1), 1.);
+    }
+
+    #[test]
+    fn test_crop() -> Result<(), &'static str> {
+        let c = numbered(5, 4);
+        let cropped = crop(&c, 1, 2, 3, 2)?;
+        assert_eq!((cropped.width, cropped.height), (3, 2));
+        assert_eq!(pixel_at(&cropped, 0, 0), Color::new(1., 2., 0.));
+        assert_eq!(pixel_at(&cropped, 2, 1), Color::new(3., 3., 0.));
+        assert!(crop(&c, 3, 0, 3, 1).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_flips() {
+        let mut c = numbered(3, 2);
+        write_alpha(&mut c, 0, 0, 0.25);
+        let h = flip_horizontal(&c);
+        assert_eq!(pixel_at(&h, 0, 0), Color::new(2., 0., 0.));
+        assert_eq!(alpha_at(&h, 2, 0), 0.25);
+        let v = flip_vertical(&c);
+        assert_eq!(pixel_at(&v, 0, 0), Color::new(0., 1., 0.));
+        assert_eq!(alpha_at(&v, 0, 1), 0.25);
+    }
+
+    #[test]
+    fn test_blit_clips_to_target() {
+        let mut target = Canvas::new(4, 4);
+        let mut source = numbered(3, 3);
+        write_alpha(&mut source, 2, 2, 0.5);
+        blit(&mut target, &source, 2, -1);
+        assert_eq!(pixel_at(&target, 2, 0), Color::new(0., 1., 0.));
+        assert_eq!(pixel_at(&target, 3, 1), Color::new(1., 2., 0.));
+        assert_eq!(alpha_at(&target, 3, 1), 1.);
+        assert_eq!(pixel_at(&target, 1, 0), Color::new(0., 0., 0.));
+        // Entirely outside
+        blit(&mut target, &source, 10, 10);
+        blit(&mut target, &source, -3, 0);
+    }
+
+    #[test]
+    fn test_alpha_over() {
+        let mut target = Canvas::new(2, 1);
+        write_pixel(&mut target, 0, 0, Color::new(0., 0., 1.));
+        // The second pixel is left empty
+        write_alpha(&mut target, 1, 0, 0.);
+
+        // Half-covered red, premultiplied
+        let mut source = Canvas::new(2, 1);
+        for x in 0..2 {
+            write_pixel(&mut source, x, 0, Color::new(0.5, 0., 0.));
+            write_alpha(&mut source, x, 0, 0.5);
+        }
+        alpha_over(&mut target, &source, 0, 0);
+        assert_eq!(pixel_at(&target, 0, 0), Color::new(0.5, 0., 0.5));
+        assert_eq!(alpha_at(&target, 0, 0), 1.);
+        assert_eq!(pixel_at(&target, 1, 0), Color::new(0.5, 0., 0.));
+        assert_eq!(alpha_at(&target, 1, 0), 0.5);
+    }
+
+    #[test]
+    fn test_resize_nearest_repeats_pixels() -> Result<(), &'static str> {
+        let c = numbered(2, 2);
+        let big = resize(&c, 4, 4, ResizeFilter::Nearest)?;
+        assert_eq!(pixel_at(&big, 1, 1), Color::new(0., 0., 0.));
+        assert_eq!(pixel_at(&big, 2, 1), Color::new(1., 0., 0.));
+        assert_eq!(pixel_at(&big, 3, 3), Color::new(1., 1., 0.));
+        Ok(())
+    }
+
+    #[test]
+    fn test_resize_keeps_flat_images_flat() -> Result<(), &'static str> {
+        let mut c = Canvas::new(7, 5);
+        for y in 0..5 {
+            for x in 0..7 {
+                write_pixel(&mut c, x, y, Color::new(0.3, 0.6, 0.9));
+            }
+        }
+        for filter in [ResizeFilter::Bilinear, ResizeFilter::Lanczos] {
+            for (w, h) in [(13, 9), (3, 2)] {
+                let r = resize(&c, w, h, filter)?;
+                for y in 0..h {
+                    for x in 0..w {
+                        assert_eq!(pixel_at(&r, x, y), Color::new(0.3, 0.6, 0.9));
+                        assert!((alpha_at(&r, x, y) - 1.).abs() < 1e-5);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_resize_down_averages() -> Result<(), &'static str> {
+        // A one-pixel checkerboard shrinks to flat grey rather than aliasing
+        let mut c = Canvas::new(8, 8);
+        for y in 0..8 {
+            for x in 0..8 {
+                if (x + y) % 2 == 0 {
+                    write_pixel(&mut c, x, y, Color::new(1., 1., 1.));
+                }
+            }
+        }
+        for filter in [ResizeFilter::Bilinear, ResizeFilter::Lanczos] {
+            let small = resize(&c, 2, 2, filter)?;
+            let p = pixel_at(&small, 1, 0);
+            assert!((p.red - 0.5).abs() < 0.05, "{:?}", p);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_bilinear_interpolates_between_pixels() -> Result<(), &'static str> {
+        let c = numbered(2, 1);
+        let r = resize(&c, 4, 1, ResizeFilter::Bilinear)?;
+        assert_eq!(pixel_at(&r, 0, 0), Color::new(0., 0., 0.));
+        assert_eq!(pixel_at(&r, 1, 0), Color::new(0.25, 0., 0.));
+        assert_eq!(pixel_at(&r, 2, 0), Color::new(0.75, 0., 0.));
+        assert_eq!(pixel_at(&r, 3, 0), Color::new(1., 0., 0.));
+        assert!(resize(&c, 0, 1, ResizeFilter::Bilinear).is_err());
+        Ok(())
+    }
 }
@@ -0,0 +1,142 @@
+mod tests {
+    use tracer::background::Background;
+    use tracer::camera::{render_with, Camera, RenderSettings};
+    use tracer::canvas::{alpha_at, pixel_at, Canvas};
+    use tracer::compositing::{has_compositing_objects, shadow_density};
+    use tracer::intersections::{prepare_computations, Intersection};
+    use tracer::lights::PointLight;
+    use tracer::materials::Material;
+    use tracer::plane::Plane;
+    use tracer::ray::Ray;
+    use tracer::sphere::Sphere;
+    use tracer::transforms::{translation, view_transform};
+    use tracer::world::{color_at, ShapeEnum, World};
+    use tracer::{normalize, point, vector, Color, Tuple};
+
+    // A sphere resting on a shadow-catching floor, lit from the upper left so
+    // its shadow falls around (1, 0, 0)
+    fn scene() -> World {
+        let mut w = World::new();
+        w.light = Some(PointLight::new(
+            point(-10., 10., 0.),
+            Color::new(1., 1., 1.),
+        ));
+        w.background = Background::Color(Color::new(0.2, 0.4, 0.6));
+        let floor = Plane {
+            material: Material {
+                shadow_catcher: true,
+                ..Material::default()
+            },
+            ..Plane::default()
+        };
+        let sphere = Sphere {
+            transform: translation(0., 1., 0.),
+            ..Sphere::default()
+        };
+        w.objects = vec![ShapeEnum::Plane(floor), ShapeEnum::Sphere(sphere)];
+        w
+    }
+
+    // A single pixel looking straight at `target`
+    fn look_at(target: Tuple) -> Camera {
+        let mut c = Camera::new(1, 1, 0.01);
+        c.transform = view_transform(point(0., 6., -6.), target, vector(0., 1., 0.));
+        c
+    }
+
+    fn render_pixel(world: &World, target: Tuple, transparent: bool) -> Result<Canvas, String> {
+        let settings = RenderSettings {
+            transparent_background: transparent,
+            ..RenderSettings::default()
+        };
+        Ok(render_with(look_at(target), world, &settings)?)
+    }
+
+    fn floor_density(world: &World, x: f32, z: f32) -> f32 {
+        let origin = point(x, 1., z);
+        let ray = Ray::new(origin, vector(0., -1., 0.));
+        let i = Intersection::new(1., world.objects[0].clone());
+        let comps = prepare_computations(i.clone(), ray, vec![i]);
+        shadow_density(world, &comps)
+    }
+
+    #[test]
+    fn test_shadow_density() {
+        let w = scene();
+        assert!(floor_density(&w, 1., 0.) > 0.99);
+        assert!(floor_density(&w, -3., 3.) < 0.01);
+        assert!(has_compositing_objects(&w));
+        assert!(!has_compositing_objects(&World::default()));
+    }
+
+    #[test]
+    fn test_shadow_catcher_alpha_is_shadow_density() -> Result<(), String> {
+        let w = scene();
+        let lit = render_pixel(&w, point(-3., 0., 3.), true)?;
+        assert!(alpha_at(&lit, 0, 0) < 0.01);
+        assert_eq!(pixel_at(&lit, 0, 0), Color::new(0., 0., 0.));
+
+        let shadow = render_pixel(&w, point(1.2, 0., 0.), true)?;
+        assert!(alpha_at(&shadow, 0, 0) > 0.99);
+        assert_eq!(pixel_at(&shadow, 0, 0), Color::new(0., 0., 0.));
+
+        let sphere = render_pixel(&w, point(0., 1., 0.), true)?;
+        assert_eq!(alpha_at(&sphere, 0, 0), 1.);
+        assert!(pixel_at(&sphere, 0, 0).luminance() > 0.);
+        Ok(())
+    }
+
+    #[test]
+    fn test_shadow_catcher_over_background() -> Result<(), String> {
+        let w = scene();
+        let background = Color::new(0.2, 0.4, 0.6);
+        let lit = render_pixel(&w, point(-3., 0., 3.), false)?;
+        assert_eq!(alpha_at(&lit, 0, 0), 1.);
+        assert_eq!(pixel_at(&lit, 0, 0), background);
+        let shadow = render_pixel(&w, point(1.2, 0., 0.), false)?;
+        assert_eq!(pixel_at(&shadow, 0, 0), Color::new(0., 0., 0.));
+
+        // Secondary rays see the same thing
+        let ray = Ray::new(
+            point(0., 6., -6.),
+            normalize(point(-3., 0., 3.) - point(0., 6., -6.)),
+        );
+        assert_eq!(color_at(&w, ray, 5), background);
+        Ok(())
+    }
+
+    #[test]
+    fn test_reflective_shadow_catcher_adds_reflections() -> Result<(), String> {
+        let mut w = scene();
+        if let ShapeEnum::Plane(ref mut floor) = w.objects[0] {
+            floor.material.reflective = 0.5;
+        }
+        // Away from the sphere the floor only mirrors the cut-out background,
+        // which adds nothing
+        let far = render_pixel(&w, point(-3., 0., 3.), true)?;
+        assert!(alpha_at(&far, 0, 0) < 0.01);
+        assert_eq!(pixel_at(&far, 0, 0), Color::new(0., 0., 0.));
+        // Just in front of the sphere it mirrors the sphere instead
+        let near = render_pixel(&w, point(0., 0., -1.2), true)?;
+        assert_ne!(pixel_at(&near, 0, 0), Color::new(0.1, 0.2, 0.3));
+        assert!(pixel_at(&near, 0, 0).luminance() > 0.);
+        Ok(())
+    }
+
+    #[test]
+    fn test_holdout_cuts_a_hole() -> Result<(), String> {
+        let mut w = scene();
+        if let ShapeEnum::Sphere(ref mut sphere) = w.objects[1] {
+            sphere.material.holdout = true;
+        }
+        for transparent in [true, false] {
+            let image = render_pixel(&w, point(0., 1., 0.), transparent)?;
+            assert_eq!(alpha_at(&image, 0, 0), 0.);
+            assert_eq!(pixel_at(&image, 0, 0), Color::new(0., 0., 0.));
+        }
+        // The holdout still casts its shadow onto the catcher
+        let shadow = render_pixel(&w, point(1.2, 0., 0.), true)?;
+        assert!(alpha_at(&shadow, 0, 0) > 0.99);
+        Ok(())
+    }
+}
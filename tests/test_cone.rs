@@ -52,18 +52,18 @@ mod tests {
         shape.maximum = 0.5;
         shape.closed = true;
         
-        let origins = vec![
+        let origins = [
             point(0., 0., -5.),
             point(0., 0., -0.25),
             point(0., 0., -0.25),
         ];
 
-        let directions = vec![
+        let directions = [
             vector(0., 1., 0.),
             vector(0., 1., 1.),
             vector(0., 1., 0.),
         ];
-        let counts = vec![0, 2, 4];
+        let counts = [0, 2, 4];
 
         for idx in 0..origins.len() {
             let direction = normalize(directions[idx]);
@@ -77,12 +77,12 @@ mod tests {
     fn test_cone_normal() {
         let shape = Cone::default();
 
-        let points = vec![
+        let points = [
             point(0., 0., 0.),
             point(1., 1., 1.),
             point(-1., -1., 0.),
         ];
-        let normals = vec![
+        let normals = [
             vector(0., 0., 0.),
             vector(1., -f32::sqrt(2.), 1.),
             vector(-1., 1., 0.),
@@ -0,0 +1,145 @@
+mod tests {
+    use std::f32::consts::PI;
+
+    use rand::{rngs::SmallRng, SeedableRng};
+    use tracer::camera::{render_with, render_with_stats, Camera, RenderSettings};
+    use tracer::debug::{false_color, heat_color, DebugView, RenderMode};
+    use tracer::integrators::Integrator;
+    use tracer::plane::Plane;
+    use tracer::ray::Ray;
+    use tracer::sphere::Sphere;
+    use tracer::transforms::{translation, view_transform};
+    use tracer::world::{color_at, ShapeEnum, World};
+    use tracer::{point, vector, Color};
+
+    fn trace(mode: RenderMode, world: &World, ray: Ray) -> Color {
+        let mut rng = SmallRng::seed_from_u64(0);
+        DebugView::new(mode, 5).li(world, ray, &mut rng)
+    }
+
+    #[test]
+    fn test_shaded_mode_is_the_default() {
+        assert_eq!(RenderSettings::default().mode, RenderMode::Shaded);
+        let w = World::default();
+        let r = Ray::new(point(0., 0., -5.), vector(0., 0., 1.));
+        assert_eq!(trace(RenderMode::Shaded, &w, r), color_at(&w, r, 5));
+    }
+
+    #[test]
+    fn test_normals_view() {
+        let w = World::default();
+        let r = Ray::new(point(0., 0., -5.), vector(0., 0., 1.));
+        assert_eq!(trace(RenderMode::Normals, &w, r), Color::new(0.5, 0.5, 0.));
+
+        let miss = Ray::new(point(0., 0., -5.), vector(0., 1., 0.));
+        assert_eq!(trace(RenderMode::Normals, &w, miss), Color::new(0., 0., 0.));
+    }
+
+    #[test]
+    fn test_depth_view_normalises_over_range() {
+        let w = World::default();
+        let r = Ray::new(point(0., 0., -5.), vector(0., 0., 1.));
+        // The outer sphere is hit at t = 4
+        let mode = RenderMode::Depth { near: 2., far: 6. };
+        assert_eq!(trace(mode, &w, r), Color::new(0.5, 0.5, 0.5));
+
+        let near = RenderMode::Depth { near: 5., far: 6. };
+        assert_eq!(trace(near, &w, r), Color::new(1., 1., 1.));
+        let far = RenderMode::Depth { near: 0., far: 3. };
+        assert_eq!(trace(far, &w, r), Color::new(0., 0., 0.));
+    }
+
+    #[test]
+    fn test_object_index_view() {
+        let mut w = World::new();
+        w.objects = vec![
+            ShapeEnum::Plane(Plane {
+                transform: translation(0., -1., 0.),
+                ..Plane::default()
+            }),
+            ShapeEnum::Sphere(Sphere::default()),
+        ];
+        let sphere = Ray::new(point(0., 0., -5.), vector(0., 0., 1.));
+        let floor = Ray::new(point(0., 0., -5.), vector(0., -1., 0.));
+        assert_eq!(trace(RenderMode::ObjectIndex, &w, sphere), false_color(1));
+        assert_eq!(trace(RenderMode::ObjectIndex, &w, floor), false_color(0));
+        assert_ne!(false_color(0), false_color(1));
+    }
+
+    #[test]
+    fn test_false_colors_are_distinct() {
+        for a in 0..8 {
+            for b in a + 1..8 {
+                assert_ne!(false_color(a), false_color(b));
+            }
+        }
+    }
+
+    #[test]
+    fn test_uv_view() {
+        let mut w = World::new();
+        w.objects = vec![ShapeEnum::Plane(Plane::default())];
+        let r = Ray::new(point(0.25, 1., 0.75), vector(0., -1., 0.));
+        assert_eq!(trace(RenderMode::Uv, &w, r), Color::new(0.25, 0.75, 0.));
+    }
+
+    #[test]
+    fn test_heat_color_ramp() {
+        assert_eq!(heat_color(0.), Color::new(0., 0., 1.));
+        assert_eq!(heat_color(0.5), Color::new(0.5, 1., 0.));
+        assert_eq!(heat_color(1.), Color::new(1., 0., 0.));
+        assert_eq!(heat_color(3.), Color::new(1., 0., 0.));
+    }
+
+    #[test]
+    fn test_intersection_tests_are_counted() {
+        let w = World::default();
+        let r = Ray::new(point(0., 0., -5.), vector(0., 0., 1.));
+
+        // One primary ray and one shadow ray, each against both spheres
+        let mode = RenderMode::IntersectionHeatmap { max_tests: 4 };
+        assert_eq!(trace(mode, &w, r), Color::new(1., 0., 0.));
+        let miss = Ray::new(point(0., 0., -5.), vector(0., 1., 0.));
+        assert_eq!(trace(mode, &w, miss), heat_color(0.5));
+    }
+
+    #[test]
+    fn test_heatmap_counts_reflected_and_refracted_rays() {
+        let mut w = World::new();
+        w.light = World::default().light;
+        let mut floor = Plane::default();
+        w.objects = vec![ShapeEnum::Plane(floor.clone())];
+        let r = Ray::new(point(0., 1., 0.), vector(0., -1., 0.));
+        let mode = RenderMode::IntersectionHeatmap { max_tests: 4 };
+        // Camera and shadow rays
+        assert_eq!(trace(mode, &w, r), heat_color(0.5));
+
+        // Plus a reflection that escapes
+        floor.material.reflective = 1.;
+        w.objects = vec![ShapeEnum::Plane(floor.clone())];
+        assert_eq!(trace(mode, &w, r), heat_color(0.75));
+
+        // Plus a refraction that escapes
+        floor.material.transparency = 1.;
+        w.objects = vec![ShapeEnum::Plane(floor)];
+        assert_eq!(trace(mode, &w, r), heat_color(1.));
+    }
+
+    #[test]
+    fn test_render_settings_select_mode() -> Result<(), String> {
+        let w = World::default();
+        let mut c = Camera::new(11, 11, PI / 2.);
+        c.transform = view_transform(point(0., 0., -5.), point(0., 0., 0.), vector(0., 1., 0.));
+        let settings = RenderSettings {
+            mode: RenderMode::Normals,
+            ..RenderSettings::default()
+        };
+        let image = render_with(c, &w, &settings)?;
+        assert_eq!(image.pixels[5][5], Color::new(0.5, 0.5, 0.));
+        assert_eq!(image.pixels[0][0], Color::new(0., 0., 0.));
+
+        let (_, stats) = render_with_stats(c, &w, &settings)?;
+        assert_eq!(stats.rays, 11 * 11);
+        Ok(())
+    }
+}
@@ -0,0 +1,188 @@
+mod tests {
+    use std::f32::consts::PI;
+
+    use rand::{rngs::SmallRng, Rng, SeedableRng};
+    use tracer::background::Background;
+    use tracer::camera::{render_with_integrator, Camera, RenderSettings};
+    use tracer::canvas::{alpha_at, pixel_at, write_alpha, write_pixel, Canvas};
+    use tracer::denoise::{denoise, render_denoised, DenoiseSettings, Guides};
+    use tracer::integrators::PathTracer;
+    use tracer::metrics::mse;
+    use tracer::plane::Plane;
+    use tracer::sampling::SamplePattern;
+    use tracer::transforms::{translation, view_transform};
+    use tracer::world::{ShapeEnum, World};
+    use tracer::{point, vector, Color};
+
+    // Left half one shade, right half another
+    fn split(width: usize, height: usize, left: Color, right: Color) -> Canvas {
+        let mut c = Canvas::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                let color = if x < width / 2 { left } else { right };
+                write_pixel(&mut c, x, y, color);
+            }
+        }
+        c
+    }
+
+    fn noisy(canvas: &Canvas, amount: f32, seed: u64) -> Canvas {
+        let mut rng = SmallRng::seed_from_u64(seed);
+        let mut result = canvas.clone();
+        for row in result.pixels.iter_mut() {
+            for pixel in row.iter_mut() {
+                let n = (rng.gen::<f32>() - 0.5) * 2. * amount;
+                *pixel = *pixel + Color::new(n, n, n);
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn test_denoise_reduces_noise() -> Result<(), String> {
+        let grey = Color::new(0.5, 0.5, 0.5);
+        let clean = split(24, 16, grey, grey);
+        let noisy = noisy(&clean, 0.2, 1);
+        let albedo = split(24, 16, grey, grey);
+        let guides = Guides {
+            albedo: Some(&albedo),
+            ..Guides::default()
+        };
+        let result = denoise(&noisy, guides, &DenoiseSettings::default())?;
+        assert!(mse(&result, &clean)? < mse(&noisy, &clean)? * 0.25);
+        Ok(())
+    }
+
+    #[test]
+    fn test_zero_strength_is_identity() -> Result<(), String> {
+        let c = noisy(
+            &split(8, 8, Color::new(0.2, 0.2, 0.2), Color::new(0.8, 0.8, 0.8)),
+            0.1,
+            2,
+        );
+        let result = denoise(&c, Guides::default(), &DenoiseSettings::new(0.))?;
+        assert_eq!(result.pixels, c.pixels);
+        Ok(())
+    }
+
+    #[test]
+    fn test_guides_stop_blur_across_edges() -> Result<(), String> {
+        let (dark, light) = (Color::new(0.45, 0.45, 0.45), Color::new(0.55, 0.55, 0.55));
+        let clean = split(24, 16, dark, light);
+        let noisy = noisy(&clean, 0.1, 3);
+        // Strong enough that the color term alone can't tell the halves apart
+        let settings = DenoiseSettings::new(5.);
+
+        let unguided = denoise(&noisy, Guides::default(), &settings)?;
+        let edge = pixel_at(&unguided, 11, 8).red;
+        assert!(edge > 0.47, "{}", edge);
+
+        let normal = split(24, 16, Color::new(0., 0., 1.), Color::new(1., 0., 0.));
+        let depth = split(24, 16, Color::new(2., 2., 2.), Color::new(5., 5., 5.));
+        for guides in [
+            Guides {
+                normal: Some(&normal),
+                ..Guides::default()
+            },
+            Guides {
+                depth: Some(&depth),
+                ..Guides::default()
+            },
+        ] {
+            let guided = denoise(&noisy, guides, &settings)?;
+            for y in 0..16 {
+                assert!((pixel_at(&guided, 11, y).red - 0.45).abs() < 0.04);
+                assert!((pixel_at(&guided, 12, y).red - 0.55).abs() < 0.04);
+            }
+            assert!(mse(&guided, &clean)? < mse(&noisy, &clean)?);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_denoise_keeps_alpha() -> Result<(), String> {
+        let mut c = noisy(
+            &split(8, 8, Color::new(0.5, 0.5, 0.5), Color::new(0.5, 0.5, 0.5)),
+            0.1,
+            4,
+        );
+        write_alpha(&mut c, 2, 3, 0.5);
+        let result = denoise(&c, Guides::default(), &DenoiseSettings::default())?;
+        assert_eq!(alpha_at(&result, 2, 3), 0.5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_guide_size_must_match() {
+        let c = Canvas::new(8, 8);
+        let small = Canvas::new(4, 8);
+        let guides = Guides {
+            depth: Some(&small),
+            ..Guides::default()
+        };
+        assert!(denoise(&c, guides, &DenoiseSettings::default()).is_err());
+    }
+
+    #[test]
+    fn test_non_positive_sigma_is_an_error() {
+        let c = Canvas::new(8, 8);
+        let albedo = Canvas::new(8, 8);
+        let guides = Guides {
+            albedo: Some(&albedo),
+            ..Guides::default()
+        };
+        for settings in [
+            DenoiseSettings {
+                spatial_sigma: 0.,
+                ..DenoiseSettings::default()
+            },
+            DenoiseSettings {
+                albedo_sigma: 0.,
+                ..DenoiseSettings::default()
+            },
+            DenoiseSettings {
+                normal_sigma: -1.,
+                ..DenoiseSettings::default()
+            },
+            DenoiseSettings {
+                depth_sigma: f32::NAN,
+                ..DenoiseSettings::default()
+            },
+        ] {
+            assert!(denoise(&c, guides, &settings).is_err());
+        }
+    }
+
+    #[test]
+    fn test_render_denoised_path_tracer() -> Result<(), String> {
+        // A bright sky lights the floor through diffuse bounces, which is
+        // noisy at low sample counts
+        let mut w = World {
+            background: Background::Color(Color::new(0.8, 0.8, 0.8)),
+            ..World::default()
+        };
+        w.objects.push(ShapeEnum::Plane(Plane {
+            transform: translation(0., -1., 0.),
+            ..Plane::default()
+        }));
+        let mut c = Camera::new(24, 16, PI / 3.);
+        c.transform = view_transform(point(0., 1.5, -5.), point(0., 0., 0.), vector(0., 1., 0.));
+        let tracer = PathTracer::default();
+        let noisy_settings = RenderSettings {
+            samples_per_pixel: 2,
+            sample_pattern: SamplePattern::Jittered,
+            ..RenderSettings::default()
+        };
+        let reference_settings = RenderSettings {
+            samples_per_pixel: 64,
+            sample_pattern: SamplePattern::Jittered,
+            ..RenderSettings::default()
+        };
+        let (reference, _) = render_with_integrator(c, &w, &reference_settings, &tracer)?;
+        let (noisy, _) = render_with_integrator(c, &w, &noisy_settings, &tracer)?;
+        let denoised =
+            render_denoised(c, &w, &noisy_settings, &tracer, &DenoiseSettings::default())?;
+        assert!(mse(&denoised, &reference)? < mse(&noisy, &reference)?);
+        Ok(())
+    }
+}
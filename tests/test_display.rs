@@ -0,0 +1,192 @@
+mod tests {
+    use tracer::canvas::{write_pixel, Canvas};
+    use tracer::display::{canvas_to_ppm_with, srgb_oetf, DisplayTransform, Dither, ToneMap};
+    use tracer::lut::{parse_cube, CubeLut, Look};
+    use tracer::Color;
+
+    fn srgb_to_linear(encoded: f32) -> f32 {
+        ((encoded + 0.055) / 1.055).powf(2.4)
+    }
+
+    #[test]
+    fn test_srgb_oetf() {
+        assert_eq!(srgb_oetf(0.), 0.);
+        assert!((srgb_oetf(1.) - 1.).abs() < 1e-6);
+        assert!((srgb_oetf(0.0031308) - 0.04045).abs() < 1e-5);
+        assert!((srgb_oetf(0.5) - 0.735357).abs() < 1e-5);
+        assert!((srgb_oetf(0.001) - 0.01292).abs() < 1e-6);
+        assert_eq!(srgb_oetf(-1.), 0.);
+        assert!((srgb_oetf(5.) - 1.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_tone_maps() {
+        assert_eq!(ToneMap::Clamp.apply(2.), 1.);
+        assert_eq!(ToneMap::Clamp.apply(-2.), 0.);
+        assert_eq!(ToneMap::Reinhard.apply(1.), 0.5);
+        assert_eq!(ToneMap::Reinhard.apply(3.), 0.75);
+
+        let extended = ToneMap::ExtendedReinhard { white: 4. };
+        assert!((extended.apply(4.) - 1.).abs() < 1e-6);
+        assert_eq!(extended.apply(10.), 1.);
+        assert!(extended.apply(1.) > ToneMap::Reinhard.apply(1.));
+
+        assert_eq!(ToneMap::Aces.apply(0.), 0.);
+        assert!((ToneMap::Aces.apply(100.) - 1.).abs() < 0.01);
+        let mut previous = 0.;
+        for i in 1..30 {
+            let value = ToneMap::Aces.apply(i as f32 * 0.1);
+            assert!(value > previous);
+            previous = value;
+        }
+    }
+
+    #[test]
+    fn test_extended_reinhard_without_positive_white() {
+        for white in [0., -2., f32::NAN, 1e-30] {
+            let tone_map = ToneMap::ExtendedReinhard { white };
+            assert_eq!(tone_map.apply(0.), 0.);
+            assert_eq!(tone_map.apply(0.5), 1.);
+            assert_eq!(tone_map.apply(20.), 1.);
+        }
+    }
+
+    #[test]
+    fn test_exposure_in_stops() {
+        let brighter = DisplayTransform::new(1., ToneMap::Clamp);
+        let darker = DisplayTransform::new(-2., ToneMap::Clamp);
+        let neutral = DisplayTransform::default();
+        let grey = Color::new(0.25, 0.25, 0.25);
+        assert_eq!(
+            brighter.encode(grey),
+            neutral.encode(Color::new(0.5, 0.5, 0.5))
+        );
+        assert_eq!(
+            darker.encode(grey),
+            neutral.encode(Color::new(0.0625, 0.0625, 0.0625))
+        );
+    }
+
+    #[test]
+    fn test_bytes_are_rounded_and_encoded() {
+        let transform = DisplayTransform::default();
+        assert_eq!(
+            transform.to_bytes(Color::new(0.5, 0., 1.), 0, 0),
+            [188, 0, 255]
+        );
+        assert_eq!(
+            transform.to_bytes(Color::new(2., -1., 0.2), 0, 0),
+            [255, 0, 124]
+        );
+    }
+
+    #[test]
+    fn test_dither_offsets_cover_range_evenly() {
+        for dither in [Dither::Ordered, Dither::BlueNoise] {
+            let size = if dither == Dither::Ordered { 8 } else { 32 };
+            let mut offsets: Vec<f32> = (0..size * size)
+                .map(|i| dither.offset(i % size, i / size))
+                .collect();
+            offsets.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            let step = 1. / (size * size) as f32;
+            for (i, offset) in offsets.iter().enumerate() {
+                assert!((offset - (-0.5 + (i as f32 + 0.5) * step)).abs() < 1e-6);
+            }
+            // The pattern tiles
+            assert_eq!(dither.offset(3, 5), dither.offset(3 + size, 5 + 2 * size));
+        }
+        assert_eq!(Dither::None.offset(3, 4), 0.);
+    }
+
+    #[test]
+    fn test_blue_noise_spreads_out_low_thresholds() {
+        // The lowest tenth of the mask has no two pixels side by side
+        let low = |x: usize, y: usize| Dither::BlueNoise.offset(x % 32, y % 32) < -0.4;
+        for y in 0..32 {
+            for x in 0..32 {
+                if low(x, y) {
+                    assert!(!low(x + 1, y) && !low(x, y + 1));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_dithering_preserves_average_level() {
+        // Encoded value a quarter of the way between two 8-bit levels
+        let linear = srgb_to_linear(100.25 / 255.);
+        let color = Color::new(linear, linear, linear);
+        for (dither, size) in [(Dither::Ordered, 8), (Dither::BlueNoise, 32)] {
+            let transform = DisplayTransform {
+                dither,
+                ..DisplayTransform::default()
+            };
+            let mut total = 0.;
+            for y in 0..size {
+                for x in 0..size {
+                    total += transform.to_bytes(color, x, y)[0] as f32;
+                }
+            }
+            assert!((total / (size * size) as f32 - 100.25).abs() < 0.02);
+        }
+        // Without dithering every pixel rounds the same way
+        assert_eq!(
+            DisplayTransform::default().to_bytes(color, 3, 7),
+            [100, 100, 100]
+        );
+    }
+
+    #[test]
+    fn test_canvas_to_ppm_with_transform() {
+        let mut c = Canvas::new(2, 1);
+        write_pixel(&mut c, 0, 0, Color::new(1., 3., 0.5));
+        let transform = DisplayTransform::new(0., ToneMap::Reinhard);
+        let ppm = canvas_to_ppm_with(&c, &transform);
+        assert_eq!(ppm[..3], ["P3", "2 1", "255"]);
+        assert_eq!(ppm[3], "188 225 156 0 0 0");
+    }
+    fn lut_3d(size: usize, f: impl Fn(f32) -> f32) -> CubeLut {
+        let mut text = format!("LUT_3D_SIZE {}\n", size);
+        let step = 1. / (size - 1) as f32;
+        for b in 0..size {
+            for g in 0..size {
+                for r in 0..size {
+                    let (r, g, b) = (r as f32 * step, g as f32 * step, b as f32 * step);
+                    text.push_str(&format!("{} {} {}\n", f(r), f(g), f(b)));
+                }
+            }
+        }
+        parse_cube(&text).unwrap()
+    }
+
+    #[test]
+    fn test_identity_look_leaves_output_unchanged() {
+        // Highlights above one still go through the tone map untouched
+        let mut c = Canvas::new(3, 1);
+        write_pixel(&mut c, 0, 0, Color::new(1., 3., 0.5));
+        write_pixel(&mut c, 1, 0, Color::new(0.02, 12., 0.3));
+        write_pixel(&mut c, 2, 0, Color::new(0.7, 0.1, 0.));
+        let plain = DisplayTransform::new(0.5, ToneMap::Reinhard);
+        let graded = DisplayTransform {
+            look: Some(Look::new(lut_3d(5, |x| x))),
+            ..plain.clone()
+        };
+        assert_eq!(
+            canvas_to_ppm_with(&c, &graded),
+            canvas_to_ppm_with(&c, &plain)
+        );
+    }
+
+    #[test]
+    fn test_look_grades_encoded_color() {
+        let plain = DisplayTransform::new(0., ToneMap::Reinhard);
+        let inverted = DisplayTransform {
+            look: Some(Look::new(lut_3d(2, |x| 1. - x))),
+            ..plain.clone()
+        };
+        for color in [Color::new(0.5, 4., 0.01), Color::new(0.18, 0.18, 0.18)] {
+            let expected = plain.to_bytes(color, 0, 0).map(|c| 255 - c);
+            assert_eq!(inverted.to_bytes(color, 0, 0), expected);
+        }
+    }
+}
@@ -0,0 +1,196 @@
+mod tests {
+    use std::f32::consts::PI;
+    use std::sync::Arc;
+
+    use rand::{rngs::SmallRng, Rng, SeedableRng};
+    use tracer::background::equirectangular_uv;
+    use tracer::canvas::{write_pixel, Canvas};
+    use tracer::environment::{environment_lighting, equirectangular_direction, EnvironmentLight};
+    use tracer::intersections::{prepare_computations, Intersection};
+    use tracer::materials::Material;
+    use tracer::plane::Plane;
+    use tracer::ray::Ray;
+    use tracer::sphere::Sphere;
+    use tracer::transforms::translation;
+    use tracer::world::{color_at, ShapeEnum, World};
+    use tracer::{magnitude, point, vector, Color};
+
+    fn constant_map(color: Color) -> Arc<Canvas> {
+        let mut canvas = Canvas::new(16, 8);
+        for y in 0..8 {
+            for x in 0..16 {
+                write_pixel(&mut canvas, x, y, color);
+            }
+        }
+        Arc::new(canvas)
+    }
+
+    fn white_floor() -> ShapeEnum {
+        ShapeEnum::Plane(Plane {
+            material: Material {
+                ambient: 0.,
+                diffuse: 1.,
+                specular: 0.,
+                ..Material::default()
+            },
+            ..Plane::default()
+        })
+    }
+
+    fn estimate(world: &World, object: ShapeEnum, r: Ray, t: f32) -> Color {
+        let i = Intersection::new(t, object);
+        let comps = prepare_computations(i.clone(), r, vec![i]);
+        let mut rng = SmallRng::seed_from_u64(3);
+        environment_lighting(world, &comps, &mut rng)
+    }
+
+    #[test]
+    fn test_equirectangular_direction_round_trip() {
+        for (u, v) in [(0.1, 0.2), (0.5, 0.5), (0.8, 0.7), (0.3, 0.95)] {
+            let d = equirectangular_direction(u, v);
+            assert!((magnitude(d) - 1.).abs() < 1e-5);
+            let (u2, v2) = equirectangular_uv(d);
+            assert!((u - u2).abs() < 1e-4 && (v - v2).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_constant_map_samples_uniformly() -> Result<(), &'static str> {
+        let light = EnvironmentLight::new(constant_map(Color::new(1., 1., 1.)))?;
+        let mut rng = SmallRng::seed_from_u64(0);
+        let mut area = 0.;
+        for _ in 0..1000 {
+            let (direction, pdf) = light.sample(rng.gen(), rng.gen());
+            assert!((pdf - light.pdf(direction)).abs() < 1e-3 * pdf);
+            area += 1. / pdf;
+        }
+        // The average of 1 / pdf estimates the solid angle of the whole sphere
+        assert!((area / 1000. / (4. * PI) - 1.).abs() < 0.05);
+        Ok(())
+    }
+
+    #[test]
+    fn test_empty_map_is_an_error() {
+        assert!(EnvironmentLight::new(Arc::new(Canvas::new(0, 0))).is_err());
+        assert!(EnvironmentLight::new(Arc::new(Canvas::new(4, 0))).is_err());
+    }
+
+    #[test]
+    fn test_samples_follow_bright_pixels() -> Result<(), &'static str> {
+        let mut canvas = Canvas::new(16, 8);
+        for y in 0..8 {
+            for x in 0..16 {
+                write_pixel(&mut canvas, x, y, Color::new(0.01, 0.01, 0.01));
+            }
+        }
+        write_pixel(&mut canvas, 4, 3, Color::new(1000., 1000., 1000.));
+        let light = EnvironmentLight::new(Arc::new(canvas))?;
+
+        let mut rng = SmallRng::seed_from_u64(0);
+        let mut in_sun = 0;
+        for _ in 0..200 {
+            let (direction, _) = light.sample(rng.gen(), rng.gen());
+            let (u, v) = equirectangular_uv(direction);
+            let (x, y) = ((u * 16.) as usize, ((1. - v) * 8.) as usize);
+            if (x, y) == (4, 3) {
+                in_sun += 1;
+            }
+        }
+        assert!(in_sun > 190);
+        Ok(())
+    }
+
+    #[test]
+    fn test_white_furnace() -> Result<(), &'static str> {
+        // A white Lambertian floor under a uniform sky reflects exactly the sky's radiance
+        let mut w = World::new();
+        let floor = white_floor();
+        w.objects = vec![floor.clone()];
+        let mut environment = EnvironmentLight::new(constant_map(Color::new(1., 1., 1.)))?;
+        environment.samples = 256;
+        w.environment = Some(environment);
+
+        let r = Ray::new(point(0., 1., 0.), vector(0., -1., 0.));
+        let result = estimate(&w, floor.clone(), r, 1.);
+        assert!((result.red - 1.).abs() < 0.05);
+        assert_eq!(result.red, result.blue);
+        Ok(())
+    }
+
+    #[test]
+    fn test_occluders_block_environment() -> Result<(), &'static str> {
+        let mut w = World::new();
+        let floor = white_floor();
+        w.objects = vec![
+            floor.clone(),
+            ShapeEnum::Sphere(Sphere {
+                transform: translation(0., 1.5, 0.),
+                ..Sphere::default()
+            }),
+        ];
+        let mut environment = EnvironmentLight::new(constant_map(Color::new(1., 1., 1.)))?;
+        environment.samples = 64;
+        w.environment = Some(environment);
+
+        let r = Ray::new(point(0., 0.4, -0.2), vector(0., -1., 0.5));
+        let result = estimate(&w, floor.clone(), r, 0.4);
+        assert!(result.red < 0.8 && result.red > 0.);
+        Ok(())
+    }
+
+    #[test]
+    fn test_glossy_surface_reflects_bright_spot() -> Result<(), &'static str> {
+        // A bright patch straight above a shiny floor seen from straight above
+        let mut canvas = Canvas::new(16, 8);
+        for x in 0..16 {
+            write_pixel(&mut canvas, x, 0, Color::new(50., 50., 50.));
+        }
+        let mut environment = EnvironmentLight::new(Arc::new(canvas))?;
+        environment.samples = 32;
+
+        let floor = ShapeEnum::Plane(Plane {
+            material: Material {
+                ambient: 0.,
+                diffuse: 0.,
+                specular: 1.,
+                shininess: 50.,
+                ..Material::default()
+            },
+            ..Plane::default()
+        });
+        let mut w = World::new();
+        w.objects = vec![floor.clone()];
+        w.environment = Some(environment);
+
+        let above = estimate(
+            &w,
+            floor.clone(),
+            Ray::new(point(0., 1., 0.), vector(0., -1., 0.)),
+            1.,
+        );
+        let grazing = estimate(
+            &w,
+            floor,
+            Ray::new(point(0., 1., -5.), vector(0., -1., 5.)),
+            1.,
+        );
+        assert!(above.red > 1.);
+        assert!(grazing.red < above.red * 0.1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_environment_lights_whitted_render_without_point_light() -> Result<(), &'static str> {
+        let mut w = World::new();
+        w.objects = vec![white_floor()];
+        let mut environment = EnvironmentLight::new(constant_map(Color::new(0.5, 0.5, 0.5)))?;
+        environment.samples = 256;
+        w.environment = Some(environment);
+
+        let r = Ray::new(point(0., 1., 0.), vector(0., -1., 0.));
+        let c = color_at(&w, r, 5);
+        assert!((c.green - 0.5).abs() < 0.03);
+        assert_eq!(c, color_at(&w, r, 5));
+        Ok(())
+    }
+}
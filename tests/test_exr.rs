@@ -0,0 +1,199 @@
+mod tests {
+    use std::io::Read;
+
+    use flate2::read::ZlibDecoder;
+    use tracer::canvas::{alpha_at, write_alpha, write_pixel, AlphaMode, Canvas};
+    use tracer::exr::{canvas_to_exr, canvas_to_exr_with_alpha, ExrCompression};
+    use tracer::Color;
+
+    fn i32_at(data: &[u8], pos: usize) -> i32 {
+        i32::from_le_bytes(data[pos..pos + 4].try_into().unwrap())
+    }
+
+    fn c_string(data: &[u8], pos: &mut usize) -> String {
+        let end = *pos + data[*pos..].iter().position(|&b| b == 0).unwrap();
+        let text = String::from_utf8(data[*pos..end].to_vec()).unwrap();
+        *pos = end + 1;
+        text
+    }
+
+    // Just enough of a reader to check what `canvas_to_exr` writes
+    fn read_exr(data: &[u8]) -> (Vec<(String, Vec<u8>)>, Canvas) {
+        assert_eq!(data[..4], [0x76, 0x2f, 0x31, 0x01]);
+        assert_eq!(i32_at(data, 4), 2);
+        let mut pos = 8;
+        let mut attributes = Vec::new();
+        loop {
+            let name = c_string(data, &mut pos);
+            if name.is_empty() {
+                break;
+            }
+            let _kind = c_string(data, &mut pos);
+            let size = i32_at(data, pos) as usize;
+            attributes.push((name, data[pos + 4..pos + 4 + size].to_vec()));
+            pos += 4 + size;
+        }
+        let find = |name: &str| {
+            attributes
+                .iter()
+                .find(|(n, _)| n == name)
+                .unwrap()
+                .1
+                .clone()
+        };
+        let window = find("dataWindow");
+        let width = i32_at(&window, 8) as usize + 1;
+        let height = i32_at(&window, 12) as usize + 1;
+        let lines = if find("compression") == [3] { 16 } else { 1 };
+        // Each channel entry is its name, a null and 16 bytes; A sorts first
+        let channels = find("channels");
+        let has_alpha = channels[0] == b'A';
+        let channel_count = if has_alpha { 4 } else { 3 };
+
+        let mut canvas = Canvas::new(width, height);
+        let chunks = height.div_ceil(lines);
+        for chunk in 0..chunks {
+            let offset = u64::from_le_bytes(
+                data[pos + chunk * 8..pos + chunk * 8 + 8]
+                    .try_into()
+                    .unwrap(),
+            ) as usize;
+            let first = i32_at(data, offset) as usize;
+            let size = i32_at(data, offset + 4) as usize;
+            let stored = &data[offset + 8..offset + 8 + size];
+            let count = lines.min(height - first);
+            let raw_size = count * width * channel_count * 4;
+            let raw = if size < raw_size {
+                let mut inflated = Vec::new();
+                ZlibDecoder::new(stored).read_to_end(&mut inflated).unwrap();
+                for i in 1..inflated.len() {
+                    inflated[i] = inflated[i].wrapping_add(inflated[i - 1]).wrapping_sub(128);
+                }
+                let half = inflated.len().div_ceil(2);
+                (0..inflated.len())
+                    .map(|i| {
+                        if i % 2 == 0 {
+                            inflated[i / 2]
+                        } else {
+                            inflated[half + i / 2]
+                        }
+                    })
+                    .collect()
+            } else {
+                stored.to_vec()
+            };
+            assert_eq!(raw.len(), raw_size);
+            let value = |i: usize| f32::from_le_bytes(raw[i * 4..i * 4 + 4].try_into().unwrap());
+            for line in 0..count {
+                for x in 0..width {
+                    let mut base = line * width * channel_count;
+                    if has_alpha {
+                        write_alpha(&mut canvas, x, first + line, value(base + x));
+                        base += width;
+                    }
+                    let color = Color::new(
+                        value(base + 2 * width + x),
+                        value(base + width + x),
+                        value(base + x),
+                    );
+                    write_pixel(&mut canvas, x, first + line, color);
+                }
+            }
+        }
+        (attributes, canvas)
+    }
+
+    fn gradient(width: usize, height: usize) -> Canvas {
+        let mut canvas = Canvas::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                let color = Color::new(x as f32 * 0.37, y as f32 * 1.5, 12.25 - (x + y) as f32);
+                write_pixel(&mut canvas, x, y, color);
+            }
+        }
+        canvas
+    }
+
+    #[test]
+    fn test_header_attributes() -> Result<(), String> {
+        let data = canvas_to_exr(&gradient(5, 3), ExrCompression::None)?;
+        let (attributes, _) = read_exr(&data);
+        let names: Vec<&str> = attributes.iter().map(|(n, _)| n.as_str()).collect();
+        for required in [
+            "channels",
+            "compression",
+            "dataWindow",
+            "displayWindow",
+            "lineOrder",
+            "pixelAspectRatio",
+            "screenWindowCenter",
+            "screenWindowWidth",
+        ] {
+            assert!(names.contains(&required));
+        }
+        let channels = &attributes[0].1;
+        assert_eq!(channels.len(), 3 * 18 + 1);
+        assert_eq!(&channels[..2], b"B\0");
+        Ok(())
+    }
+
+    #[test]
+    fn test_uncompressed_round_trip() -> Result<(), String> {
+        let canvas = gradient(7, 5);
+        let (_, read) = read_exr(&canvas_to_exr(&canvas, ExrCompression::None)?);
+        assert_eq!((read.width, read.height), (7, 5));
+        // Exact float values, including those far above one
+        assert_eq!(
+            read.pixels[4][6].blue.to_bits(),
+            canvas.pixels[4][6].blue.to_bits()
+        );
+        for y in 0..5 {
+            for x in 0..7 {
+                assert_eq!(read.pixels[y][x], canvas.pixels[y][x]);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_zip_round_trip() -> Result<(), String> {
+        let canvas = gradient(40, 37);
+        let zipped = canvas_to_exr(&canvas, ExrCompression::Zip)?;
+        let plain = canvas_to_exr(&canvas, ExrCompression::None)?;
+        assert!(zipped.len() < plain.len());
+        let (attributes, read) = read_exr(&zipped);
+        assert!(attributes.contains(&("compression".to_string(), vec![3])));
+        for y in 0..37 {
+            for x in 0..40 {
+                assert_eq!(read.pixels[y][x], canvas.pixels[y][x]);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_alpha_channel() -> Result<(), String> {
+        let mut canvas = gradient(6, 20);
+        write_pixel(&mut canvas, 1, 2, Color::new(0.2, 0.4, 0.6));
+        write_alpha(&mut canvas, 1, 2, 0.5);
+        write_pixel(&mut canvas, 3, 17, Color::new(0., 0., 0.));
+        write_alpha(&mut canvas, 3, 17, 0.);
+
+        for compression in [ExrCompression::None, ExrCompression::Zip] {
+            let data = canvas_to_exr_with_alpha(&canvas, compression, AlphaMode::Premultiplied)?;
+            let (attributes, read) = read_exr(&data);
+            assert_eq!(attributes[0].1.len(), 4 * 18 + 1);
+            assert_eq!(alpha_at(&read, 1, 2), 0.5);
+            assert_eq!(alpha_at(&read, 3, 17), 0.);
+            assert_eq!(alpha_at(&read, 0, 0), 1.);
+            assert_eq!(read.pixels[2][1], Color::new(0.2, 0.4, 0.6));
+            assert_eq!(read.pixels[19][5], canvas.pixels[19][5]);
+        }
+
+        let data = canvas_to_exr_with_alpha(&canvas, ExrCompression::None, AlphaMode::Straight)?;
+        let (_, read) = read_exr(&data);
+        assert_eq!(read.pixels[2][1], Color::new(0.4, 0.8, 1.2));
+        assert_eq!(read.pixels[17][3], Color::new(0., 0., 0.));
+        Ok(())
+    }
+}
@@ -11,7 +11,7 @@ mod tests {
         assert_eq!(a.w, 1.0);
 
         assert!(a.is_vector());
-        assert_eq!(a.is_point(), false);
+        assert!(!a.is_point());
     }
 
     #[test]
@@ -24,7 +24,7 @@ mod tests {
         assert_eq!(a.w, 0.);
 
         assert!(a.is_point());
-        assert_eq!(a.is_vector(), false);
+        assert!(!a.is_vector());
     }
 
     #[test]
@@ -0,0 +1,43 @@
+mod tests {
+    use tracer::filters::Filter;
+
+    #[test]
+    fn test_filter_radius() {
+        assert_eq!(Filter::Box.radius(), 0.5);
+        assert_eq!(Filter::Tent.radius(), 1.);
+        assert_eq!(Filter::Gaussian.radius(), 1.5);
+        assert_eq!(Filter::Mitchell.radius(), 2.);
+    }
+
+    #[test]
+    fn test_box_filter() {
+        assert_eq!(Filter::Box.weight(0., 0.), 1.);
+        assert_eq!(Filter::Box.weight(0.4, -0.4), 1.);
+        assert_eq!(Filter::Box.weight(0.6, 0.), 0.);
+    }
+
+    #[test]
+    fn test_tent_filter() {
+        assert_eq!(Filter::Tent.weight(0., 0.), 1.);
+        assert_eq!(Filter::Tent.weight(0.5, 0.), 0.5);
+        assert_eq!(Filter::Tent.weight(0.5, 0.5), 0.25);
+        assert_eq!(Filter::Tent.weight(1.2, 0.), 0.);
+    }
+
+    #[test]
+    fn test_gaussian_filter_falls_off() {
+        let center = Filter::Gaussian.weight(0., 0.);
+        let near = Filter::Gaussian.weight(0.5, 0.);
+        let far = Filter::Gaussian.weight(1., 0.);
+        assert!(center > near && near > far && far > 0.);
+        assert_eq!(Filter::Gaussian.weight(1.5, 0.), 0.);
+    }
+
+    #[test]
+    fn test_mitchell_filter() {
+        assert!((Filter::Mitchell.weight(0., 0.) - (8. / 9.) * (8. / 9.)).abs() < 1e-5);
+        assert!(Filter::Mitchell.weight(1.5, 0.) < 0.);
+        assert!(Filter::Mitchell.weight(2., 0.).abs() < 1e-6);
+        assert_eq!(Filter::Mitchell.weight(2.5, 0.), 0.);
+    }
+}
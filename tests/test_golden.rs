@@ -0,0 +1,154 @@
+mod tests {
+    use std::f32::consts::PI;
+    use std::path::PathBuf;
+    use tracer::camera::{Camera, RenderSettings};
+    use tracer::canvas::{write_pixel, Canvas};
+    use tracer::golden::{check_render, compare_to_reference, Tolerance, REGENERATE_ENV};
+    use tracer::materials::Material;
+    use tracer::plane::Plane;
+    use tracer::transforms::{translation, view_transform};
+    use tracer::world::{ShapeEnum, World};
+    use tracer::{point, vector, Color};
+
+    fn golden_dir() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden")
+    }
+
+    // A fresh directory per test so the failure output can be inspected
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("tracer-golden-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn camera(hsize: usize, vsize: usize) -> Camera {
+        let mut c = Camera::new(hsize, vsize, PI / 3.);
+        c.transform = view_transform(point(0., 1.5, -5.), point(0., 0.5, 0.), vector(0., 1., 0.));
+        c
+    }
+
+    fn gradient(width: usize, height: usize) -> Canvas {
+        let mut canvas = Canvas::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                let v = x as f32 / width as f32;
+                write_pixel(&mut canvas, x, y, Color::new(v, 0.5, 1. - v));
+            }
+        }
+        canvas
+    }
+
+    #[test]
+    fn test_golden_default_world() -> Result<(), String> {
+        let world = World::default();
+        check_render(
+            &golden_dir(),
+            "default_world",
+            camera(40, 20),
+            &world,
+            &RenderSettings::default(),
+            Tolerance::default(),
+        )?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_golden_reflective_floor() -> Result<(), String> {
+        let mut world = World::default();
+        let floor = Plane {
+            transform: translation(0., -1., 0.),
+            material: Material {
+                reflective: 0.5,
+                ..Material::default()
+            },
+        };
+        world.objects.push(ShapeEnum::Plane(floor));
+        check_render(
+            &golden_dir(),
+            "reflective_floor",
+            camera(40, 20),
+            &world,
+            &RenderSettings::default(),
+            Tolerance::default(),
+        )?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_matching_image_passes_and_cleans_up() -> Result<(), String> {
+        let dir = scratch_dir("pass");
+        let image = gradient(16, 8);
+        compare_to_reference(&dir, "gradient", &image, Tolerance::default(), true)?;
+        assert!(dir.join("gradient.pfm").exists());
+
+        let metrics = compare_to_reference(&dir, "gradient", &image, Tolerance::default(), false)?;
+        assert_eq!(metrics.max_difference, 0.);
+        assert!(!dir.join("gradient.actual.pfm").exists());
+        assert!(!dir.join("gradient.diff.ppm").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+        Ok(())
+    }
+
+    #[test]
+    fn test_mismatch_writes_actual_and_diff() -> Result<(), String> {
+        let dir = scratch_dir("fail");
+        let image = gradient(16, 8);
+        compare_to_reference(&dir, "gradient", &image, Tolerance::default(), true)?;
+
+        let mut changed = image.clone();
+        write_pixel(&mut changed, 3, 3, Color::new(1., 1., 1.));
+        let result = compare_to_reference(&dir, "gradient", &changed, Tolerance::default(), false);
+        assert!(result.is_err());
+        assert!(dir.join("gradient.actual.pfm").exists());
+        assert!(dir.join("gradient.diff.ppm").exists());
+
+        // A looser tolerance accepts the same change and removes the old output
+        let loose = Tolerance {
+            min_psnr: 0.,
+            min_ssim: 0.,
+            max_difference: 1.,
+        };
+        compare_to_reference(&dir, "gradient", &changed, loose, false)?;
+        assert!(!dir.join("gradient.actual.pfm").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing_reference_mentions_regenerate_switch() {
+        let dir = scratch_dir("missing");
+        std::fs::create_dir_all(&dir).unwrap();
+        let result = compare_to_reference(
+            &dir,
+            "nothing",
+            &gradient(4, 4),
+            Tolerance::default(),
+            false,
+        );
+        assert!(result.unwrap_err().contains(REGENERATE_ENV));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_size_mismatch_fails() -> Result<(), String> {
+        let dir = scratch_dir("size");
+        compare_to_reference(
+            &dir,
+            "gradient",
+            &gradient(8, 8),
+            Tolerance::default(),
+            true,
+        )?;
+        let result = compare_to_reference(
+            &dir,
+            "gradient",
+            &gradient(8, 4),
+            Tolerance::default(),
+            false,
+        );
+        assert!(result.is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+        Ok(())
+    }
+}
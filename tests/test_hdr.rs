@@ -0,0 +1,204 @@
+mod tests {
+    use tracer::canvas::{write_pixel, Canvas};
+    use tracer::hdr::{canvas_from_hdr, canvas_to_hdr, color_to_rgbe, rgbe_to_color};
+    use tracer::Color;
+
+    fn hdr_file(width: usize, height: usize, pixels: &[u8]) -> Vec<u8> {
+        let mut data = format!(
+            "#?RADIANCE\n# made by hand\nFORMAT=32-bit_rle_rgbe\nEXPOSURE=1.0\n\n-Y {} +X {}\n",
+            height, width
+        )
+        .into_bytes();
+        data.extend_from_slice(pixels);
+        data
+    }
+
+    #[test]
+    fn test_rgbe_to_color() {
+        assert_eq!(rgbe_to_color([0, 0, 0, 0]), Color::new(0., 0., 0.));
+        assert_eq!(
+            rgbe_to_color([128, 64, 32, 129]),
+            Color::new(1.0039062, 0.5039062, 0.2539062)
+        );
+        // Values above one are what makes the format worth having
+        assert_eq!(rgbe_to_color([128, 0, 0, 136]), Color::new(128.5, 0.5, 0.5));
+    }
+
+    #[test]
+    fn test_read_flat_hdr() -> Result<(), String> {
+        let data = hdr_file(
+            2,
+            2,
+            &[
+                128, 0, 0, 129, 0, 128, 0, 129, //
+                0, 0, 128, 129, 128, 128, 128, 130,
+            ],
+        );
+        let canvas = canvas_from_hdr(&data)?;
+        assert_eq!((canvas.width, canvas.height), (2, 2));
+        assert_eq!(
+            canvas.pixels[0][0],
+            Color::new(1.0039062, 0.0039062, 0.0039062)
+        );
+        assert_eq!(
+            canvas.pixels[0][1],
+            Color::new(0.0039062, 1.0039062, 0.0039062)
+        );
+        assert_eq!(
+            canvas.pixels[1][0],
+            Color::new(0.0039062, 0.0039062, 1.0039062)
+        );
+        assert_eq!(
+            canvas.pixels[1][1],
+            Color::new(2.0078125, 2.0078125, 2.0078125)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_old_style_repeats() -> Result<(), String> {
+        let data = hdr_file(4, 1, &[128, 0, 0, 129, 1, 1, 1, 3]);
+        let canvas = canvas_from_hdr(&data)?;
+        for x in 0..4 {
+            assert_eq!(
+                canvas.pixels[0][x],
+                Color::new(1.0039062, 0.0039062, 0.0039062)
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_run_length_encoded_hdr() -> Result<(), String> {
+        let mut scanline = vec![2, 2, 0, 8];
+        // Red: a run of eight 128s
+        scanline.extend_from_slice(&[128 + 8, 128]);
+        // Green: eight literal values
+        scanline.extend_from_slice(&[8, 0, 16, 32, 48, 64, 80, 96, 112]);
+        // Blue: a run of zeros
+        scanline.extend_from_slice(&[128 + 8, 0]);
+        // Exponent: a literal pair followed by a run
+        scanline.extend_from_slice(&[2, 129, 130, 128 + 6, 129]);
+        let data = hdr_file(8, 1, &scanline);
+
+        let canvas = canvas_from_hdr(&data)?;
+        assert_eq!(canvas.width, 8);
+        assert_eq!(canvas.pixels[0][0], rgbe_to_color([128, 0, 0, 129]));
+        assert_eq!(canvas.pixels[0][1], rgbe_to_color([128, 16, 0, 130]));
+        assert_eq!(canvas.pixels[0][7], rgbe_to_color([128, 112, 0, 129]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_reject_invalid_hdr() {
+        assert!(canvas_from_hdr(b"P3\n1 1\n255\n0 0 0\n").is_err());
+        assert!(canvas_from_hdr(b"#?RADIANCE\nFORMAT=32-bit_rle_xyze\n\n-Y 1 +X 1\n").is_err());
+        assert!(canvas_from_hdr(b"#?RADIANCE\n\n+Y 1 +X 1\n\x80\x80\x80\x80").is_err());
+        // Truncated pixel data
+        assert!(canvas_from_hdr(&hdr_file(2, 1, &[128, 0, 0, 129])).is_err());
+    }
+
+    #[test]
+    fn test_reject_overlong_repeats() {
+        // Zero-length repeats keep raising the count's shift past the width of usize
+        let mut pixels = vec![128, 0, 0, 129];
+        for _ in 0..12 {
+            pixels.extend_from_slice(&[1, 1, 1, 0]);
+        }
+        pixels.extend_from_slice(&[1, 1, 1, 1]);
+        assert!(canvas_from_hdr(&hdr_file(4, 1, &pixels)).is_err());
+
+        // A repeat longer than what's left of the scanline
+        assert!(canvas_from_hdr(&hdr_file(4, 1, &[128, 0, 0, 129, 1, 1, 1, 4])).is_err());
+    }
+
+    // Channels share an exponent, so precision is relative to the brightest
+    fn close(a: Color, b: Color) -> bool {
+        let tolerance = b.red.max(b.green).max(b.blue) * 0.01 + 1e-6;
+        (a.red - b.red).abs() < tolerance
+            && (a.green - b.green).abs() < tolerance
+            && (a.blue - b.blue).abs() < tolerance
+    }
+
+    #[test]
+    fn test_color_to_rgbe() {
+        assert_eq!(color_to_rgbe(Color::new(0., 0., 0.)), [0, 0, 0, 0]);
+        assert_eq!(color_to_rgbe(Color::new(1., 0.5, 0.25)), [128, 64, 32, 129]);
+        assert_eq!(color_to_rgbe(Color::new(-1., 0.5, 0.)), [0, 128, 0, 128]);
+        for color in [
+            Color::new(1.5, 0.25, 100.),
+            Color::new(0.001, 0.002, 0.003),
+            Color::new(65000., 1., 0.),
+        ] {
+            assert!(close(rgbe_to_color(color_to_rgbe(color)), color));
+        }
+    }
+
+    #[test]
+    fn test_color_to_rgbe_out_of_range() {
+        assert_eq!(
+            color_to_rgbe(Color::new(f32::INFINITY, 0., 0.)),
+            [0, 0, 0, 0]
+        );
+        assert_eq!(
+            color_to_rgbe(Color::new(f32::NAN, f32::NAN, f32::NAN)),
+            [0, 0, 0, 0]
+        );
+        assert_eq!(
+            color_to_rgbe(Color::new(f32::NEG_INFINITY, 0.5, f32::NAN)),
+            [0, 128, 0, 128]
+        );
+        // Too bright for the exponent byte, so clamped to the largest value
+        assert_eq!(
+            color_to_rgbe(Color::new(f32::MAX, 0., 0.)),
+            [255, 0, 0, 255]
+        );
+        assert_eq!(
+            color_to_rgbe(Color::new(3e38, 3e38, 1.)),
+            [255, 255, 0, 255]
+        );
+    }
+
+    fn test_canvas(width: usize, height: usize) -> Canvas {
+        let mut canvas = Canvas::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                // Long runs on the left, noise on the right
+                let color = if x < width / 2 {
+                    Color::new(4., 2., 0.5)
+                } else {
+                    Color::new(x as f32 * 0.3, (x * y) as f32 * 0.01, 30. / (y + 1) as f32)
+                };
+                write_pixel(&mut canvas, x, y, color);
+            }
+        }
+        canvas
+    }
+
+    #[test]
+    fn test_hdr_round_trip() -> Result<(), String> {
+        for (width, height) in [(300, 4), (20, 3), (3, 2)] {
+            let canvas = test_canvas(width, height);
+            let read = canvas_from_hdr(&canvas_to_hdr(&canvas))?;
+            assert_eq!((read.width, read.height), (width, height));
+            for y in 0..height {
+                for x in 0..width {
+                    assert!(close(read.pixels[y][x], canvas.pixels[y][x]));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_hdr_runs_are_compressed() {
+        let mut canvas = Canvas::new(256, 1);
+        for x in 0..256 {
+            write_pixel(&mut canvas, x, 0, Color::new(2., 2., 2.));
+        }
+        let data = canvas_to_hdr(&canvas);
+        assert!(data.starts_with(b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 1 +X 256\n"));
+        // Each channel fits in three runs of up to 127 bytes
+        assert!(data.len() < 60 + 4 * 6);
+    }
+}
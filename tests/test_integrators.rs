@@ -0,0 +1,93 @@
+mod tests {
+    use std::f32::consts::PI;
+
+    use rand::{rngs::SmallRng, SeedableRng};
+    use tracer::camera::{render_with_integrator, Camera, RenderSettings};
+    use tracer::integrators::{Integrator, PathTracer, Whitted};
+    use tracer::plane::Plane;
+    use tracer::ray::Ray;
+    use tracer::sphere::Sphere;
+    use tracer::transforms::{translation, view_transform};
+    use tracer::world::{color_at, ShapeEnum, World};
+    use tracer::{point, vector, Color};
+
+    #[test]
+    fn test_whitted_matches_color_at() {
+        let w = World::default();
+        let r = Ray::new(point(0., 0., -5.), vector(0., 0., 1.));
+        let mut rng = SmallRng::seed_from_u64(0);
+        assert_eq!(Whitted::new(5).li(&w, r, &mut rng), color_at(&w, r, 5));
+    }
+
+    #[test]
+    fn test_path_tracer_miss_is_black() {
+        let w = World::default();
+        let r = Ray::new(point(0., 0., -5.), vector(0., 1., 0.));
+        let mut rng = SmallRng::seed_from_u64(0);
+        assert_eq!(
+            PathTracer::default().li(&w, r, &mut rng),
+            Color::new(0., 0., 0.)
+        );
+    }
+
+    #[test]
+    fn test_single_bounce_is_direct_light_without_ambient() {
+        let w = World::default();
+        let r = Ray::new(point(0., 0., -5.), vector(0., 0., 1.));
+        let mut rng = SmallRng::seed_from_u64(0);
+        let tracer = PathTracer {
+            max_depth: 1,
+            ..PathTracer::default()
+        };
+        assert_eq!(
+            tracer.li(&w, r, &mut rng),
+            Color::new(0.30066, 0.37583, 0.2255)
+        );
+    }
+
+    #[test]
+    fn test_path_tracer_lights_shadowed_surfaces_indirectly() {
+        let mut w = World::new();
+        w.light = World::default().light;
+        w.objects = vec![
+            ShapeEnum::Plane(Plane {
+                transform: translation(0., -1., 0.),
+                ..Plane::default()
+            }),
+            ShapeEnum::Sphere(Sphere::default()),
+        ];
+
+        // The underside of the sphere is in its own shadow but faces the lit floor
+        let r = Ray::new(point(0., -0.9, -5.), vector(0., 0., 1.));
+        let mut rng = SmallRng::seed_from_u64(7);
+        let direct = PathTracer {
+            max_depth: 1,
+            ..PathTracer::default()
+        };
+        assert_eq!(direct.li(&w, r, &mut rng), Color::new(0., 0., 0.));
+
+        let tracer = PathTracer::default();
+        let mut total = Color::new(0., 0., 0.);
+        for _ in 0..64 {
+            total = total + tracer.li(&w, r, &mut rng);
+        }
+        assert!(total.red / 64. > 0.01);
+    }
+
+    #[test]
+    fn test_render_with_path_tracer_is_deterministic() -> Result<(), String> {
+        let w = World::default();
+        let mut c = Camera::new(6, 6, PI / 2.);
+        c.transform = view_transform(point(0., 0., -5.), point(0., 0., 0.), vector(0., 1., 0.));
+        let settings = RenderSettings {
+            samples_per_pixel: 4,
+            ..RenderSettings::default()
+        };
+        let tracer = PathTracer::default();
+        let (a, stats) = render_with_integrator(c, &w, &settings, &tracer)?;
+        let (b, _) = render_with_integrator(c, &w, &settings, &tracer)?;
+        assert_eq!(a.pixels, b.pixels);
+        assert_eq!(stats.rays, 6 * 6 * 4);
+        Ok(())
+    }
+}
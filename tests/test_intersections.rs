@@ -10,17 +10,17 @@ mod tests {
     #[test]
     fn test_intersection() {
         let s = Sphere::default();
-        let i = Intersection::new(3.5, ShapeEnum::Sphere(s));
+        let i = Intersection::new(3.5, ShapeEnum::Sphere(s.clone()));
         assert_eq!(i.t, 3.5);
-        assert_eq!(i.object, ShapeEnum::Sphere(s));
+        assert_eq!(i.object, ShapeEnum::Sphere(s.clone()));
     }
 
     #[test]
     fn test_intersections() {
         let s = Sphere::default();
-        let i1 = Intersection::new(1., ShapeEnum::Sphere(s));
-        let i2 = Intersection::new(2., ShapeEnum::Sphere(s));
-        let xs = vec![i1, i2];
+        let i1 = Intersection::new(1., ShapeEnum::Sphere(s.clone()));
+        let i2 = Intersection::new(2., ShapeEnum::Sphere(s.clone()));
+        let xs = [i1, i2];
 
         assert_eq!(xs.len(), 2);
         assert_eq!(xs[0].t, 1.);
@@ -30,9 +30,9 @@ mod tests {
     #[test]
     fn test_hit_positive() {
         let s = Sphere::default();
-        let i1 = Intersection::new(1., ShapeEnum::Sphere(s));
-        let i2 = Intersection::new(2., ShapeEnum::Sphere(s));
-        let xs = vec![i1, i2];
+        let i1 = Intersection::new(1., ShapeEnum::Sphere(s.clone()));
+        let i2 = Intersection::new(2., ShapeEnum::Sphere(s.clone()));
+        let xs = vec![i1.clone(), i2.clone()];
         let i = hit(xs);
         assert_eq!(i.unwrap(), i1);
     }
@@ -40,9 +40,9 @@ mod tests {
     #[test]
     fn test_hit_some_negative() {
         let s = Sphere::default();
-        let i1 = Intersection::new(-1., ShapeEnum::Sphere(s));
-        let i2 = Intersection::new(1., ShapeEnum::Sphere(s));
-        let xs = vec![i1, i2];
+        let i1 = Intersection::new(-1., ShapeEnum::Sphere(s.clone()));
+        let i2 = Intersection::new(1., ShapeEnum::Sphere(s.clone()));
+        let xs = vec![i1.clone(), i2.clone()];
         let i = hit(xs);
         assert_eq!(i.unwrap(), i2);
     }
@@ -50,9 +50,9 @@ mod tests {
     #[test]
     fn test_all_negative() {
         let s = Sphere::default();
-        let i1 = Intersection::new(-2., ShapeEnum::Sphere(s));
-        let i2 = Intersection::new(-1., ShapeEnum::Sphere(s));
-        let xs = vec![i1, i2];
+        let i1 = Intersection::new(-2., ShapeEnum::Sphere(s.clone()));
+        let i2 = Intersection::new(-1., ShapeEnum::Sphere(s.clone()));
+        let xs = vec![i1.clone(), i2.clone()];
         let i = hit(xs);
         assert!(i.is_none());
     }
@@ -60,11 +60,11 @@ mod tests {
     #[test]
     fn test_first_hit_nonnegative() {
         let s = Sphere::default();
-        let i1 = Intersection::new(5., ShapeEnum::Sphere(s));
-        let i2 = Intersection::new(7., ShapeEnum::Sphere(s));
-        let i3 = Intersection::new(-3., ShapeEnum::Sphere(s));
-        let i4 = Intersection::new(2., ShapeEnum::Sphere(s));
-        let xs = vec![i1, i2, i3, i4];
+        let i1 = Intersection::new(5., ShapeEnum::Sphere(s.clone()));
+        let i2 = Intersection::new(7., ShapeEnum::Sphere(s.clone()));
+        let i3 = Intersection::new(-3., ShapeEnum::Sphere(s.clone()));
+        let i4 = Intersection::new(2., ShapeEnum::Sphere(s.clone()));
+        let xs = vec![i1, i2, i3, i4.clone()];
         let i = hit(xs);
         assert_eq!(i.unwrap(), i4);
     }
@@ -73,8 +73,8 @@ mod tests {
     fn test_precompute_intersection_state() {
         let r = Ray::new(point(0., 0., -5.), vector(0., 0., 1.));
         let shape = Sphere::default();
-        let i = Intersection::new(4., ShapeEnum::Sphere(shape));
-        let comps = prepare_computations(i, r, vec![i]);
+        let i = Intersection::new(4., ShapeEnum::Sphere(shape.clone()));
+        let comps = prepare_computations(i.clone(), r, vec![i.clone()]);
         assert_eq!(comps.t, i.t);
         assert_eq!(comps.object, i.object);
         assert_eq!(comps.point, point(0., 0., -1.));
@@ -86,8 +86,8 @@ mod tests {
     fn test_hit_outside() {
         let r = Ray::new(point(0., 0., -5.), vector(0., 0., 1.));
         let shape = Sphere::default();
-        let i = Intersection::new(4., ShapeEnum::Sphere(shape));
-        let comps = prepare_computations(i, r, vec![i]);
+        let i = Intersection::new(4., ShapeEnum::Sphere(shape.clone()));
+        let comps = prepare_computations(i.clone(), r, vec![i]);
         assert!(!comps.inside);
     }
 
@@ -95,8 +95,8 @@ mod tests {
     fn test_hit_inside() {
         let r = Ray::new(point(0., 0., 0.), vector(0., 0., 1.));
         let shape = Sphere::default();
-        let i = Intersection::new(1., ShapeEnum::Sphere(shape));
-        let comps = prepare_computations(i, r, vec![i]);
+        let i = Intersection::new(1., ShapeEnum::Sphere(shape.clone()));
+        let comps = prepare_computations(i.clone(), r, vec![i]);
         assert_eq!(comps.point, point(0., 0., 1.));
         assert_eq!(comps.eyev, vector(0., 0., -1.));
         assert!(comps.inside);
@@ -118,15 +118,15 @@ mod tests {
         c.material.refractive_index = 2.5;
 
         let xs = vec![
-            Intersection::new(2., ShapeEnum::Sphere(a)),
-            Intersection::new(2.75, ShapeEnum::Sphere(b)),
-            Intersection::new(3.25, ShapeEnum::Sphere(c)),
-            Intersection::new(4.75, ShapeEnum::Sphere(b)),
-            Intersection::new(5.25, ShapeEnum::Sphere(c)),
-            Intersection::new(6., ShapeEnum::Sphere(a)),
+            Intersection::new(2., ShapeEnum::Sphere(a.clone())),
+            Intersection::new(2.75, ShapeEnum::Sphere(b.clone())),
+            Intersection::new(3.25, ShapeEnum::Sphere(c.clone())),
+            Intersection::new(4.75, ShapeEnum::Sphere(b.clone())),
+            Intersection::new(5.25, ShapeEnum::Sphere(c.clone())),
+            Intersection::new(6., ShapeEnum::Sphere(a.clone())),
         ];
 
-        let results = vec![
+        let results = [
             (1.0, 1.5),
             (1.5, 2.0),
             (2.0, 2.5),
@@ -137,7 +137,7 @@ mod tests {
 
         let r = Ray::new(point(0., 0., -4.), vector(0., 0., 1.));
         for idx in 0..results.len() {
-            let intersection = xs[idx];
+            let intersection = xs[idx].clone();
             let result = results[idx];
             let comps = prepare_computations(intersection, r, xs.clone());
             assert_eq!(comps.n1, result.0);
@@ -150,8 +150,8 @@ mod tests {
         let r = Ray::new(point(0., 0., -5.), vector(0., 0., 1.));
         let mut shape = glass_sphere();
         shape.transform = translation(0., 0., 1.);
-        let i = Intersection::new(5., ShapeEnum::Sphere(shape));
-        let xs = vec![i];
+        let i = Intersection::new(5., ShapeEnum::Sphere(shape.clone()));
+        let xs = vec![i.clone()];
         let comps = prepare_computations(i, r, xs);
         assert_eq!(comps.under_point.z, 1e-4);
         assert!(comps.point.z < comps.under_point.z);
@@ -162,10 +162,10 @@ mod tests {
         let shape = glass_sphere();
         let r = Ray::new(point(0., 0., f32::sqrt(2.) / 2.), vector(0., 1., 0.));
         let xs = vec![
-            Intersection::new(-f32::sqrt(2.) / 2., ShapeEnum::Sphere(shape)),
-            Intersection::new(f32::sqrt(2.) / 2., ShapeEnum::Sphere(shape)),
+            Intersection::new(-f32::sqrt(2.) / 2., ShapeEnum::Sphere(shape.clone())),
+            Intersection::new(f32::sqrt(2.) / 2., ShapeEnum::Sphere(shape.clone())),
         ];
-        let comps = prepare_computations(xs[1], r, xs);
+        let comps = prepare_computations(xs[1].clone(), r, xs);
         let reflectance = shlick(comps);
         assert_eq!(reflectance, 1.);
     }
@@ -174,8 +174,11 @@ mod tests {
     fn test_shlick_perpendicular() {
         let shape = ShapeEnum::Sphere(glass_sphere());
         let r = Ray::new(point(0., 0., 0.), vector(0., 1., 0.));
-        let xs = vec![Intersection::new(-1., shape), Intersection::new(1., shape)];
-        let comps = prepare_computations(xs[1], r, xs);
+        let xs = vec![
+            Intersection::new(-1., shape.clone()),
+            Intersection::new(1., shape),
+        ];
+        let comps = prepare_computations(xs[1].clone(), r, xs);
         let reflectance = shlick(comps);
         assert_eq!(reflectance, 0.040000003);
     }
@@ -185,7 +188,7 @@ mod tests {
         let shape = ShapeEnum::Sphere(glass_sphere());
         let r = Ray::new(point(0., 0.99, -2.), vector(0., 0., 1.));
         let xs = vec![Intersection::new(1.8589, shape)];
-        let comps = prepare_computations(xs[0], r, xs);
+        let comps = prepare_computations(xs[0].clone(), r, xs);
         let reflectance = shlick(comps);
         assert_eq!(reflectance, 0.48873067);
     }
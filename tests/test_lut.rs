@@ -0,0 +1,171 @@
+mod tests {
+    use tracer::canvas::{alpha_at, write_alpha, write_pixel, Canvas};
+    use tracer::lut::{apply_lut, parse_cube, CubeLut, Look, LutInterpolation, LutTable};
+    use tracer::Color;
+
+    // A 3D LUT built from a function of the lattice coordinates
+    fn cube_text(size: usize, f: impl Fn(f32, f32, f32) -> (f32, f32, f32)) -> String {
+        let mut text = format!("# generated\nTITLE \"test look\"\nLUT_3D_SIZE {}\n\n", size);
+        let step = 1. / (size - 1) as f32;
+        for b in 0..size {
+            for g in 0..size {
+                for r in 0..size {
+                    let (x, y, z) = f(r as f32 * step, g as f32 * step, b as f32 * step);
+                    text.push_str(&format!("{} {} {}\n", x, y, z));
+                }
+            }
+        }
+        text
+    }
+
+    fn close(a: Color, b: Color) -> bool {
+        (a.red - b.red).abs() < 1e-4
+            && (a.green - b.green).abs() < 1e-4
+            && (a.blue - b.blue).abs() < 1e-4
+    }
+
+    #[test]
+    fn test_parse_3d_cube() -> Result<(), String> {
+        let lut = parse_cube(&cube_text(3, |r, g, b| (r, g, b)))?;
+        assert_eq!(lut.title, "test look");
+        assert_eq!(lut.domain_min, Color::new(0., 0., 0.));
+        assert_eq!(lut.domain_max, Color::new(1., 1., 1.));
+        match lut.table {
+            LutTable::ThreeD { size, entries } => {
+                assert_eq!(size, 3);
+                // Red changes fastest
+                assert_eq!(entries[1], Color::new(0.5, 0., 0.));
+                assert_eq!(entries[3], Color::new(0., 0.5, 0.));
+                assert_eq!(entries[9], Color::new(0., 0., 0.5));
+            }
+            _ => panic!("expected a 3D LUT"),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_default_interpolation_is_tetrahedral() {
+        assert_eq!(LutInterpolation::default(), LutInterpolation::Tetrahedral);
+    }
+
+    #[test]
+    fn test_identity_lut_keeps_colors() -> Result<(), String> {
+        let lut = parse_cube(&cube_text(5, |r, g, b| (r, g, b)))?;
+        for color in [
+            Color::new(0.1, 0.5, 0.9),
+            Color::new(0.33, 0.66, 0.12),
+            Color::new(1., 0., 0.75),
+        ] {
+            assert!(close(lut.apply(color, LutInterpolation::Trilinear), color));
+            assert!(close(
+                lut.apply(color, LutInterpolation::Tetrahedral),
+                color
+            ));
+        }
+        // Inputs outside the domain are clamped to its edge
+        assert!(close(
+            lut.apply(Color::new(2., -1., 0.5), LutInterpolation::Tetrahedral),
+            Color::new(1., 0., 0.5)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_interpolation_between_lattice_points() -> Result<(), String> {
+        // Squaring isn't linear, so results between lattice points differ
+        let lut = parse_cube(&cube_text(2, |r, g, b| (r * g, g * b, b * r)))?;
+        let color = Color::new(0.2, 0.6, 0.4);
+        let trilinear = lut.apply(color, LutInterpolation::Trilinear);
+        let tetrahedral = lut.apply(color, LutInterpolation::Tetrahedral);
+        // Trilinear interpolation of a bilinear function is exact
+        assert!(close(trilinear, Color::new(0.12, 0.24, 0.08)));
+        // Tetrahedral: g > b > r, so the path is origin, green, green+blue, white
+        assert!(close(tetrahedral, Color::new(0.2, 0.4, 0.2)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_tetrahedral_keeps_greys_on_the_diagonal() -> Result<(), String> {
+        let lut = parse_cube(&cube_text(4, |r, g, b| (r * r, g * 0.5 + b * 0.5, b)))?;
+        // Only the lattice's own diagonal points contribute to a grey input
+        let grey = Color::new(0.5, 0.5, 0.5);
+        let expected = lut.apply(grey, LutInterpolation::Trilinear);
+        assert!(close(
+            lut.apply(grey, LutInterpolation::Tetrahedral),
+            expected
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_1d_lut_with_input_range() -> Result<(), String> {
+        let text = "LUT_1D_SIZE 3\nLUT_1D_INPUT_RANGE 0 2\n0 0 1\n0.25 0.5 0.5\n1 1 0\n";
+        let lut = parse_cube(text)?;
+        assert_eq!(lut.domain_max, Color::new(2., 2., 2.));
+        let result = lut.apply(Color::new(0.5, 1., 1.5), LutInterpolation::Tetrahedral);
+        assert!(close(result, Color::new(0.125, 0.5, 0.25)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_domain_min_max() -> Result<(), String> {
+        let text = "LUT_1D_SIZE 2\nDOMAIN_MIN -1 0 0\nDOMAIN_MAX 1 4 1\n0 0 0\n1 1 1\n";
+        let lut = parse_cube(text)?;
+        let result = lut.apply(Color::new(0., 1., 0.5), LutInterpolation::Trilinear);
+        assert!(close(result, Color::new(0.5, 0.25, 0.5)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_reject_invalid_cube() {
+        assert!(parse_cube("0 0 0\n1 1 1\n").is_err());
+        assert!(parse_cube("LUT_3D_SIZE 2\n0 0 0\n1 1 1\n").is_err());
+        assert!(parse_cube("LUT_1D_SIZE 2\n0 0 zero\n1 1 1\n").is_err());
+        assert!(parse_cube("LUT_1D_SIZE 2\n0 0\n1 1 1\n").is_err());
+        assert!(parse_cube("LUT_1D_SIZE 1\n0 0 0\n").is_err());
+        assert!(
+            parse_cube("LUT_1D_SIZE 2\nDOMAIN_MIN 1 1 1\nDOMAIN_MAX 0 0 0\n0 0 0\n1 1 1\n")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_apply_lut_to_canvas() -> Result<(), String> {
+        let lut: CubeLut = parse_cube(&cube_text(3, |r, g, b| (1. - r, 1. - g, 1. - b)))?;
+        let mut canvas = Canvas::new(2, 2);
+        write_pixel(&mut canvas, 1, 0, Color::new(0.25, 0.5, 1.));
+        let result = apply_lut(&canvas, &lut, LutInterpolation::Tetrahedral);
+        assert!(close(result.pixels[0][1], Color::new(0.75, 0.5, 0.)));
+        assert!(close(result.pixels[1][1], Color::new(1., 1., 1.)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply_lut_grades_straight_color() -> Result<(), String> {
+        let lut: CubeLut = parse_cube(&cube_text(3, |r, g, b| (1. - r, 1. - g, 1. - b)))?;
+        let mut canvas = Canvas::new(2, 1);
+        // Half-covered (0.5, 1, 0), premultiplied
+        write_pixel(&mut canvas, 0, 0, Color::new(0.25, 0.5, 0.));
+        write_alpha(&mut canvas, 0, 0, 0.5);
+        write_alpha(&mut canvas, 1, 0, 0.);
+        let result = apply_lut(&canvas, &lut, LutInterpolation::Trilinear);
+        assert!(close(result.pixels[0][0], Color::new(0.25, 0., 0.5)));
+        assert_eq!(alpha_at(&result, 0, 0), 0.5);
+        // Empty pixels stay empty
+        assert_eq!(result.pixels[0][1], Color::new(0., 0., 0.));
+        assert_eq!(alpha_at(&result, 1, 0), 0.);
+        Ok(())
+    }
+
+    #[test]
+    fn test_look() -> Result<(), String> {
+        let lut = parse_cube(&cube_text(3, |r, g, b| (1. - r, 1. - g, 1. - b)))?;
+        let look = Look::new(lut.clone());
+        assert_eq!(look.interpolation, LutInterpolation::Tetrahedral);
+
+        let color = Color::new(0.2, 0.7, 0.4);
+        let expected = lut.apply(color, LutInterpolation::Tetrahedral);
+        assert_eq!(look.apply(color), expected);
+        Ok(())
+    }
+}
@@ -1,8 +1,8 @@
 mod tests {
     use tracer::intersections::{prepare_computations, Intersection};
     use tracer::lights::{lighting, PointLight};
-    use tracer::materials::Material;
-    use tracer::patterns::{Pattern, PatternType, StripePattern};
+    use tracer::materials::{Material, MIN_SHININESS};
+    use tracer::patterns::{CheckerPattern, Pattern, PatternType, StripePattern};
     use tracer::plane::Plane;
     use tracer::ray::Ray;
     use tracer::sphere::Sphere;
@@ -116,7 +116,15 @@ mod tests {
         let normalv = vector(0., 0., -1.);
         let light = PointLight::new(point(0., 0., -10.), Color::new(1., 1., 1.));
 
-        let c1 = lighting(m, obj, light, point(0.9, 0., 0.), eyev, normalv, false);
+        let c1 = lighting(
+            m.clone(),
+            obj.clone(),
+            light,
+            point(0.9, 0., 0.),
+            eyev,
+            normalv,
+            false,
+        );
         let c2 = lighting(m, obj, light, point(1.1, 0., 0.), eyev, normalv, false);
         assert_eq!(c1, white);
         assert_eq!(c2, black);
@@ -136,7 +144,7 @@ mod tests {
             vector(0., -f32::sqrt(2.) / 2., f32::sqrt(2.) / 2.),
         );
         let i = Intersection::new(f32::sqrt(2.), ShapeEnum::Plane(shape));
-        let comps = prepare_computations(i, r, vec![i]);
+        let comps = prepare_computations(i.clone(), r, vec![i]);
 
         assert_eq!(
             comps.reflectv,
@@ -150,4 +158,88 @@ mod tests {
         assert_eq!(m.transparency, 0.);
         assert_eq!(m.refractive_index, 1.0);
     }
+
+    #[test]
+    fn test_material_scalar_patterns_default_none() {
+        let m = Material::default();
+        let obj = ShapeEnum::Sphere(Sphere::default());
+        assert!(m.reflective_pattern.is_none());
+        assert_eq!(m.diffuse_at(&obj, point(0.5, 0., 0.)), 0.9);
+        assert_eq!(m.shininess_at(&obj, point(0.5, 0., 0.)), 200.);
+    }
+
+    #[test]
+    fn test_reflective_pattern_scales_reflectivity() {
+        let mut m = Material::default();
+        let obj = ShapeEnum::Plane(Plane::default());
+        let black = Color::new(0., 0., 0.);
+        let white = Color::new(1., 1., 1.);
+        m.reflective = 0.8;
+        m.reflective_pattern = Some(Pattern::new(PatternType::Checker(CheckerPattern::new(
+            black, white,
+        ))));
+
+        assert_eq!(m.reflective_at(&obj, point(0.5, 0., 0.5)), 0.);
+        assert!((m.reflective_at(&obj, point(1.5, 0., 0.5)) - 0.8).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_black_shininess_texel_keeps_minimum_exponent() {
+        let black = Color::new(0., 0., 0.);
+        let white = Color::new(1., 1., 1.);
+        let m = Material {
+            shininess_pattern: Some(Pattern::new(PatternType::Stripe(StripePattern::new(
+                white, black,
+            )))),
+            ..Material::default()
+        };
+        let obj = ShapeEnum::Sphere(Sphere::default());
+        assert_eq!(m.shininess_at(&obj, point(1.5, 0., 0.)), MIN_SHININESS);
+        assert_eq!(m.shininess_at(&obj, point(0.5, 0., 0.)), 200.);
+
+        // Well away from the mirror direction the highlight must fade
+        let light = PointLight::new(point(0., 10., -10.), white);
+        let c = lighting(
+            Material {
+                ambient: 0.,
+                diffuse: 0.,
+                ..m
+            },
+            obj,
+            light,
+            point(1.5, 0., 0.),
+            vector(0., 0., -1.),
+            vector(0., 0., -1.),
+            false,
+        );
+        assert!(c.red < 0.9);
+    }
+
+    #[test]
+    fn test_lighting_with_specular_pattern() {
+        let mut m = Material::default();
+        let obj = ShapeEnum::Sphere(Sphere::default());
+        let white = Color::new(1., 1., 1.);
+        let black = Color::new(0., 0., 0.);
+        m.specular_pattern = Some(Pattern::new(PatternType::Stripe(StripePattern::new(
+            white, black,
+        ))));
+        let eyev = vector(0., 0., -1.);
+        let normalv = vector(0., 0., -1.);
+        let light1 = PointLight::new(point(0.5, 0., -10.), Color::new(1., 1., 1.));
+        let light2 = PointLight::new(point(1.5, 0., -10.), Color::new(1., 1., 1.));
+
+        let c1 = lighting(
+            m.clone(),
+            obj.clone(),
+            light1,
+            point(0.5, 0., 0.),
+            eyev,
+            normalv,
+            false,
+        );
+        let c2 = lighting(m, obj, light2, point(1.5, 0., 0.), eyev, normalv, false);
+        assert_eq!(c1, Color::new(1.9, 1.9, 1.9));
+        assert_eq!(c2, Color::new(1., 1., 1.));
+    }
 }
@@ -153,7 +153,7 @@ mod tests {
             [0., 0., 1., 0.],
             [0., 0., 0., 1.],
         ]);
-        let output = a.clone() * identity;
+        let output = a * identity;
         assert_eq!(output, a);
     }
 
@@ -0,0 +1,324 @@
+mod tests {
+    use std::f32::consts::PI;
+
+    use rand::{rngs::SmallRng, SeedableRng};
+    use tracer::lights::PointLight;
+    use tracer::materials::Material;
+    use tracer::medium::{
+        light_transmittance, march, media_segments, medium_boundary, transmittance, Medium,
+    };
+    use tracer::patterns::{NoisePattern, Pattern, PatternType, StripePattern};
+    use tracer::plane::Plane;
+    use tracer::ray::Ray;
+    use tracer::sphere::Sphere;
+    use tracer::transforms::{rotation_x, rotation_z, scaling, translation};
+    use tracer::world::{color_at, ShapeEnum, World};
+    use tracer::{point, vector, Color};
+
+    fn fog_world(medium: Medium) -> World {
+        let mut w = World::new();
+        w.medium = Some(medium);
+        w
+    }
+
+    fn medium_sphere(medium: Medium) -> ShapeEnum {
+        ShapeEnum::Sphere(Sphere {
+            material: medium_boundary(medium),
+            ..Sphere::default()
+        })
+    }
+
+    #[test]
+    fn test_phase_function_integrates_to_one() {
+        for anisotropy in [0., 0.5, -0.7] {
+            let medium = Medium {
+                anisotropy,
+                ..Medium::default()
+            };
+            let steps = 2000;
+            let mut total = 0.;
+            for i in 0..steps {
+                let cos_theta = -1. + (i as f32 + 0.5) * 2. / steps as f32;
+                total += medium.phase(cos_theta) * 2. * PI * 2. / steps as f32;
+            }
+            assert!((total - 1.).abs() < 1e-3);
+        }
+        assert!((Medium::default().phase(0.3) - 1. / (4. * PI)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_homogeneous_fog_transmittance() {
+        let medium = Medium::new(Color::new(0.1, 0.2, 0.3), Color::new(0.1, 0.1, 0.1));
+        let w = fog_world(medium);
+        let r = Ray::new(point(0., 0., 0.), vector(1., 0., 0.));
+        assert_eq!(
+            transmittance(&w, r, 2.),
+            Color::new(f32::exp(-0.4), f32::exp(-0.6), f32::exp(-0.8))
+        );
+    }
+
+    #[test]
+    fn test_fog_ends_at_max_distance() {
+        let mut medium = Medium::new(Color::new(0.5, 0.5, 0.5), Color::new(0., 0., 0.));
+        medium.max_distance = 3.;
+        let w = fog_world(medium);
+        let r = Ray::new(point(0., 0., 0.), vector(0., 0., 1.));
+        let expected = f32::exp(-1.5);
+        assert_eq!(
+            transmittance(&w, r, f32::INFINITY),
+            Color::new(expected, expected, expected)
+        );
+    }
+
+    #[test]
+    fn test_segments_inside_shape() {
+        let medium = Medium::default();
+        let mut w = World::new();
+        w.objects = vec![medium_sphere(medium)];
+
+        let outside = Ray::new(point(0., 0., -5.), vector(0., 0., 1.));
+        let segments = media_segments(&w, outside, f32::INFINITY);
+        assert_eq!(segments.len(), 1);
+        assert_eq!((segments[0].start, segments[0].end), (4., 6.));
+
+        let inside = Ray::new(point(0., 0., 0.), vector(0., 0., 1.));
+        let segments = media_segments(&w, inside, f32::INFINITY);
+        assert_eq!((segments[0].start, segments[0].end), (0., 1.));
+
+        // Stopping at a surface inside the medium
+        let segments = media_segments(&w, outside, 5.);
+        assert_eq!((segments[0].start, segments[0].end), (4., 5.));
+    }
+
+    #[test]
+    fn test_global_fog_fills_gaps_between_shapes() {
+        let mut w = fog_world(Medium::default());
+        w.objects = vec![medium_sphere(Medium::new(
+            Color::new(1., 1., 1.),
+            Color::new(0., 0., 0.),
+        ))];
+        let r = Ray::new(point(0., 0., -5.), vector(0., 0., 1.));
+        let spans: Vec<(f32, f32, bool)> = media_segments(&w, r, 10.)
+            .iter()
+            .map(|s| (s.start, s.end, s.object.is_some()))
+            .collect();
+        assert_eq!(
+            spans,
+            vec![(0., 4., false), (4., 6., true), (6., 10., false)]
+        );
+    }
+
+    #[test]
+    fn test_density_pattern_varies_transmittance() {
+        // Stripes alternate between empty and full density every unit along x
+        let stripes = Pattern::new(PatternType::Stripe(StripePattern::new(
+            Color::new(0., 0., 0.),
+            Color::new(1., 1., 1.),
+        )));
+        let mut medium = Medium::new(Color::new(1., 1., 1.), Color::new(0., 0., 0.));
+        medium.density_pattern = Some(stripes);
+        let w = fog_world(medium.clone());
+
+        let empty = Ray::new(point(0.5, 0., 0.), vector(0., 0., 1.));
+        let full = Ray::new(point(1.5, 0., 0.), vector(0., 0., 1.));
+        assert_eq!(transmittance(&w, empty, 2.), Color::new(1., 1., 1.));
+        let expected = f32::exp(-2.);
+        assert_eq!(
+            transmittance(&w, full, 2.),
+            Color::new(expected, expected, expected)
+        );
+
+        let mut noise = Pattern::new(PatternType::Noise(NoisePattern::new(
+            Color::new(0., 0., 0.),
+            Color::new(1., 1., 1.),
+        )));
+        noise.transform = scaling(0.3, 0.3, 0.3) * rotation_z(0.4);
+        medium.density_pattern = Some(noise);
+        let w = fog_world(medium);
+        let t = transmittance(&w, full, 4.).red;
+        assert!(t > f32::exp(-4.) && t < 1.);
+    }
+
+    #[test]
+    fn test_media_shapes_attenuate_but_do_not_block_light() {
+        let mut w = World::new();
+        w.objects = vec![medium_sphere(Medium::new(
+            Color::new(0.5, 0.5, 0.5),
+            Color::new(0., 0., 0.),
+        ))];
+        let through = light_transmittance(&w, point(0., 0., -5.), vector(0., 0., 1.), 10.);
+        let expected = f32::exp(-1.);
+        assert_eq!(through, Color::new(expected, expected, expected));
+
+        w.objects.push(ShapeEnum::Sphere(Sphere {
+            transform: translation(0., 0., 3.),
+            ..Sphere::default()
+        }));
+        let blocked = light_transmittance(&w, point(0., 0., -5.), vector(0., 0., 1.), 10.);
+        assert_eq!(blocked, Color::new(0., 0., 0.));
+    }
+
+    #[test]
+    fn test_fog_scatters_light_towards_camera() {
+        let mut w = fog_world(Medium::default());
+        let r = Ray::new(point(0., 0., -5.), vector(0., 0., 1.));
+        assert_eq!(color_at(&w, r, 5), Color::new(0., 0., 0.));
+
+        w.light = Some(PointLight::new(point(0., 3., 0.), Color::new(1., 1., 1.)));
+        let c = color_at(&w, r, 5);
+        assert!(c.red > 0.);
+        assert_eq!(c, color_at(&w, r, 5));
+    }
+
+    #[test]
+    fn test_forward_scattering_glows_towards_light() {
+        let mut w = fog_world(Medium {
+            anisotropy: 0.8,
+            max_distance: 10.,
+            ..Medium::default()
+        });
+        w.light = Some(PointLight::new(point(0., 0., 20.), Color::new(1., 1., 1.)));
+        let mut rng = SmallRng::seed_from_u64(0);
+        let towards = Ray::new(point(0., 0., 0.), vector(0., 0., 1.));
+        let away = Ray::new(point(0., 0., 0.), vector(0., 0., -1.));
+        let (towards, _) = march(&w, towards, f32::INFINITY, &mut rng);
+        let (away, _) = march(&w, away, f32::INFINITY, &mut rng);
+        assert!(towards.red > 10. * away.red);
+    }
+
+    #[test]
+    fn test_fog_dims_distant_surfaces() {
+        let wall = ShapeEnum::Plane(Plane {
+            material: Material {
+                ambient: 1.,
+                diffuse: 0.,
+                specular: 0.,
+                ..Material::default()
+            },
+            transform: translation(0., 0., 10.) * rotation_x(PI / 2.),
+        });
+        let mut w = World::new();
+        w.light = Some(PointLight::new(point(0., 0., -10.), Color::new(1., 1., 1.)));
+        w.objects = vec![wall];
+        let r = Ray::new(point(0., 0., 0.), vector(0., 0., 1.));
+        let clear = color_at(&w, r, 5);
+
+        w.medium = Some(Medium::new(
+            Color::new(0.1, 0.1, 0.1),
+            Color::new(0., 0., 0.),
+        ));
+        let foggy = color_at(&w, r, 5);
+        assert!((foggy.red - clear.red * f32::exp(-1.)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_shadow_rays_pass_through_media() {
+        let floor = ShapeEnum::Plane(Plane {
+            material: Material {
+                ambient: 0.,
+                ..Material::default()
+            },
+            ..Plane::default()
+        });
+        let cloud = ShapeEnum::Sphere(Sphere {
+            material: medium_boundary(Medium::new(
+                Color::new(0.5, 0.5, 0.5),
+                Color::new(0., 0., 0.),
+            )),
+            transform: translation(0., 3., 0.),
+            ..Sphere::default()
+        });
+        let mut w = World::new();
+        w.light = Some(PointLight::new(point(0., 10., 0.), Color::new(1., 1., 1.)));
+        w.objects = vec![floor];
+        let r = Ray::new(point(0., 1., -1.), vector(0., -1., 1.));
+        let lit = color_at(&w, r, 5);
+
+        w.objects.push(cloud);
+        let shaded = color_at(&w, r, 5);
+        assert!(shaded.red < lit.red && shaded.red > 0.);
+    }
+
+    #[test]
+    fn test_fog_pattern_with_singular_transform() {
+        let mut stripes = Pattern::new(PatternType::Stripe(StripePattern::new(
+            Color::new(0., 0., 0.),
+            Color::new(1., 1., 1.),
+        )));
+        stripes.transform = scaling(0., 1., 1.);
+        let medium = Medium {
+            density_pattern: Some(stripes),
+            ..Medium::new(Color::new(1., 1., 1.), Color::new(0., 0., 0.))
+        };
+        assert!(medium.density_at(None, point(0.5, 0., 0.)).is_err());
+
+        // Marching ignores the pattern rather than panicking
+        let w = fog_world(medium);
+        let ray = Ray::new(point(0.5, 0., 0.), vector(0., 0., 1.));
+        let expected = f32::exp(-2.);
+        assert!((transmittance(&w, ray, 2.).red - expected).abs() < 1e-5);
+    }
+    #[test]
+    fn test_shape_medium_pattern_with_singular_transform() {
+        let mut stripes = Pattern::new(PatternType::Stripe(StripePattern::new(
+            Color::new(0., 0., 0.),
+            Color::new(1., 1., 1.),
+        )));
+        stripes.transform = scaling(0., 1., 1.);
+        let medium = Medium {
+            density_pattern: Some(stripes.clone()),
+            ..Medium::new(Color::new(1., 1., 1.), Color::new(0., 0., 0.))
+        };
+        let sphere = medium_sphere(medium.clone());
+        assert!(medium
+            .density_at(Some(&sphere), point(0.5, 0., 0.))
+            .is_err());
+
+        // A flattened shape can't be mapped back either
+        let flat = ShapeEnum::Sphere(Sphere {
+            transform: scaling(1., 0., 1.),
+            ..Sphere::default()
+        });
+        let readable = Medium {
+            density_pattern: Some(Pattern::new(PatternType::Stripe(StripePattern::new(
+                Color::new(1., 1., 1.),
+                Color::new(1., 1., 1.),
+            )))),
+            ..medium.clone()
+        };
+        assert!(readable
+            .density_at(Some(&flat), point(0.5, 0., 0.))
+            .is_err());
+        assert_eq!(
+            readable.density_at(Some(&sphere), point(0.5, 0., 0.)),
+            Ok(1.)
+        );
+
+        // Marching through the sphere falls back to the uniform density
+        let mut w = World::new();
+        w.objects = vec![sphere];
+        let ray = Ray::new(point(0., 0., -5.), vector(0., 0., 1.));
+        let expected = f32::exp(-2.);
+        assert!((transmittance(&w, ray, 10.).red - expected).abs() < 1e-4);
+    }
+    #[test]
+    fn test_invalid_step_sizes_still_finish() {
+        let white = Pattern::new(PatternType::Stripe(StripePattern::new(
+            Color::new(1., 1., 1.),
+            Color::new(1., 1., 1.),
+        )));
+        let ray = Ray::new(point(0., 0., 0.), vector(0., 0., 1.));
+        let expected = f32::exp(-2.);
+        for step_size in [0., -1., f32::NAN, f32::INFINITY, 1e-30] {
+            let w = fog_world(Medium {
+                density_pattern: Some(white.clone()),
+                step_size,
+                ..Medium::new(Color::new(1., 1., 1.), Color::new(0., 0., 0.))
+            });
+            assert!((transmittance(&w, ray, 2.).red - expected).abs() < 1e-4);
+            let mut rng = SmallRng::seed_from_u64(0);
+            let (_, marched) = march(&w, ray, 2., &mut rng);
+            assert!((marched.red - expected).abs() < 1e-3);
+        }
+    }
+}
@@ -0,0 +1,126 @@
+mod tests {
+    use rand::{rngs::SmallRng, Rng, SeedableRng};
+    use tracer::canvas::{write_pixel, Canvas};
+    use tracer::metrics::{
+        absolute_error, compare, difference_image, max_difference, mse, psnr, squared_error, ssim,
+    };
+    use tracer::Color;
+
+    fn gradient(width: usize, height: usize) -> Canvas {
+        let mut canvas = Canvas::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                let v = (x + y) as f32 / (width + height) as f32;
+                write_pixel(&mut canvas, x, y, Color::new(v, 1. - v, 0.5));
+            }
+        }
+        canvas
+    }
+
+    fn noisy(canvas: &Canvas, amount: f32, seed: u64) -> Canvas {
+        let mut rng = SmallRng::seed_from_u64(seed);
+        let mut result = canvas.clone();
+        for row in result.pixels.iter_mut() {
+            for pixel in row.iter_mut() {
+                let n = (rng.gen::<f32>() - 0.5) * 2. * amount;
+                *pixel = *pixel + Color::new(n, n, n);
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn test_per_pixel_errors() -> Result<(), String> {
+        let mut a = Canvas::new(2, 1);
+        let mut b = Canvas::new(2, 1);
+        write_pixel(&mut a, 0, 0, Color::new(0.5, 0.2, 1.));
+        write_pixel(&mut b, 0, 0, Color::new(0.25, 0.6, 1.));
+        let absolute = absolute_error(&a, &b)?;
+        let squared = squared_error(&a, &b)?;
+        assert_eq!(absolute.pixels[0][0], Color::new(0.25, 0.4, 0.));
+        assert_eq!(squared.pixels[0][0], Color::new(0.0625, 0.16, 0.));
+        assert_eq!(absolute.pixels[0][1], Color::new(0., 0., 0.));
+        assert!((mse(&a, &b)? - (0.0625 + 0.16) / 6.).abs() < 1e-6);
+        assert!((max_difference(&a, &b)? - 0.4).abs() < 1e-6);
+        Ok(())
+    }
+
+    #[test]
+    fn test_psnr() -> Result<(), String> {
+        let a = Canvas::new(4, 4);
+        let mut b = Canvas::new(4, 4);
+        assert_eq!(psnr(&a, &b, 1.)?, f32::INFINITY);
+        for y in 0..4 {
+            for x in 0..4 {
+                write_pixel(&mut b, x, y, Color::new(0.1, 0.1, 0.1));
+            }
+        }
+        // MSE of 0.01 is 20 dB below a peak of 1
+        assert!((psnr(&a, &b, 1.)? - 20.).abs() < 1e-4);
+        assert!((psnr(&a, &b, 10.)? - 40.).abs() < 1e-4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_ssim() -> Result<(), String> {
+        let a = gradient(32, 24);
+        assert!((ssim(&a, &a)? - 1.).abs() < 1e-5);
+        let slightly = ssim(&a, &noisy(&a, 0.02, 1))?;
+        let very = ssim(&a, &noisy(&a, 0.2, 1))?;
+        assert!(slightly < 1. && slightly > 0.5);
+        assert!(very < slightly);
+        Ok(())
+    }
+
+    #[test]
+    fn test_noise_falls_with_more_samples() -> Result<(), String> {
+        // Averaging four noisy images halves the noise, about +6 dB
+        let reference = gradient(32, 32);
+        let one = noisy(&reference, 0.1, 7);
+        let mut averaged = Canvas::new(32, 32);
+        for seed in 0..4 {
+            let sample = noisy(&reference, 0.1, seed + 10);
+            for y in 0..32 {
+                for x in 0..32 {
+                    averaged.pixels[y][x] = averaged.pixels[y][x] + sample.pixels[y][x] * 0.25;
+                }
+            }
+        }
+        let gain = psnr(&reference, &averaged, 1.)? - psnr(&reference, &one, 1.)?;
+        assert!((gain - 6.).abs() < 1.);
+        Ok(())
+    }
+
+    #[test]
+    fn test_compare_summary() -> Result<(), String> {
+        let a = gradient(16, 16);
+        let metrics = compare(&a, &a)?;
+        assert_eq!(metrics.mse, 0.);
+        assert_eq!(metrics.psnr, f32::INFINITY);
+        assert_eq!(metrics.max_difference, 0.);
+        assert!((metrics.ssim - 1.).abs() < 1e-5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_difference_image() -> Result<(), String> {
+        let a = Canvas::new(3, 1);
+        let mut b = Canvas::new(3, 1);
+        write_pixel(&mut b, 1, 0, Color::new(0., 0.05, 0.));
+        write_pixel(&mut b, 2, 0, Color::new(0.5, 0., 0.));
+        let diff = difference_image(&a, &b, 0.1)?;
+        assert_eq!(diff.pixels[0][0], Color::new(0., 0., 0.));
+        assert_eq!(diff.pixels[0][1], Color::new(0.5, 1., 0.));
+        assert_eq!(diff.pixels[0][2], Color::new(1., 0., 0.));
+        Ok(())
+    }
+
+    #[test]
+    fn test_size_mismatch() {
+        let a = Canvas::new(3, 2);
+        let b = Canvas::new(2, 3);
+        assert!(mse(&a, &b).is_err());
+        assert!(ssim(&a, &b).is_err());
+        assert!(difference_image(&a, &b, 1.).is_err());
+    }
+}
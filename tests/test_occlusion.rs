@@ -0,0 +1,127 @@
+mod tests {
+    use std::f32::consts::PI;
+
+    use rand::{rngs::SmallRng, SeedableRng};
+    use tracer::camera::{Camera, RenderSettings};
+    use tracer::lights::{lighting_occluded, PointLight};
+    use tracer::materials::Material;
+    use tracer::occlusion::{render_ambient_occlusion, AmbientOcclusion};
+    use tracer::plane::Plane;
+    use tracer::ray::Ray;
+    use tracer::sphere::Sphere;
+    use tracer::transforms::{scaling, translation, view_transform};
+    use tracer::world::{color_at, ShapeEnum, World};
+    use tracer::{normalize, point, vector, Color};
+
+    fn floor_and_ball() -> World {
+        let mut w = World::new();
+        w.light = Some(PointLight::new(
+            point(-10., 10., -10.),
+            Color::new(1., 1., 1.),
+        ));
+        w.objects = vec![
+            ShapeEnum::Plane(Plane::default()),
+            ShapeEnum::Sphere(Sphere {
+                transform: translation(0., 1., 0.),
+                ..Sphere::default()
+            }),
+        ];
+        w
+    }
+
+    #[test]
+    fn test_open_point_is_fully_visible() {
+        let mut w = World::new();
+        w.objects = vec![ShapeEnum::Plane(Plane::default())];
+        let ao = AmbientOcclusion::default();
+        let mut rng = SmallRng::seed_from_u64(0);
+        let v = ao.visibility(&w, point(0., 0.0001, 0.), vector(0., 1., 0.), &mut rng);
+        assert_eq!(v, 1.);
+    }
+
+    #[test]
+    fn test_enclosed_point_is_fully_occluded() {
+        let mut w = World::new();
+        w.objects = vec![ShapeEnum::Sphere(Sphere::default())];
+        let ao = AmbientOcclusion::new(32, 2.);
+        let mut rng = SmallRng::seed_from_u64(0);
+        let v = ao.visibility(&w, point(0., 0., 0.), vector(0., 1., 0.), &mut rng);
+        assert_eq!(v, 0.);
+    }
+
+    #[test]
+    fn test_occluders_beyond_max_distance_are_ignored() {
+        let mut w = World::new();
+        w.objects = vec![ShapeEnum::Sphere(Sphere {
+            transform: scaling(4., 4., 4.),
+            ..Sphere::default()
+        })];
+        let ao = AmbientOcclusion::new(32, 2.);
+        let mut rng = SmallRng::seed_from_u64(0);
+        let v = ao.visibility(&w, point(0., 0., 0.), vector(0., 1., 0.), &mut rng);
+        assert_eq!(v, 1.);
+    }
+
+    #[test]
+    fn test_contact_point_is_partially_occluded() {
+        let w = floor_and_ball();
+        let ao = AmbientOcclusion::new(256, 10.);
+        let mut rng = SmallRng::seed_from_u64(0);
+        let v = ao.visibility(&w, point(0.5, 0.0001, 0.), vector(0., 1., 0.), &mut rng);
+        assert!(v > 0.1 && v < 0.9);
+    }
+
+    #[test]
+    fn test_lighting_scales_ambient_by_visibility() {
+        let m = Material::default();
+        let obj = ShapeEnum::Sphere(Sphere::default());
+        let light = PointLight::new(point(0., 0., -10.), Color::new(1., 1., 1.));
+        let result = lighting_occluded(
+            &m,
+            &obj,
+            light,
+            point(0., 0., 0.),
+            vector(0., 0., -1.),
+            vector(0., 0., -1.),
+            true,
+            0.5,
+        );
+        assert_eq!(result, Color::new(0.05, 0.05, 0.05));
+    }
+
+    #[test]
+    fn test_world_occlusion_darkens_ambient_near_contact() {
+        let mut w = floor_and_ball();
+        // Looking at the floor right next to the ball, from the shadowed side
+        let r = Ray::new(point(0.3, 5., 5.), normalize(vector(0., -5., -4.2)));
+        let plain = color_at(&w, r, 5);
+        w.ambient_occlusion = Some(AmbientOcclusion::new(64, 2.));
+        let occluded = color_at(&w, r, 5);
+        assert!(occluded.red < plain.red);
+        assert_eq!(occluded, color_at(&w, r, 5));
+    }
+
+    #[test]
+    fn test_render_ambient_occlusion_is_grayscale() -> Result<(), String> {
+        let w = floor_and_ball();
+        let mut c = Camera::new(11, 11, PI / 2.);
+        c.transform = view_transform(point(0., 2., -4.), point(0., 0.5, 0.), vector(0., 1., 0.));
+        let image = render_ambient_occlusion(
+            c,
+            &w,
+            &RenderSettings::default(),
+            AmbientOcclusion::new(16, 1.),
+        )?;
+
+        // The top row looks past the scene into empty space
+        assert_eq!(image.pixels[0][5], Color::new(1., 1., 1.));
+        for row in &image.pixels {
+            for pixel in row {
+                assert_eq!(pixel.red, pixel.green);
+                assert_eq!(pixel.green, pixel.blue);
+                assert!((0. ..=1.).contains(&pixel.red));
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,8 +1,8 @@
 mod tests {
     use tracer::matrix::Matrix;
     use tracer::patterns::{
-        pattern_at_shape, CheckerPattern, GradientPattern, Pattern, PatternType, RadialGradient,
-        RingPattern, StripePattern,
+        pattern_at_shape, perlin, CheckerPattern, GradientPattern, NoisePattern, Pattern,
+        PatternType, RadialGradient, RingPattern, StripePattern,
     };
     use tracer::shape::Shape;
     use tracer::sphere::Sphere;
@@ -171,4 +171,30 @@ mod tests {
             Color::new(0.5, 0.5, 0.5)
         );
     }
+
+    #[test]
+    fn test_perlin_vanishes_on_lattice() {
+        assert_eq!(perlin(point(0., 0., 0.)), 0.);
+        assert_eq!(perlin(point(3., -2., 7.)), 0.);
+        assert_ne!(perlin(point(0.3, 0.6, 0.2)), 0.);
+    }
+
+    #[test]
+    fn test_noise_pattern_blends_between_colors() {
+        let black = Color::new(0., 0., 0.);
+        let white = Color::new(1., 1., 1.);
+        let mut pattern = NoisePattern::new(black, white);
+        pattern.octaves = 4;
+
+        assert_eq!(
+            pattern.local_pattern_at(point(0., 0., 0.)),
+            Color::new(0.5, 0.5, 0.5)
+        );
+        for i in 0..50 {
+            let p = point(i as f32 * 0.37, i as f32 * 0.11 - 3., i as f32 * -0.53);
+            let c = pattern.local_pattern_at(p);
+            assert!(c.red >= 0. && c.red <= 1.);
+            assert_eq!(c, pattern.local_pattern_at(p));
+        }
+    }
 }
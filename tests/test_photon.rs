@@ -0,0 +1,137 @@
+mod tests {
+    use rand::{rngs::SmallRng, Rng, SeedableRng};
+    use tracer::lights::PointLight;
+    use tracer::materials::Material;
+    use tracer::photon::{KdTree, Photon, PhotonMap};
+    use tracer::plane::Plane;
+    use tracer::ray::Ray;
+    use tracer::sphere::glass_sphere;
+    use tracer::transforms::translation;
+    use tracer::world::{color_at, ShapeEnum, World};
+    use tracer::{magnitude, point, vector, Color};
+
+    fn floor() -> ShapeEnum {
+        ShapeEnum::Plane(Plane {
+            material: Material {
+                ambient: 0.,
+                specular: 0.,
+                ..Material::default()
+            },
+            ..Plane::default()
+        })
+    }
+
+    fn lit_world(objects: Vec<ShapeEnum>, light: PointLight) -> World {
+        let mut w = World::new();
+        w.light = Some(light);
+        w.objects = objects;
+        w
+    }
+
+    #[test]
+    fn test_kd_tree_finds_photons_within_radius() {
+        let mut rng = SmallRng::seed_from_u64(1);
+        let photons: Vec<Photon> = (0..500)
+            .map(|_| Photon {
+                position: point(rng.gen(), rng.gen(), rng.gen()),
+                direction: vector(0., -1., 0.),
+                power: Color::new(1., 1., 1.),
+            })
+            .collect();
+        let tree = KdTree::new(photons.clone());
+        assert_eq!(tree.len(), 500);
+
+        for _ in 0..20 {
+            let query = point(rng.gen(), rng.gen(), rng.gen());
+            let expected = photons
+                .iter()
+                .filter(|p| magnitude(p.position - query) <= 0.2)
+                .count();
+            let found = tree.within(query, 0.2);
+            assert_eq!(found.len(), expected);
+            assert!(found.iter().all(|p| magnitude(p.position - query) <= 0.2));
+        }
+        assert!(KdTree::new(Vec::new())
+            .within(point(0., 0., 0.), 1.)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_no_photons_without_specular_surfaces() {
+        let w = lit_world(
+            vec![floor()],
+            PointLight::new(point(0., 5., 0.), Color::new(1., 1., 1.)),
+        );
+        assert!(PhotonMap::new(&w, 1000, 0.5).is_empty());
+        assert!(PhotonMap::new(&World::new(), 1000, 0.5).is_empty());
+    }
+
+    #[test]
+    fn test_mirror_irradiance_matches_reflected_light() {
+        // A mirror above the light acts as a second light mirrored to y = 15.
+        // Without distance falloff the light's power is fixed where photons
+        // first land, so the floor gets the mirror's irradiance scaled by
+        // (5 / 15)^2.
+        let mirror = ShapeEnum::Plane(Plane {
+            material: Material {
+                reflective: 1.,
+                ..Material::default()
+            },
+            transform: translation(0., 10., 0.),
+        });
+        let w = lit_world(
+            vec![floor(), mirror],
+            PointLight::new(point(0., 5., 0.), Color::new(1., 1., 1.)),
+        );
+        let map = PhotonMap::new(&w, 50_000, 1.5);
+        let irradiance = map.irradiance(point(0., 0., 0.), vector(0., 1., 0.));
+        assert!((irradiance.red - 1. / 9.).abs() < 0.015);
+        // Photons only land on the side facing the mirror
+        assert_eq!(
+            map.irradiance(point(0., 0., 0.), vector(0., -1., 0.)),
+            Color::new(0., 0., 0.)
+        );
+    }
+
+    #[test]
+    fn test_glass_sphere_focuses_light() {
+        let mut lens = glass_sphere();
+        lens.transform = translation(0., 2., 0.);
+        let w = lit_world(
+            vec![floor(), ShapeEnum::Sphere(lens)],
+            PointLight::new(point(0., 10., 0.), Color::new(1., 1., 1.)),
+        );
+        let map = PhotonMap::new(&w, 20_000, 0.2);
+        assert!(!map.is_empty());
+        assert_eq!(map, PhotonMap::new(&w, 20_000, 0.2));
+
+        let up = vector(0., 1., 0.);
+        // Brighter under the lens than the light would make it on its own
+        assert!(map.irradiance(point(0., 0., 0.), up).red > 1.);
+        assert_eq!(
+            map.irradiance(point(3., 0., 0.), up),
+            Color::new(0., 0., 0.)
+        );
+    }
+
+    #[test]
+    fn test_shade_hit_adds_caustics() {
+        let mut lens = glass_sphere();
+        lens.transform = translation(0., 2., 0.);
+        let mut w = lit_world(
+            vec![floor(), ShapeEnum::Sphere(lens)],
+            PointLight::new(point(0., 10., 0.), Color::new(1., 1., 1.)),
+        );
+        let r = Ray::new(point(0., 0.5, -1.), vector(0., -0.5, 1.));
+        let plain = color_at(&w, r, 5);
+        w.caustics = Some(PhotonMap::new(&w, 20_000, 0.3));
+        let with_caustics = color_at(&w, r, 5);
+        assert!(with_caustics.red > plain.red);
+
+        // Far from the lens nothing changes
+        let r = Ray::new(point(5., 1., 0.), vector(0., -1., 0.));
+        let mut clear = w.clone();
+        clear.caustics = None;
+        assert_eq!(color_at(&w, r, 5), color_at(&clear, r, 5));
+    }
+}
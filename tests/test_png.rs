@@ -0,0 +1,104 @@
+mod tests {
+    use std::io::Read;
+
+    use flate2::read::ZlibDecoder;
+    use tracer::canvas::{write_alpha, write_pixel, AlphaMode, Canvas};
+    use tracer::display::DisplayTransform;
+    use tracer::png::canvas_to_png;
+    use tracer::Color;
+
+    fn u32_at(data: &[u8], pos: usize) -> u32 {
+        u32::from_be_bytes(data[pos..pos + 4].try_into().unwrap())
+    }
+
+    // Chunk types and contents, checking each checksum on the way
+    fn chunks(data: &[u8]) -> Vec<(String, Vec<u8>)> {
+        assert_eq!(
+            data[..8],
+            [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n']
+        );
+        let mut pos = 8;
+        let mut result = Vec::new();
+        while pos < data.len() {
+            let length = u32_at(data, pos) as usize;
+            let body = &data[pos + 4..pos + 8 + length];
+            assert_eq!(u32_at(data, pos + 8 + length), crc32fast::hash(body));
+            let kind = String::from_utf8(body[..4].to_vec()).unwrap();
+            result.push((kind, body[4..].to_vec()));
+            pos += 12 + length;
+        }
+        result
+    }
+
+    // RGBA rows without their filter bytes
+    fn pixels(data: &[u8]) -> Vec<Vec<[u8; 4]>> {
+        let chunks = chunks(data);
+        let header = &chunks[0].1;
+        let width = u32_at(header, 0) as usize;
+        let mut raw = Vec::new();
+        ZlibDecoder::new(&chunks[1].1[..])
+            .read_to_end(&mut raw)
+            .unwrap();
+        raw.chunks(1 + width * 4)
+            .map(|row| {
+                assert_eq!(row[0], 0);
+                row[1..].chunks(4).map(|p| p.try_into().unwrap()).collect()
+            })
+            .collect()
+    }
+
+    fn canvas() -> Canvas {
+        let mut c = Canvas::new(3, 2);
+        write_pixel(&mut c, 0, 0, Color::new(1., 0., 0.));
+        // Half-covered white, stored premultiplied
+        write_pixel(&mut c, 1, 0, Color::new(0.5, 0.5, 0.5));
+        write_alpha(&mut c, 1, 0, 0.5);
+        write_alpha(&mut c, 2, 1, 0.);
+        c
+    }
+
+    #[test]
+    fn test_png_structure() -> Result<(), &'static str> {
+        let data = canvas_to_png(&canvas(), &DisplayTransform::default(), AlphaMode::Straight)?;
+        let chunks = chunks(&data);
+        let kinds: Vec<&str> = chunks.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(kinds, ["IHDR", "IDAT", "IEND"]);
+        assert_eq!(chunks[0].1, [0, 0, 0, 3, 0, 0, 0, 2, 8, 6, 0, 0, 0]);
+        assert!(canvas_to_png(
+            &Canvas::new(0, 4),
+            &DisplayTransform::default(),
+            AlphaMode::Straight
+        )
+        .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_png_straight_alpha() -> Result<(), &'static str> {
+        let data = canvas_to_png(&canvas(), &DisplayTransform::default(), AlphaMode::Straight)?;
+        let rows = pixels(&data);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0][0], [255, 0, 0, 255]);
+        assert_eq!(rows[0][1], [255, 255, 255, 128]);
+        assert_eq!(rows[1][2], [0, 0, 0, 0]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_png_premultiplied_alpha() -> Result<(), &'static str> {
+        let data = canvas_to_png(
+            &canvas(),
+            &DisplayTransform::default(),
+            AlphaMode::Premultiplied,
+        )?;
+        let rows = pixels(&data);
+        assert_eq!(rows[0][0], [255, 0, 0, 255]);
+        // Half of linear white encodes brighter than half of the encoded byte
+        let half = DisplayTransform::default().to_bytes(Color::new(0.5, 0.5, 0.5), 1, 0);
+        assert_eq!(rows[0][1][..3], half);
+        assert!(half[0] > 128);
+        assert_eq!(rows[0][1][3], 128);
+        assert_eq!(rows[1][2], [0, 0, 0, 0]);
+        Ok(())
+    }
+}
@@ -0,0 +1,221 @@
+mod tests {
+    use tracer::canvas::{alpha_at, pixel_at, write_alpha, write_pixel, Canvas};
+    use tracer::post::{apply_effects, PostEffect};
+    use tracer::Color;
+
+    fn flat(width: usize, height: usize, color: Color) -> Canvas {
+        let mut c = Canvas::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                write_pixel(&mut c, x, y, color);
+            }
+        }
+        c
+    }
+
+    // A dark canvas with one bright highlight in the middle
+    fn highlight(value: f32) -> Canvas {
+        let mut c = flat(21, 21, Color::new(0.1, 0.1, 0.1));
+        write_pixel(&mut c, 10, 10, Color::new(value, value, value));
+        c
+    }
+
+    fn bloom(levels: u32) -> PostEffect {
+        PostEffect::Bloom {
+            threshold: 1.,
+            intensity: 1.,
+            radius: 1.,
+            levels,
+        }
+    }
+
+    fn glare(streaks: u32) -> PostEffect {
+        PostEffect::Glare {
+            threshold: 1.,
+            intensity: 1.,
+            length: 3.,
+            streaks,
+            angle: 0.,
+        }
+    }
+
+    #[test]
+    fn test_bloom_ignores_light_below_threshold() {
+        let c = highlight(0.9);
+        let result = bloom(3).apply(&c);
+        assert_eq!(result.pixels, c.pixels);
+    }
+
+    #[test]
+    fn test_bloom_spreads_bright_highlights() {
+        let c = highlight(50.);
+        let result = bloom(1).apply(&c);
+        let near = pixel_at(&result, 11, 10).red;
+        let far = pixel_at(&result, 18, 10).red;
+        assert!(near > 1.);
+        assert!(near > far);
+        assert!((far - 0.1).abs() < 1e-3);
+        // The highlight itself isn't dimmed
+        assert!(pixel_at(&result, 10, 10).red >= 50.);
+
+        // Wider levels carry light further out
+        let wide = bloom(3).apply(&c);
+        assert!(pixel_at(&wide, 18, 10).red > far + 0.01);
+    }
+
+    #[test]
+    fn test_bloom_scales_stop_at_image_size() {
+        let c = highlight(50.);
+        // Every level past the image size blurs the same way
+        let wide = bloom(40).apply(&c);
+        let capped = bloom(4).apply(&c);
+        for (a, b) in wide
+            .pixels
+            .iter()
+            .flatten()
+            .zip(capped.pixels.iter().flatten())
+        {
+            assert!(a.red.is_finite());
+            assert!(a.red >= 0.1 && b.red >= 0.1);
+        }
+        // Many wide levels spread the same light more evenly than a few
+        let spread = |image: &Canvas| pixel_at(image, 10, 10).red - pixel_at(image, 0, 0).red;
+        assert!(spread(&wide) < spread(&capped));
+    }
+
+    #[test]
+    fn test_bloom_is_emissive_over_transparency() {
+        let mut c = highlight(50.);
+        write_pixel(&mut c, 11, 10, Color::new(0., 0., 0.));
+        write_alpha(&mut c, 11, 10, 0.);
+        let result = bloom(1).apply(&c);
+        assert!(pixel_at(&result, 11, 10).red > 0.);
+        assert_eq!(alpha_at(&result, 11, 10), 0.);
+    }
+
+    #[test]
+    fn test_bloom_keeps_hue() {
+        let mut c = flat(9, 9, Color::new(0., 0., 0.));
+        write_pixel(&mut c, 4, 4, Color::new(8., 4., 0.));
+        let result = bloom(1).apply(&c);
+        let p = pixel_at(&result, 5, 4);
+        assert!((p.green / p.red - 0.5).abs() < 1e-4);
+        assert_eq!(p.blue, 0.);
+    }
+
+    #[test]
+    fn test_glare_streaks() {
+        let c = highlight(50.);
+        let cross = glare(2).apply(&c);
+        let background = 0.1;
+        assert!(pixel_at(&cross, 14, 10).red > background + 0.01);
+        assert!(pixel_at(&cross, 10, 6).red > background + 0.01);
+        assert!(pixel_at(&cross, 6, 10).red > background + 0.01);
+        // Nothing between the arms
+        assert_eq!(pixel_at(&cross, 13, 13).red, background);
+        // Streaks fade with distance
+        assert!(pixel_at(&cross, 11, 10).red > pixel_at(&cross, 14, 10).red);
+
+        let rotated = PostEffect::Glare {
+            threshold: 1.,
+            intensity: 1.,
+            length: 3.,
+            streaks: 2,
+            angle: std::f32::consts::FRAC_PI_4,
+        }
+        .apply(&c);
+        assert!(pixel_at(&rotated, 13, 13).red > background + 0.01);
+        assert_eq!(pixel_at(&rotated, 14, 10).red, background);
+    }
+
+    #[test]
+    fn test_vignette() {
+        let c = flat(20, 10, Color::new(1., 1., 1.));
+        let result = PostEffect::Vignette { strength: 0.5 }.apply(&c);
+        let center = pixel_at(&result, 10, 5).red;
+        let corner = pixel_at(&result, 0, 0).red;
+        assert!(center > 0.99);
+        assert!(corner < 0.6 && corner > 0.5);
+        let none = PostEffect::Vignette { strength: 0. }.apply(&c);
+        assert_eq!(none.pixels, c.pixels);
+    }
+
+    #[test]
+    fn test_chromatic_aberration() {
+        // Brightness rising to the right in every channel
+        let mut c = Canvas::new(21, 3);
+        for y in 0..3 {
+            for x in 0..21 {
+                let v = x as f32 / 20.;
+                write_pixel(&mut c, x, y, Color::new(v, v, v));
+            }
+        }
+        let result = PostEffect::ChromaticAberration { amount: 0.1 }.apply(&c);
+        // Red is pushed outwards, so the right side shows red from nearer the center
+        let right = pixel_at(&result, 18, 1);
+        assert!(right.red < right.green);
+        assert!(right.blue > right.green);
+        assert_eq!(right.green, pixel_at(&c, 18, 1).green);
+        // The center doesn't move
+        assert_eq!(pixel_at(&result, 10, 1), pixel_at(&c, 10, 1));
+
+        let flat_image = flat(8, 8, Color::new(0.2, 0.4, 0.6));
+        let unchanged = PostEffect::ChromaticAberration { amount: 0.2 }.apply(&flat_image);
+        assert_eq!(unchanged.pixels, flat_image.pixels);
+    }
+
+    #[test]
+    fn test_chromatic_aberration_stays_inside_coverage() {
+        // Opaque white on the left, transparent on the right
+        let mut c = Canvas::new(21, 3);
+        for y in 0..3 {
+            for x in 0..21 {
+                if x < 10 {
+                    write_pixel(&mut c, x, y, Color::new(1., 1., 1.));
+                } else {
+                    write_alpha(&mut c, x, y, 0.);
+                }
+            }
+        }
+        let result = PostEffect::ChromaticAberration { amount: 0.2 }.apply(&c);
+        for y in 0..3 {
+            for x in 10..21 {
+                assert_eq!(pixel_at(&result, x, y), Color::new(0., 0., 0.));
+                assert_eq!(alpha_at(&result, x, y), 0.);
+            }
+        }
+        // Inside the coverage, shifted color stays straight white
+        assert_eq!(pixel_at(&result, 2, 1), Color::new(1., 1., 1.));
+    }
+
+    #[test]
+    fn test_effects_chain_in_order() {
+        let c = highlight(50.);
+        assert_eq!(apply_effects(&c, &[]).pixels, c.pixels);
+
+        let vignette = PostEffect::Vignette { strength: 1. };
+        let bloom_first = apply_effects(&c, &[bloom(2), vignette]);
+        let vignette_first = apply_effects(&c, &[vignette, bloom(2)]);
+        assert_eq!(
+            bloom_first.pixels,
+            vignette.apply(&bloom(2).apply(&c)).pixels
+        );
+        assert_ne!(bloom_first.pixels, vignette_first.pixels);
+    }
+
+    #[test]
+    fn test_effects_keep_alpha() {
+        let mut c = highlight(50.);
+        write_alpha(&mut c, 3, 4, 0.25);
+        for effect in [
+            bloom(2),
+            glare(3),
+            PostEffect::Vignette { strength: 0.3 },
+            PostEffect::ChromaticAberration { amount: 0.05 },
+        ] {
+            let result = effect.apply(&c);
+            assert_eq!(alpha_at(&result, 3, 4), 0.25);
+            assert_eq!(alpha_at(&result, 0, 0), 1.);
+        }
+    }
+}
@@ -0,0 +1,147 @@
+mod tests {
+    use rand::{rngs::SmallRng, SeedableRng};
+    use tracer::sampling::{
+        concentric_sample_disk, cosine_sample_hemisphere, pixel_samples, sample_polygon,
+        uniform_sample_sphere, Distribution1D, Distribution2D, SamplePattern,
+    };
+    use tracer::{dot, magnitude, normalize, vector};
+
+    #[test]
+    fn test_concentric_disk_bounds() {
+        assert_eq!(concentric_sample_disk(0.5, 0.5), (0., 0.));
+        let (x, y) = concentric_sample_disk(1., 0.5);
+        assert!((x - 1.).abs() < 1e-6 && y.abs() < 1e-6);
+        for i in 0..=10 {
+            for j in 0..=10 {
+                let (x, y) = concentric_sample_disk(i as f32 / 10., j as f32 / 10.);
+                assert!(x * x + y * y <= 1. + 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn test_polygon_sample_inside_unit_circle() {
+        for i in 0..10 {
+            for j in 0..10 {
+                let (x, y) = sample_polygon(5, i as f32 / 10., j as f32 / 10.);
+                assert!(x * x + y * y <= 1. + 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn test_grid_samples() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let samples = pixel_samples(SamplePattern::Grid, 4, &mut rng);
+        assert_eq!(
+            samples,
+            vec![(0.25, 0.25), (0.75, 0.25), (0.25, 0.75), (0.75, 0.75)]
+        );
+
+        let samples = pixel_samples(SamplePattern::Grid, 1, &mut rng);
+        assert_eq!(samples, vec![(0.5, 0.5)]);
+    }
+
+    #[test]
+    fn test_jittered_samples_rounded_to_square() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let samples = pixel_samples(SamplePattern::Jittered, 5, &mut rng);
+        assert_eq!(samples.len(), 9);
+        for (i, (u, v)) in samples.iter().enumerate() {
+            let (sx, sy) = ((i % 3) as f32, (i / 3) as f32);
+            assert!(*u >= sx / 3. && *u < (sx + 1.) / 3.);
+            assert!(*v >= sy / 3. && *v < (sy + 1.) / 3.);
+        }
+    }
+
+    #[test]
+    fn test_random_samples() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let samples = pixel_samples(SamplePattern::Random, 5, &mut rng);
+        assert_eq!(samples.len(), 5);
+        assert!(samples
+            .iter()
+            .all(|(u, v)| (0. ..1.).contains(u) && (0. ..1.).contains(v)));
+    }
+
+    #[test]
+    fn test_cosine_hemisphere_samples() {
+        let normal = normalize(vector(1., 2., -0.5));
+        let mut mean = vector(0., 0., 0.);
+        for i in 0..16 {
+            for j in 0..16 {
+                let d = cosine_sample_hemisphere(
+                    normal,
+                    (i as f32 + 0.5) / 16.,
+                    (j as f32 + 0.5) / 16.,
+                );
+                assert!((magnitude(d) - 1.).abs() < 1e-5);
+                assert!(dot(d, normal) >= 0.);
+                mean = mean + d;
+            }
+        }
+        // The average direction of a cosine lobe leans towards the normal
+        assert!(dot(normalize(mean), normal) > 0.99);
+    }
+
+    #[test]
+    fn test_uniform_sphere_samples() {
+        let mut mean = vector(0., 0., 0.);
+        for i in 0..16 {
+            for j in 0..16 {
+                let d = uniform_sample_sphere((i as f32 + 0.5) / 16., (j as f32 + 0.5) / 16.);
+                assert!((magnitude(d) - 1.).abs() < 1e-5);
+                mean = mean + d;
+            }
+        }
+        assert!(magnitude(mean) / 256. < 1e-3);
+    }
+
+    #[test]
+    fn test_distribution_1d_follows_function() {
+        let d = Distribution1D::new(vec![1., 3.]);
+        assert_eq!(d.integral, 2.);
+        assert_eq!(d.pdf(0.25), 0.5);
+        assert_eq!(d.pdf(0.75), 1.5);
+
+        // A quarter of the mass lies in the first half
+        let (x, pdf, offset) = d.sample(0.125);
+        assert_eq!((x, pdf, offset), (0.25, 0.5, 0));
+        let (x, pdf, offset) = d.sample(0.625);
+        assert_eq!((x, pdf, offset), (0.75, 1.5, 1));
+    }
+
+    #[test]
+    fn test_distribution_1d_skips_empty_segments() {
+        let d = Distribution1D::new(vec![0., 2., 0.]);
+        for i in 0..10 {
+            let (x, pdf, offset) = d.sample(i as f32 / 10.);
+            assert_eq!(offset, 1);
+            assert!((1. / 3. ..2. / 3.).contains(&x));
+            assert_eq!(pdf, 3.);
+        }
+    }
+
+    #[test]
+    fn test_distribution_1d_all_zero_is_uniform() {
+        let d = Distribution1D::new(vec![0., 0., 0., 0.]);
+        let (x, pdf, _) = d.sample(0.6);
+        assert!((x - 0.6).abs() < 1e-6);
+        assert_eq!(pdf, 1.);
+    }
+
+    #[test]
+    fn test_distribution_2d_pdf_matches_samples() {
+        let d = Distribution2D::new(vec![vec![1., 0.], vec![2., 5.]]);
+        for i in 0..8 {
+            for j in 0..8 {
+                let ((u, v), pdf) = d.sample((i as f32 + 0.5) / 8., (j as f32 + 0.5) / 8.);
+                assert!((pdf - d.pdf(u, v)).abs() < 1e-5);
+                assert!(pdf > 0.);
+            }
+        }
+        // The empty cell is never chosen and its density is zero
+        assert_eq!(d.pdf(0.75, 0.25), 0.);
+        assert!((d.pdf(0.75, 0.75) - 2.5).abs() < 1e-5);
+    }
+}
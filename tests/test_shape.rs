@@ -1,5 +1,5 @@
 mod tests {
-    use std::f32::consts::PI;
+    use std::f32::consts::{FRAC_1_SQRT_2, PI};
 
     use tracer::materials::Material;
     use tracer::matrix::Matrix;
@@ -7,9 +7,9 @@ mod tests {
     use tracer::shape::{intersect, normal_at, Shape};
     use tracer::transforms::{rotation_z, scaling, translation};
     use tracer::Tuple;
-    use tracer::{point, vector};
+    use tracer::{magnitude, point, vector};
 
-    #[derive(Clone, Copy)]
+    #[derive(Clone)]
     struct TestShape {
         material: Material,
         saved_ray: Ray,
@@ -80,9 +80,11 @@ mod tests {
     #[test]
     fn test_assign_material() {
         let mut s = TestShape::new();
-        let mut m = Material::default();
-        m.ambient = 1.;
-        s.material = m;
+        let m = Material {
+            ambient: 1.,
+            ..Material::default()
+        };
+        s.material = m.clone();
         assert_eq!(s.material, m);
     }
 
@@ -92,7 +94,7 @@ mod tests {
         let r = Ray::new(point(0., 0., -5.), vector(0., 0., 1.));
         let mut s = TestShape::new();
         s.set_transform(scaling(2., 2., 2.));
-        let _xs = intersect(s, r);
+        let _xs = intersect(s.clone(), r);
         assert_eq!(s.saved_ray.origin, point(0., 0., -2.5));
         assert_eq!(s.saved_ray.direction, vector(0., 0., 0.5));
     }
@@ -103,7 +105,7 @@ mod tests {
         let r = Ray::new(point(0., 0., -5.), vector(0., 0., 1.));
         let mut s = TestShape::new();
         s.set_transform(translation(5., 0., 0.));
-        let _xs = intersect(s, r);
+        let _xs = intersect(s.clone(), r);
         assert_eq!(s.saved_ray.origin, point(-5., 0., -5.));
         assert_eq!(s.saved_ray.direction, vector(0., 0., 1.));
     }
@@ -112,8 +114,9 @@ mod tests {
     fn test_normal_translated_shape() {
         let mut s = TestShape::new();
         s.set_transform(translation(0., 1., 0.));
-        let n = normal_at(s, point(0., 1.70711, -0.70711));
-        assert_eq!(n, vector(0., 0.7071068, -0.70710677));
+        let n = normal_at(s, point(0., 1. + FRAC_1_SQRT_2, -FRAC_1_SQRT_2));
+        let diff = n - vector(0., FRAC_1_SQRT_2, -FRAC_1_SQRT_2);
+        assert!(magnitude(diff) < 1e-6);
     }
 
     #[test]
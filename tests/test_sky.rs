@@ -0,0 +1,129 @@
+mod tests {
+    use tracer::background::Background;
+    use tracer::lights::{lighting, lighting_directional, DirectionalLight, PointLight};
+    use tracer::materials::Material;
+    use tracer::plane::Plane;
+    use tracer::ray::Ray;
+    use tracer::sky::PreethamSky;
+    use tracer::sphere::Sphere;
+    use tracer::transforms::translation;
+    use tracer::world::{color_at, is_sun_shadowed, ShapeEnum, World};
+    use tracer::{normalize, point, vector, Color};
+
+    #[test]
+    fn test_zenith_luminance_matches_intensity() {
+        let mut sky = PreethamSky::new(vector(0., 1., 1.), 3.);
+        sky.intensity = 2.;
+        let zenith = sky.radiance(vector(0., 1., 0.));
+        assert!((zenith.luminance() - 2.).abs() < 0.02);
+    }
+
+    #[test]
+    fn test_clear_sky_is_blue() {
+        let sky = PreethamSky::new(vector(0., 1., 1.), 2.5);
+        let zenith = sky.radiance(vector(0., 1., 0.));
+        assert!(zenith.blue > zenith.red);
+    }
+
+    #[test]
+    fn test_sky_brightest_around_sun() {
+        let sky = PreethamSky::new(vector(0., 0.5, 1.), 3.);
+        let near_sun = sky.radiance(vector(0., 0.55, 1.));
+        let opposite = sky.radiance(vector(0., 0.5, -1.));
+        assert!(near_sun.luminance() > 2. * opposite.luminance());
+    }
+
+    #[test]
+    fn test_below_horizon_repeats_horizon() {
+        let sky = PreethamSky::new(vector(1., 1., 0.), 3.);
+        assert_eq!(
+            sky.radiance(vector(0., -0.5, 1.)),
+            sky.radiance(vector(0., -0.9, 1.))
+        );
+    }
+
+    #[test]
+    fn test_sun_color_reddens_towards_horizon() {
+        let high = PreethamSky::new(vector(0., 1., 0.2), 3.).sun_color();
+        let low = PreethamSky::new(vector(0., 0.05, 1.), 3.).sun_color();
+        assert!((high.luminance() - 1.).abs() < 1e-4);
+        assert!((low.luminance() - 1.).abs() < 1e-4);
+        assert!(low.red / low.blue > high.red / high.blue);
+        assert!(low.red > low.blue);
+
+        let set = PreethamSky::new(vector(0., -0.1, 1.), 3.).sun_color();
+        assert_eq!(set, Color::new(0., 0., 0.));
+    }
+
+    #[test]
+    fn test_sun_light_matches_sky() {
+        let sky = PreethamSky::new(vector(1., 1., 0.), 4.);
+        let sun = sky.sun_light(3.);
+        let expected = normalize(vector(1., 1., 0.));
+        assert!((sun.direction - expected).x.abs() < 1e-6);
+        assert!((sun.direction - expected).y.abs() < 1e-6);
+        assert_eq!(sun.intensity, sky.sun_color() * 3.);
+    }
+
+    #[test]
+    fn test_sky_background_for_missed_rays() {
+        let sky = PreethamSky::new(vector(0., 1., 1.), 3.);
+        let w = World {
+            background: Background::Sky(sky),
+            ..World::default()
+        };
+        let r = Ray::new(point(0., 0., -5.), vector(0., 1., 0.));
+        assert_eq!(color_at(&w, r, 5), sky.radiance(vector(0., 1., 0.)));
+    }
+
+    #[test]
+    fn test_directional_light_matches_aligned_point_light() {
+        let m = Material::default();
+        let obj = ShapeEnum::Sphere(Sphere::default());
+        let position = point(0., 0., 0.);
+        let eyev = vector(0., 0., -1.);
+        let normalv = vector(0., 0., -1.);
+        let white = Color::new(1., 1., 1.);
+        let point_light = PointLight::new(point(0., 0., -10.), white);
+        let sun = DirectionalLight::new(vector(0., 0., -3.), white);
+        assert_eq!(
+            lighting_directional(&m, &obj, sun, position, eyev, normalv, false, 1.),
+            lighting(
+                m.clone(),
+                obj.clone(),
+                point_light,
+                position,
+                eyev,
+                normalv,
+                false
+            )
+        );
+        assert_eq!(
+            lighting_directional(&m, &obj, sun, position, eyev, normalv, false, 1.),
+            Color::new(1.9, 1.9, 1.9)
+        );
+    }
+
+    #[test]
+    fn test_sun_lights_and_shadows_world() {
+        let mut w = World::new();
+        w.objects = vec![
+            ShapeEnum::Plane(Plane::default()),
+            ShapeEnum::Sphere(Sphere {
+                transform: translation(0., 2., 0.),
+                ..Sphere::default()
+            }),
+        ];
+        w.sun = Some(DirectionalLight::new(
+            vector(0., 1., 0.),
+            Color::new(1., 1., 1.),
+        ));
+        assert!(is_sun_shadowed(&w, point(0., 0.01, 0.)));
+        assert!(!is_sun_shadowed(&w, point(3., 0.01, 0.)));
+
+        let lit = Ray::new(point(3., 1., 0.), vector(0., -1., 0.));
+        let shaded = Ray::new(point(0., 0.5, 0.), vector(0., -1., 0.));
+        assert_eq!(color_at(&w, lit, 5), Color::new(1.9, 1.9, 1.9));
+        assert_eq!(color_at(&w, shaded, 5), Color::new(0.1, 0.1, 0.1));
+    }
+}
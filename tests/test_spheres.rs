@@ -1,5 +1,5 @@
 mod tests {
-    use std::f32::consts::PI;
+    use std::f32::consts::{FRAC_1_SQRT_2, PI};
 
     use tracer::materials::Material;
     use tracer::matrix::Matrix;
@@ -55,7 +55,7 @@ mod tests {
         let s = Sphere::default();
         let xs = s.local_intersect(r);
         assert_eq!(xs.len(), 2);
-        assert_eq!(xs[0].object, ShapeEnum::Sphere(s));
+        assert_eq!(xs[0].object, ShapeEnum::Sphere(s.clone()));
         assert_eq!(xs[1].object, ShapeEnum::Sphere(s));
     }
 
@@ -143,8 +143,8 @@ mod tests {
     fn test_normal_translated_sphere() {
         let mut s = Sphere::default();
         s.set_transform(translation(0., 1., 0.));
-        let n = normal_at(s, point(0., 1.70711, -0.70711));
-        let diff = n - vector(0., 0.70711, -0.70711);
+        let n = normal_at(s, point(0., 1. + FRAC_1_SQRT_2, -FRAC_1_SQRT_2));
+        let diff = n - vector(0., FRAC_1_SQRT_2, -FRAC_1_SQRT_2);
         assert!(magnitude(diff) < 1e-5);
     }
 
@@ -168,9 +168,11 @@ mod tests {
     #[test]
     fn test_assign_material() {
         let mut s = Sphere::default();
-        let mut m = Material::default();
-        m.ambient = 1.;
-        s.material = m;
+        let m = Material {
+            ambient: 1.,
+            ..Material::default()
+        };
+        s.material = m.clone();
         assert_eq!(s.material, m);
     }
 
@@ -0,0 +1,122 @@
+mod tests {
+    use std::f32::consts::FRAC_1_SQRT_2;
+    use std::sync::Arc;
+
+    use tracer::canvas::{canvas_from_ppm, write_pixel, Canvas};
+    use tracer::patterns::{ImagePattern, Pattern, PatternType};
+    use tracer::uv::{
+        cube_uv, cylindrical_map, face_from_point, planar_map, spherical_map, uv_sample, CubeFace,
+    };
+    use tracer::{point, Color};
+
+    fn assert_uv(actual: (f32, f32), expected: (f32, f32)) {
+        assert!((actual.0 - expected.0).abs() < 1e-4, "{:?}", actual);
+        assert!((actual.1 - expected.1).abs() < 1e-4, "{:?}", actual);
+    }
+
+    #[test]
+    fn test_spherical_map() {
+        assert_uv(spherical_map(point(0., 0., -1.)), (0., 0.5));
+        assert_uv(spherical_map(point(1., 0., 0.)), (0.25, 0.5));
+        assert_uv(spherical_map(point(0., 0., 1.)), (0.5, 0.5));
+        assert_uv(spherical_map(point(-1., 0., 0.)), (0.75, 0.5));
+        assert_uv(spherical_map(point(0., 1., 0.)), (0.5, 1.));
+        assert_uv(spherical_map(point(0., -1., 0.)), (0.5, 0.));
+    }
+
+    #[test]
+    fn test_planar_map() {
+        assert_uv(planar_map(point(0.25, 0., 0.5)), (0.25, 0.5));
+        assert_uv(planar_map(point(0.25, 0., -0.25)), (0.25, 0.75));
+        assert_uv(planar_map(point(1.25, 0., 0.5)), (0.25, 0.5));
+        assert_uv(planar_map(point(-0.25, 0.5, -1.75)), (0.75, 0.25));
+    }
+
+    #[test]
+    fn test_cylindrical_map() {
+        assert_uv(cylindrical_map(point(0., 0., -1.)), (0., 0.));
+        assert_uv(cylindrical_map(point(0., 0.5, -1.)), (0., 0.5));
+        assert_uv(
+            cylindrical_map(point(FRAC_1_SQRT_2, 0.5, -FRAC_1_SQRT_2)),
+            (0.125, 0.5),
+        );
+        assert_uv(
+            cylindrical_map(point(-FRAC_1_SQRT_2, 0.25, FRAC_1_SQRT_2)),
+            (0.625, 0.25),
+        );
+    }
+
+    #[test]
+    fn test_cube_faces() {
+        assert_eq!(face_from_point(point(-1., 0.5, -0.25)), CubeFace::Left);
+        assert_eq!(face_from_point(point(1.1, -0.75, 0.8)), CubeFace::Right);
+        assert_eq!(face_from_point(point(0.1, 0.6, 0.9)), CubeFace::Front);
+        assert_eq!(face_from_point(point(-0.7, 0., -2.)), CubeFace::Back);
+        assert_eq!(face_from_point(point(0.5, 1., 0.9)), CubeFace::Up);
+        assert_eq!(face_from_point(point(-0.2, -1.3, 1.1)), CubeFace::Down);
+    }
+
+    #[test]
+    fn test_cube_face_uv() {
+        assert_uv(cube_uv(CubeFace::Front, point(-0.5, 0.5, 1.)), (0.25, 0.75));
+        assert_uv(cube_uv(CubeFace::Back, point(0.5, -0.5, -1.)), (0.25, 0.25));
+        assert_uv(cube_uv(CubeFace::Up, point(-0.5, 1., -0.5)), (0.25, 0.75));
+        assert_uv(cube_uv(CubeFace::Down, point(0.5, -1., 0.5)), (0.75, 0.75));
+    }
+
+    #[test]
+    fn test_uv_sample_bilinear() {
+        let mut canvas = Canvas::new(2, 1);
+        write_pixel(&mut canvas, 0, 0, Color::new(0., 0., 0.));
+        write_pixel(&mut canvas, 1, 0, Color::new(1., 1., 1.));
+
+        assert_eq!(uv_sample(&canvas, 0.25, 0.5), Color::new(0., 0., 0.));
+        assert_eq!(uv_sample(&canvas, 0.5, 0.5), Color::new(0.5, 0.5, 0.5));
+        assert_eq!(uv_sample(&canvas, 0.75, 0.5), Color::new(1., 1., 1.));
+    }
+
+    #[test]
+    fn test_empty_image_pattern_is_black() {
+        let image = ImagePattern::new(Arc::new(Canvas::new(0, 0)), tracer::uv::UvMapping::Planar);
+        let mut pattern = Pattern::new(PatternType::Image(image));
+        assert_eq!(
+            pattern.pattern_at(point(0.25, 0., 0.75)),
+            Color::new(0., 0., 0.)
+        );
+        assert_eq!(
+            uv_sample(&Canvas::new(3, 0), 0.5, 0.5),
+            Color::new(0., 0., 0.)
+        );
+    }
+
+    #[test]
+    fn test_image_pattern() -> Result<(), &'static str> {
+        let ppm = "P3\n2 2\n255\n255 0 0  0 255 0\n0 0 255  255 255 255\n";
+        let canvas = Arc::new(canvas_from_ppm(ppm)?);
+        let image = ImagePattern::new(canvas, tracer::uv::UvMapping::Planar);
+        let mut pattern = Pattern::new(PatternType::Image(image));
+
+        assert_eq!(
+            pattern.pattern_at(point(0.25, 0., 0.75)),
+            Color::new(1., 0., 0.)
+        );
+        assert_eq!(
+            pattern.pattern_at(point(0.75, 0., 0.25)),
+            Color::new(1., 1., 1.)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_image_pattern_releases_texture() {
+        let canvas = Arc::new(Canvas::new(2, 2));
+        let image = ImagePattern::new(canvas.clone(), tracer::uv::UvMapping::Planar);
+        let pattern = Pattern::new(PatternType::Image(image));
+        let copy = pattern.clone();
+        assert_eq!(Arc::strong_count(&canvas), 3);
+
+        drop(pattern);
+        drop(copy);
+        assert_eq!(Arc::strong_count(&canvas), 1);
+    }
+}
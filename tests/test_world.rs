@@ -1,10 +1,11 @@
 mod tests {
     use tracer::{
+        background::Background,
         intersections::prepare_computations,
         intersections::Intersection,
         lights::PointLight,
         materials::Material,
-        patterns::{Pattern, PatternType},
+        patterns::{Pattern, PatternType, StripePattern},
         plane::Plane,
         point,
         ray::Ray,
@@ -29,15 +30,20 @@ mod tests {
     #[test]
     fn test_default_world() {
         let light = PointLight::new(point(-10., 10., -10.), Color::new(1., 1., 1.));
-        let mut s1 = Sphere::default();
-        let mut m = Material::default();
-        m.color = Color::new(0.8, 1.0, 0.6);
-        m.diffuse = 0.7;
-        m.specular = 0.2;
-        s1.material = m;
+        let s1 = Sphere {
+            material: Material {
+                color: Color::new(0.8, 1.0, 0.6),
+                diffuse: 0.7,
+                specular: 0.2,
+                ..Material::default()
+            },
+            ..Sphere::default()
+        };
 
-        let mut s2 = Sphere::default();
-        s2.transform = scaling(0.5, 0.5, 0.5);
+        let s2 = Sphere {
+            transform: scaling(0.5, 0.5, 0.5),
+            ..Sphere::default()
+        };
 
         let w = World::default();
         assert_eq!(w.light.unwrap(), light);
@@ -62,21 +68,23 @@ mod tests {
     fn test_shade_intersection() {
         let w = World::default();
         let r = Ray::new(point(0., 0., -5.), vector(0., 0., 1.));
-        let shape = w.objects[0];
+        let shape = w.objects[0].clone();
         let i = Intersection::new(4., shape);
-        let comps = prepare_computations(i, r, vec![i]);
+        let comps = prepare_computations(i.clone(), r, vec![i]);
         let c = shade_hit(&w, comps, 5);
         assert_eq!(c, Color::new(0.38066, 0.47583, 0.2855))
     }
 
     #[test]
     fn test_shade_intersection_inside() {
-        let mut w = World::default();
-        w.light = Some(PointLight::new(point(0., 0.25, 0.), Color::new(1., 1., 1.)));
+        let w = World {
+            light: Some(PointLight::new(point(0., 0.25, 0.), Color::new(1., 1., 1.))),
+            ..World::default()
+        };
         let r = Ray::new(point(0., 0., 0.), vector(0., 0., 1.));
-        let shape = w.objects[1];
+        let shape = w.objects[1].clone();
         let i = Intersection::new(0.5, shape);
-        let comps = prepare_computations(i, r, vec![i]);
+        let comps = prepare_computations(i.clone(), r, vec![i]);
         let c = shade_hit(&w, comps, 5);
         assert_eq!(c, Color::new(0.9049522, 0.9049522, 0.9049522));
     }
@@ -112,7 +120,7 @@ mod tests {
         let r = Ray::new(point(0., 0., 0.75), vector(0., 0., -1.));
         let c = color_at(&w, r, 5);
 
-        match w.objects[1] {
+        match &w.objects[1] {
             ShapeEnum::Sphere(sphere) => {
                 assert_eq!(c, sphere.material.color);
             }
@@ -155,10 +163,10 @@ mod tests {
         let s1 = Sphere::default();
         let mut s2 = Sphere::default();
         s2.set_transform(translation(0., 0., 10.));
-        w.objects = vec![ShapeEnum::Sphere(s1), ShapeEnum::Sphere(s2)];
+        w.objects = vec![ShapeEnum::Sphere(s1), ShapeEnum::Sphere(s2.clone())];
         let r = Ray::new(point(0., 0., 5.), vector(0., 0., 1.));
         let i = Intersection::new(4., ShapeEnum::Sphere(s2));
-        let comps = prepare_computations(i, r, vec![i]);
+        let comps = prepare_computations(i.clone(), r, vec![i]);
         let c = shade_hit(&w, comps, 5);
         assert_eq!(c, Color::new(0.1, 0.1, 0.1));
     }
@@ -169,7 +177,7 @@ mod tests {
         let mut shape = Sphere::default();
         shape.set_transform(translation(0., 0., 1.));
         let i = Intersection::new(5., ShapeEnum::Sphere(shape));
-        let comps = prepare_computations(i, r, vec![i]);
+        let comps = prepare_computations(i.clone(), r, vec![i]);
         assert!(comps.over_point.z < -f32::EPSILON / 2.);
         assert!(comps.point.z > comps.over_point.z)
     }
@@ -178,13 +186,28 @@ mod tests {
     fn test_non_reflective_surface() {
         let w = World::default();
         let r = Ray::new(point(0., 0., 0.), vector(0., 0., 1.));
-        let mut shape = match w.objects[1] {
+        let mut shape = match w.objects[1].clone() {
             ShapeEnum::Sphere(sphere) => sphere,
             _ => panic!("Not a sphere"),
         };
         shape.material.ambient = 1.;
-        let i = Intersection::new(1., w.objects[1]);
-        let comps = prepare_computations(i, r, vec![i]);
+        let i = Intersection::new(1., w.objects[1].clone());
+        let comps = prepare_computations(i.clone(), r, vec![i]);
+        let color = reflected_color(&w, comps, 5);
+        assert_eq!(color, Color::new(0., 0., 0.));
+    }
+
+    #[test]
+    fn test_non_reflective_surface_traces_no_reflection() {
+        // Scaling an infinite reflection by zero would give NaN
+        let w = World {
+            background: Background::Color(Color::new(f32::INFINITY, f32::INFINITY, f32::INFINITY)),
+            ..World::new()
+        };
+        let shape = ShapeEnum::Plane(Plane::default());
+        let r = Ray::new(point(0., 1., -1.), vector(0., -1., 0.));
+        let i = Intersection::new(1., shape);
+        let comps = prepare_computations(i.clone(), r, vec![i]);
         let color = reflected_color(&w, comps, 5);
         assert_eq!(color, Color::new(0., 0., 0.));
     }
@@ -192,20 +215,21 @@ mod tests {
     #[test]
     fn test_reflective_surface() {
         let mut w = World::default();
-        let mut material = Material::default();
-        material.reflective = 0.5;
         let shape = ShapeEnum::Plane(Plane {
             transform: translation(0., -1., 0.),
-            material,
+            material: Material {
+                reflective: 0.5,
+                ..Material::default()
+            },
         });
-        w.objects.push(shape);
+        w.objects.push(shape.clone());
 
         let r = Ray::new(
             point(0., 0., -3.),
             vector(0., -f32::sqrt(2.) / 2., f32::sqrt(2.) / 2.),
         );
         let i = Intersection::new(f32::sqrt(2.), shape);
-        let comps = prepare_computations(i, r, vec![i]);
+        let comps = prepare_computations(i.clone(), r, vec![i]);
         let color = reflected_color(&w, comps, 5);
         assert_eq!(color, Color::new(0.19034664, 0.23793328, 0.14275998));
     }
@@ -213,44 +237,47 @@ mod tests {
     #[test]
     fn test_shade_hit_reflective() {
         let mut w = World::default();
-        let mut material = Material::default();
-        material.reflective = 0.5;
         let shape = ShapeEnum::Plane(Plane {
             transform: translation(0., -1., 0.),
-            material,
+            material: Material {
+                reflective: 0.5,
+                ..Material::default()
+            },
         });
-        w.objects.push(shape);
+        w.objects.push(shape.clone());
 
         let r = Ray::new(
             point(0., 0., -3.),
             vector(0., -f32::sqrt(2.) / 2., f32::sqrt(2.) / 2.),
         );
         let i = Intersection::new(f32::sqrt(2.), shape);
-        let comps = prepare_computations(i, r, vec![i]);
+        let comps = prepare_computations(i.clone(), r, vec![i]);
         let color = shade_hit(&w, comps, 5);
         assert_eq!(color, Color::new(0.87677, 0.92436, 0.82918));
     }
 
     #[test]
     fn test_color_reflective_surfaces() {
-        let mut w = World::default();
-        w.light = Some(PointLight::new(point(0., 0., 0.), Color::new(1., 1., 1.)));
-        let mut lower = Plane::default();
-        let mut material = Material::default();
-        material.reflective = 1.;
-        let transform = translation(0., -1., 0.);
-        lower.set_transform(transform);
-        lower.material = material;
-        let lower = ShapeEnum::Plane(lower);
+        let mut w = World {
+            light: Some(PointLight::new(point(0., 0., 0.), Color::new(1., 1., 1.))),
+            ..World::default()
+        };
+        let lower = ShapeEnum::Plane(Plane {
+            transform: translation(0., -1., 0.),
+            material: Material {
+                reflective: 1.,
+                ..Material::default()
+            },
+        });
         w.objects.push(lower);
 
-        let mut upper = Plane::default();
-        let mut material = Material::default();
-        material.reflective = 1.;
-        let transform = translation(0., 1., 0.);
-        upper.set_transform(transform);
-        upper.material = material;
-        let upper = ShapeEnum::Plane(upper);
+        let upper = ShapeEnum::Plane(Plane {
+            transform: translation(0., 1., 0.),
+            material: Material {
+                reflective: 1.,
+                ..Material::default()
+            },
+        });
         w.objects.push(upper);
 
         let r = Ray::new(point(0., 0., 0.), vector(0., 1., 0.));
@@ -260,21 +287,21 @@ mod tests {
     #[test]
     fn test_reflected_color_max_depth() {
         let mut w = World::default();
-        let mut shape = Plane::default();
-        let mut material = Material::default();
-        material.reflective = 0.5;
-        let transform = translation(0., -1., 0.);
-        shape.set_transform(transform);
-        shape.material = material;
-        let shape = ShapeEnum::Plane(shape);
-        w.objects.push(shape);
+        let shape = ShapeEnum::Plane(Plane {
+            transform: translation(0., -1., 0.),
+            material: Material {
+                reflective: 0.5,
+                ..Material::default()
+            },
+        });
+        w.objects.push(shape.clone());
 
         let r = Ray::new(
             point(0., 0., -3.),
             vector(0., -f32::sqrt(2.) / 2., f32::sqrt(2.) / 2.),
         );
         let i = Intersection::new(f32::sqrt(2.), shape);
-        let comps = prepare_computations(i, r, vec![i]);
+        let comps = prepare_computations(i.clone(), r, vec![i]);
         let color = reflected_color(&w, comps, 0);
         assert_eq!(color, Color::new(0., 0., 0.));
     }
@@ -284,8 +311,11 @@ mod tests {
         let w = World::default();
         let shape = w.objects.first().unwrap();
         let r = Ray::new(point(0., 0., -5.), vector(0., 0., 1.));
-        let xs = vec![Intersection::new(4., *shape), Intersection::new(6., *shape)];
-        let comps = prepare_computations(xs[0], r, xs);
+        let xs = vec![
+            Intersection::new(4., shape.clone()),
+            Intersection::new(6., shape.clone()),
+        ];
+        let comps = prepare_computations(xs[0].clone(), r, xs);
         let c = refracted_color(&w, comps, 5);
         assert_eq!(c, Color::new(0., 0., 0.));
     }
@@ -293,8 +323,8 @@ mod tests {
     #[test]
     fn test_refraction_max_depth() {
         let w = World::default();
-        let shape = w.objects[0];
-        match shape {
+        let shape = w.objects[0].clone();
+        match shape.clone() {
             ShapeEnum::Sphere(mut sphere) => {
                 sphere.material.transparency = 1.;
                 sphere.material.refractive_index = 1.5;
@@ -303,8 +333,11 @@ mod tests {
         }
 
         let r = Ray::new(point(0., 0., -5.), vector(0., 0., 1.));
-        let xs = vec![Intersection::new(4., shape), Intersection::new(6., shape)];
-        let comps = prepare_computations(xs[0], r, xs);
+        let xs = vec![
+            Intersection::new(4., shape.clone()),
+            Intersection::new(6., shape),
+        ];
+        let comps = prepare_computations(xs[0].clone(), r, xs);
         let c = refracted_color(&w, comps, 0);
         assert_eq!(c, Color::new(0., 0., 0.));
     }
@@ -318,11 +351,11 @@ mod tests {
         }
         let r = Ray::new(point(0., 0., f32::sqrt(2.) / 2.), vector(0., 1., 0.));
         let xs = vec![
-            Intersection::new(-f32::sqrt(2.) / 2., w.objects[0]),
-            Intersection::new(f32::sqrt(2.) / 2., w.objects[0]),
+            Intersection::new(-f32::sqrt(2.) / 2., w.objects[0].clone()),
+            Intersection::new(f32::sqrt(2.) / 2., w.objects[0].clone()),
         ];
 
-        let comps = prepare_computations(xs[1], r, xs);
+        let comps = prepare_computations(xs[1].clone(), r, xs);
         let c = refracted_color(&w, comps, 5);
         assert_eq!(c, Color::new(0., 0., 0.));
     }
@@ -343,13 +376,13 @@ mod tests {
 
         let r = Ray::new(point(0., 0., 0.1), vector(0., 1., 0.));
         let xs = vec![
-            Intersection::new(-0.9899, w.objects[0]),
-            Intersection::new(-0.4899, w.objects[1]),
-            Intersection::new(0.4899, w.objects[1]),
-            Intersection::new(0.9899, w.objects[0]),
+            Intersection::new(-0.9899, w.objects[0].clone()),
+            Intersection::new(-0.4899, w.objects[1].clone()),
+            Intersection::new(0.4899, w.objects[1].clone()),
+            Intersection::new(0.9899, w.objects[0].clone()),
         ];
 
-        let comps = prepare_computations(xs[2], r, xs);
+        let comps = prepare_computations(xs[2].clone(), r, xs);
         let c = refracted_color(&w, comps, 5);
         assert_eq!(c, Color::new(0., 0.99878335, 0.04724201));
     }
@@ -361,7 +394,7 @@ mod tests {
         floor.set_transform(translation(0., -1., 0.));
         floor.material.transparency = 0.5;
         floor.material.refractive_index = 1.5;
-        w.objects.push(ShapeEnum::Plane(floor));
+        w.objects.push(ShapeEnum::Plane(floor.clone()));
 
         let mut ball = Sphere::default();
         ball.material.color = Color::new(1., 0., 0.);
@@ -375,7 +408,7 @@ mod tests {
             vector(0., -f32::sqrt(2.) / 2., f32::sqrt(2.) / 2.),
         );
         let xs = vec![Intersection::new(f32::sqrt(2.), ShapeEnum::Plane(floor))];
-        let comps = prepare_computations(xs[0], r, xs);
+        let comps = prepare_computations(xs[0].clone(), r, xs);
         let color = shade_hit(&w, comps, 5);
         assert_eq!(color, Color::new(0.93642, 0.68642, 0.68642));
     }
@@ -392,7 +425,7 @@ mod tests {
         floor.material.reflective = 0.5;
         floor.material.transparency = 0.5;
         floor.material.refractive_index = 1.5;
-        w.objects.push(ShapeEnum::Plane(floor));
+        w.objects.push(ShapeEnum::Plane(floor.clone()));
 
         let mut ball = Sphere::default();
         ball.material.color = Color::new(1., 0., 0.);
@@ -401,8 +434,43 @@ mod tests {
         w.objects.push(ShapeEnum::Sphere(ball));
 
         let xs = vec![Intersection::new(f32::sqrt(2.), ShapeEnum::Plane(floor))];
-        let comps = prepare_computations(xs[0], r, xs);
+        let comps = prepare_computations(xs[0].clone(), r, xs);
         let color = shade_hit(&w, comps, 5);
         assert_eq!(color, Color::new(0.93391, 0.69643, 0.69243));
     }
+
+    #[test]
+    fn test_reflective_pattern_disables_reflection() {
+        let mut w = World::default();
+        let material = Material {
+            reflective: 0.5,
+            reflective_pattern: Some(Pattern::new(PatternType::Stripe(StripePattern::new(
+                Color::new(0., 0., 0.),
+                Color::new(1., 1., 1.),
+            )))),
+            ..Material::default()
+        };
+        let shape = ShapeEnum::Plane(Plane {
+            transform: translation(0., -1., 0.),
+            material,
+        });
+        w.objects.push(shape.clone());
+
+        let r = Ray::new(
+            point(0.5, 0., -3.),
+            vector(0., -f32::sqrt(2.) / 2., f32::sqrt(2.) / 2.),
+        );
+        let i = Intersection::new(f32::sqrt(2.), shape);
+        let comps = prepare_computations(i.clone(), r, vec![i.clone()]);
+        let color = reflected_color(&w, comps, 5);
+        assert_eq!(color, Color::new(0., 0., 0.));
+
+        let r = Ray::new(
+            point(-0.5, 0., -3.),
+            vector(0., -f32::sqrt(2.) / 2., f32::sqrt(2.) / 2.),
+        );
+        let comps = prepare_computations(i.clone(), r, vec![i]);
+        let color = reflected_color(&w, comps, 5);
+        assert!(color.red > 0.);
+    }
 }